@@ -0,0 +1,276 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use presale_client::{instruction, pda, rpc::fetch_presale, state::Presale};
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use crate::cluster::Cluster;
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Move the sale to its next stage: not started → private → public → ended
+    SetStage(SaleArgs),
+
+    /// Set the price of the current stage
+    UpdateSalePrice {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Micro-USD per token, e.g. 3500 = $0.0035
+        #[arg(long)]
+        price: u64,
+    },
+
+    /// Set the referral reward rates
+    SetReferralRate {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Percent paid for regular referrers
+        #[arg(long)]
+        regular: u8,
+
+        /// Percent paid for influencers
+        #[arg(long)]
+        influencer: u8,
+    },
+
+    /// Set the private and public sale durations
+    UpdateSalePeriod {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Private sale length in days
+        #[arg(long)]
+        private_days: i64,
+
+        /// Public sale length in days
+        #[arg(long)]
+        public_days: i64,
+    },
+
+    /// Choose how referrers are paid
+    SetReferralRewardMode {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// 0 = sale tokens, 1 = payment currency (SOL / USDC)
+        #[arg(long)]
+        mode: u8,
+    },
+
+    /// Set the bonus buyers get when they use a referral code
+    SetBuyerBonusRate {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Percent of the purchased tokens
+        #[arg(long)]
+        rate: u8,
+    },
+
+    /// Set the SOL price SOL purchases are charged at
+    SetSolPrice {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Whole USD per SOL, e.g. 200
+        #[arg(long)]
+        price: u64,
+    },
+
+    /// Set the backend key that co-signs Web2 purchases
+    SetPaymentAuthority {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Backend signer, the default pubkey disables Web2 purchases
+        #[arg(long)]
+        authority: Pubkey,
+    },
+
+    /// Set the vesting schedule for one sale stage
+    SetVestingSchedule {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// 1 = private sale, 2 = public sale
+        #[arg(long)]
+        stage: u8,
+
+        /// Percent unlocked at TGE
+        #[arg(long)]
+        tge_percent: u8,
+
+        #[arg(long)]
+        cliff_seconds: i64,
+
+        #[arg(long)]
+        vesting_duration: i64,
+    },
+
+    /// Stock `presale_wallet` from the admin's token account
+    DepositSaleTokens(DepositArgs),
+
+    /// Stock `referral_wallet` from the admin's token account
+    DepositRewardTokens(DepositArgs),
+
+    /// Dispose of unsold tokens once the sale has ended
+    FinalizePresale {
+        #[command(flatten)]
+        sale: SaleArgs,
+
+        /// Token account that receives the liquidity share
+        #[arg(long)]
+        liquidity_wallet: Pubkey,
+    },
+}
+
+#[derive(Args)]
+pub struct SaleArgs {
+    /// Sale id handed out by `ProgramConfig`
+    #[arg(long)]
+    sale_id: u64,
+}
+
+#[derive(Args)]
+pub struct DepositArgs {
+    #[command(flatten)]
+    sale: SaleArgs,
+
+    /// Whole tokens
+    #[arg(long)]
+    amount: u64,
+
+    /// Source token account [default: the admin's associated token account]
+    #[arg(long)]
+    from: Option<Pubkey>,
+}
+
+pub fn run(cluster: &Cluster, command: AdminCommand) -> Result<()> {
+    let signer = cluster.signer()?;
+    let admin = signer.pubkey();
+
+    let sale_id = match &command {
+        AdminCommand::SetStage(sale)
+        | AdminCommand::UpdateSalePrice { sale, .. }
+        | AdminCommand::SetReferralRate { sale, .. }
+        | AdminCommand::UpdateSalePeriod { sale, .. }
+        | AdminCommand::SetReferralRewardMode { sale, .. }
+        | AdminCommand::SetBuyerBonusRate { sale, .. }
+        | AdminCommand::SetSolPrice { sale, .. }
+        | AdminCommand::SetPaymentAuthority { sale, .. }
+        | AdminCommand::SetVestingSchedule { sale, .. }
+        | AdminCommand::FinalizePresale { sale, .. } => sale.sale_id,
+        AdminCommand::DepositSaleTokens(deposit) | AdminCommand::DepositRewardTokens(deposit) => {
+            deposit.sale.sale_id
+        }
+    };
+    let presale = pda::presale(sale_id).0;
+    let state = fetch_presale(&cluster.rpc, sale_id)
+        .with_context(|| format!("fetching sale {sale_id}"))?;
+    if state.admin != admin {
+        bail!("{admin} is not the admin of sale {sale_id}, {} is", state.admin);
+    }
+
+    let ix = build(command, admin, presale, &state);
+    cluster.submit(&[ix], signer.as_ref())
+}
+
+fn build(command: AdminCommand, admin: Pubkey, presale: Pubkey, state: &Presale) -> Instruction {
+    match command {
+        AdminCommand::SetStage(_) => instruction::SetStage { admin, presale }.instruction(),
+        AdminCommand::UpdateSalePrice { price, .. } => instruction::UpdateSalePrice {
+            admin,
+            presale,
+            new_price: price,
+        }
+        .instruction(),
+        AdminCommand::SetReferralRate { regular, influencer, .. } => instruction::SetReferralRate {
+            admin,
+            presale,
+            regular_referral_rate: regular,
+            influencer_referral_rate: influencer,
+        }
+        .instruction(),
+        AdminCommand::UpdateSalePeriod { private_days, public_days, .. } => instruction::UpdateSalePeriod {
+            admin,
+            presale,
+            new_private_sale_duration: private_days,
+            new_public_sale_duration: public_days,
+        }
+        .instruction(),
+        AdminCommand::SetReferralRewardMode { mode, .. } => instruction::SetReferralRewardMode {
+            admin,
+            presale,
+            referral_reward_mode: mode,
+        }
+        .instruction(),
+        AdminCommand::SetBuyerBonusRate { rate, .. } => instruction::SetBuyerBonusRate {
+            admin,
+            presale,
+            buyer_bonus_rate: rate,
+        }
+        .instruction(),
+        AdminCommand::SetSolPrice { price, .. } => instruction::SetSolPrice {
+            admin,
+            presale,
+            sol_price_in_usd: price,
+        }
+        .instruction(),
+        AdminCommand::SetPaymentAuthority { authority, .. } => instruction::SetPaymentAuthority {
+            admin,
+            presale,
+            payment_authority: authority,
+        }
+        .instruction(),
+        AdminCommand::SetVestingSchedule {
+            stage,
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+            ..
+        } => instruction::SetVestingSchedule {
+            admin,
+            presale,
+            sale_stage: stage,
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+        }
+        .instruction(),
+        AdminCommand::DepositSaleTokens(deposit) => instruction::DepositSaleTokens {
+            admin,
+            presale,
+            presale_wallet: state.presale_wallet,
+            admin_token_account: deposit.source(&admin, state),
+            amount: deposit.amount,
+        }
+        .instruction(),
+        AdminCommand::DepositRewardTokens(deposit) => instruction::DepositRewardTokens {
+            admin,
+            presale,
+            referral_wallet: state.referral_wallet,
+            admin_token_account: deposit.source(&admin, state),
+            amount: deposit.amount,
+        }
+        .instruction(),
+        AdminCommand::FinalizePresale { liquidity_wallet, .. } => instruction::FinalizePresale {
+            admin,
+            presale,
+            presale_wallet: state.presale_wallet,
+            referral_wallet: state.referral_wallet,
+            liquidity_wallet,
+            treasury_wallet: state.treasury_wallet,
+            token_mint: state.token_mint,
+        }
+        .instruction(),
+    }
+}
+
+impl DepositArgs {
+    fn source(&self, admin: &Pubkey, state: &Presale) -> Pubkey {
+        self.from
+            .unwrap_or_else(|| pda::associated_token_address(admin, &state.token_mint))
+    }
+}
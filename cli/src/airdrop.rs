@@ -0,0 +1,118 @@
+use std::{collections::HashSet, fs::File, path::PathBuf, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use presale_client::merkle::{leaf_hash, MerkleTree};
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+
+#[derive(Subcommand)]
+pub enum AirdropCommand {
+    /// Build a Merkle root and per-claimant proofs from a `claimant,amount` CSV
+    BuildRoot(BuildRootArgs),
+}
+
+#[derive(Args)]
+pub struct BuildRootArgs {
+    /// CSV file with a `claimant,amount` header, amounts in whole tokens
+    #[arg(long)]
+    csv: PathBuf,
+
+    /// Where to write the root and proofs as JSON
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct CsvRow {
+    claimant: String,
+    amount: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AirdropTree {
+    merkle_root: String,
+    total_amount: u64,
+    num_recipients: u32,
+    claims: Vec<Claim>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Claim {
+    index: u64,
+    claimant: String,
+    amount: u64,
+    proof: Vec<String>,
+}
+
+pub fn run(command: AirdropCommand) -> Result<()> {
+    match command {
+        AirdropCommand::BuildRoot(args) => build_root(args),
+    }
+}
+
+fn build_root(args: BuildRootArgs) -> Result<()> {
+    let file = File::open(&args.csv).with_context(|| format!("opening {}", args.csv.display()))?;
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(file);
+
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    for (row, record) in reader.deserialize::<CsvRow>().enumerate() {
+        let record = record.with_context(|| format!("reading CSV row {}", row + 1))?;
+        let claimant = Pubkey::from_str(&record.claimant)
+            .with_context(|| format!("row {}: invalid claimant {}", row + 1, record.claimant))?;
+        if record.amount == 0 {
+            bail!("row {}: amount must be greater than zero", row + 1);
+        }
+        if !seen.insert(claimant) {
+            bail!("row {}: duplicate claimant {}", row + 1, claimant);
+        }
+        entries.push((claimant, record.amount));
+    }
+
+    if entries.is_empty() {
+        bail!("{} has no claimants", args.csv.display());
+    }
+
+    let leaves: Vec<[u8; 32]> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, (claimant, amount))| leaf_hash(index as u64, claimant, *amount))
+        .collect();
+    let tree = MerkleTree::new(leaves);
+    let root = tree.root();
+
+    let claims = entries
+        .iter()
+        .enumerate()
+        .map(|(index, (claimant, amount))| Claim {
+            index: index as u64,
+            claimant: claimant.to_string(),
+            amount: *amount,
+            proof: tree.proof(index).iter().map(to_hex).collect(),
+        })
+        .collect();
+
+    let airdrop = AirdropTree {
+        merkle_root: to_hex(&root),
+        total_amount: entries.iter().map(|(_, amount)| amount).sum(),
+        num_recipients: u32::try_from(entries.len()).context("too many claimants")?,
+        claims,
+    };
+
+    let out = File::create(&args.out).with_context(|| format!("creating {}", args.out.display()))?;
+    serde_json::to_writer_pretty(out, &airdrop)?;
+
+    println!("Merkle root:    {}", airdrop.merkle_root);
+    println!("Recipients:     {}", airdrop.num_recipients);
+    println!("Total amount:   {}", airdrop.total_amount);
+    println!("Proofs written: {}", args.out.display());
+
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
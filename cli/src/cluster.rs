@@ -0,0 +1,105 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use presale_client::event::parse_logs;
+use solana_instruction::Instruction;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::signer;
+
+#[derive(Args)]
+pub struct ClusterArgs {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "PRESALE_RPC_URL",
+        default_value = "https://api.devnet.solana.com"
+    )]
+    url: String,
+
+    /// Signer: a keypair file or `usb://ledger[?key=<account>[/<change>]]` [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', global = true, env = "PRESALE_KEYPAIR")]
+    keypair: Option<String>,
+
+    /// Simulate the transaction and print its logs instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+}
+
+pub struct Cluster {
+    pub rpc: RpcClient,
+    keypair: Option<String>,
+    dry_run: bool,
+}
+
+impl Cluster {
+    pub fn new(args: ClusterArgs) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(args.url, Default::default()),
+            keypair: args.keypair,
+            dry_run: args.dry_run,
+        }
+    }
+
+    pub fn signer(&self) -> Result<Box<dyn Signer>> {
+        signer::load(self.keypair.as_deref())
+    }
+
+    /// Sends `instructions` in one transaction paid and signed by `signer`, or only simulates
+    /// them with `--dry-run`.
+    pub fn submit(&self, instructions: &[Instruction], signer: &dyn Signer) -> Result<()> {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&signer.pubkey()));
+
+        if self.dry_run {
+            return self.simulate(&transaction);
+        }
+
+        let blockhash = self.rpc.get_latest_blockhash().context("fetching a recent blockhash")?;
+        transaction.try_sign(&[signer], blockhash).context("signing the transaction")?;
+        let signature = self
+            .rpc
+            .send_and_confirm_transaction_with_spinner(&transaction)
+            .context("sending the transaction")?;
+
+        println!("Signature: {signature}");
+        Ok(())
+    }
+
+    /// Simulation skips signature checks, so a Ledger is never asked to sign a dry run.
+    fn simulate(&self, transaction: &Transaction) -> Result<()> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc.commitment()),
+            ..Default::default()
+        };
+        let result = self
+            .rpc
+            .simulate_transaction_with_config(transaction, config)
+            .context("simulating the transaction")?
+            .value;
+
+        let logs = result.logs.unwrap_or_default();
+        for line in &logs {
+            println!("  {line}");
+        }
+        for event in parse_logs(&logs) {
+            println!("Event: {event:?}");
+        }
+        if let Some(units) = result.units_consumed {
+            println!("Compute units: {units}");
+        }
+
+        match result.err {
+            Some(err) => bail!("simulation failed: {err}"),
+            None => {
+                println!("Simulation succeeded, nothing was sent");
+                Ok(())
+            }
+        }
+    }
+}
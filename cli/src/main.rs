@@ -0,0 +1,42 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+mod admin;
+mod airdrop;
+mod cluster;
+mod signer;
+mod status;
+
+#[derive(Parser)]
+#[command(name = "presale-cli", version, about = "Operate the DYAWN presale program")]
+struct Cli {
+    #[command(flatten)]
+    cluster: cluster::ClusterArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Merkle airdrop tooling
+    #[command(subcommand)]
+    Airdrop(airdrop::AirdropCommand),
+
+    /// Print a sale's state and wallet balances
+    Status(status::StatusArgs),
+
+    #[command(flatten)]
+    Admin(admin::AdminCommand),
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let cluster = cluster::Cluster::new(cli.cluster);
+
+    match cli.command {
+        Command::Airdrop(command) => airdrop::run(command),
+        Command::Status(args) => status::run(&cluster, args),
+        Command::Admin(command) => admin::run(&cluster, command),
+    }
+}
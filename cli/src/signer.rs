@@ -0,0 +1,49 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use solana_keypair::read_keypair_file;
+use solana_signer::Signer;
+
+/// Loads the signer from a keypair file or a `usb://ledger` URI.
+pub fn load(path: Option<&str>) -> Result<Box<dyn Signer>> {
+    let path = match path {
+        Some(path) => path.to_string(),
+        None => default_keypair_path()?.display().to_string(),
+    };
+
+    if path.starts_with("usb://") {
+        return ledger(&path);
+    }
+
+    let keypair = read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {path}: {err}"))?;
+    Ok(Box::new(keypair))
+}
+
+/// Same default as the Solana CLI.
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = env::var_os("HOME").context("HOME is not set, pass --keypair")?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+#[cfg(feature = "ledger")]
+fn ledger(path: &str) -> Result<Box<dyn Signer>> {
+    use solana_derivation_path::DerivationPath;
+    use solana_remote_wallet::{
+        locator::Locator, remote_keypair::generate_remote_keypair, remote_wallet::maybe_wallet_manager,
+    };
+    use uriparse::URIReference;
+
+    let uri = URIReference::try_from(path).with_context(|| format!("parsing {path}"))?;
+    let locator = Locator::new_from_uri(&uri)?;
+    let derivation_path = DerivationPath::from_uri_key_query(&uri)?.unwrap_or_default();
+    let wallet_manager = maybe_wallet_manager()?.context("no hardware wallet found")?;
+
+    // Ask for confirmation on the device before each signature
+    let keypair = generate_remote_keypair(locator, derivation_path, &wallet_manager, true, "keypair")?;
+    Ok(Box::new(keypair))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn ledger(path: &str) -> Result<Box<dyn Signer>> {
+    anyhow::bail!("{path}: this build has no Ledger support, rebuild with `--features ledger`")
+}
@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use presale_client::{pda, rpc::fetch_presale, state::Presale};
+use solana_pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::cluster::Cluster;
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Sale id handed out by `ProgramConfig`
+    #[arg(long)]
+    sale_id: u64,
+}
+
+pub fn run(cluster: &Cluster, args: StatusArgs) -> Result<()> {
+    let rpc = &cluster.rpc;
+    let address = pda::presale(args.sale_id).0;
+    let presale = fetch_presale(rpc, args.sale_id)
+        .with_context(|| format!("fetching sale {}", args.sale_id))?;
+
+    println!("Sale {} ({address})", presale.sale_id);
+    row("Admin", presale.admin);
+    row("Token mint", presale.token_mint);
+    row("Stage", stage_name(presale.sale_stage));
+    row("Layout version", presale.version);
+
+    section("Pricing");
+    row("Private price", usd(presale.private_price));
+    row("Public price", usd(presale.public_price));
+    row("Current price", usd(presale.current_price));
+    row("SOL price", format!("${}", presale.sol_price_in_usd));
+    row("Web2 co-signer", presale.payment_authority);
+
+    section("Timeline");
+    if presale.sale_stage == 0 {
+        row("Started", "not yet");
+    } else {
+        let private_end = presale.presale_start + presale.private_sale_duration;
+        row("Started", presale.presale_start);
+        row("Private sale ends", private_end);
+        row("Public sale ends", private_end + presale.public_sale_duration);
+    }
+    row("Private sale length", days(presale.private_sale_duration));
+    row("Public sale length", days(presale.public_sale_duration));
+    if presale.tge_timestamp > 0 {
+        row("TGE", presale.tge_timestamp);
+    }
+
+    section("Supply (whole tokens)");
+    row("Sale allocation", presale.sale_allocation);
+    row("Deposited", presale.total_deposited);
+    row("Sold", presale.total_sold);
+    row("Purchases", presale.purchase_count);
+    row("Platform fee tokens", presale.platform_tokens_charged);
+    row("Remaining", presale.remaining_tokens());
+    row("Reward deposited", presale.total_reward_deposited);
+    row("Referral charged", presale.referral_charged);
+
+    section("Raised");
+    row("SOL", sol(presale.total_raised_lamports));
+    row("Stable coins", usd(presale.total_raised_stable));
+
+    section("Referrals");
+    row("Regular rate", format!("{}%", presale.regular_referral_rate));
+    row("Influencer rate", format!("{}%", presale.influencer_referral_rate));
+    row("Buyer bonus", format!("{}%", presale.buyer_bonus_rate));
+    row(
+        "Reward mode",
+        match presale.referral_reward_mode {
+            0 => "sale tokens",
+            _ => "payment currency",
+        },
+    );
+
+    section("Wallets");
+    row("Presale wallet", token_balance(rpc, &presale.presale_wallet));
+    row("Referral wallet", token_balance(rpc, &presale.referral_wallet));
+    row("Treasury wallet", token_balance(rpc, &presale.treasury_wallet));
+    row("Merchant wallet", sol_balance(rpc, &presale.merchant_wallet));
+    row("Reserved for claims", presale.reserved_tokens);
+
+    section("Platform");
+    row("Fee recipient", presale.platform_fee_recipient);
+    row("Raise fee", bps(presale.platform_raise_fee_bps));
+    row("Token fee", bps(presale.platform_token_fee_bps));
+
+    print_end_of_sale(&presale);
+
+    Ok(())
+}
+
+fn print_end_of_sale(presale: &Presale) {
+    let disposition = &presale.unsold_disposition;
+    section("End of sale");
+    row(
+        "Unsold disposition",
+        format!(
+            "{}% burn, {}% treasury, {}% liquidity",
+            disposition.burn_percent, disposition.treasury_percent, disposition.liquidity_percent
+        ),
+    );
+    row("Pool created", presale.pool_created);
+
+    let pool = &presale.pool_config;
+    if pool.enabled {
+        row("Pool quote mint", pool.quote_mint);
+        row("Pool raise share", format!("{}%", pool.raise_percent));
+        row("Pool token amount", pool.token_amount);
+        row("LP lock", days(pool.lp_lock_duration));
+    }
+}
+
+fn section(title: &str) {
+    println!("\n{title}");
+}
+
+fn row(label: &str, value: impl std::fmt::Display) {
+    println!("  {:<22}{value}", format!("{label}:"));
+}
+
+fn stage_name(stage: u8) -> &'static str {
+    match stage {
+        0 => "not started",
+        1 => "private sale",
+        2 => "public sale",
+        3 => "ended",
+        _ => "unknown",
+    }
+}
+
+/// Prices and stable coin amounts are in micro-USD.
+fn usd(micros: u64) -> String {
+    let fraction = format!("{:06}", micros % 1_000_000);
+    let fraction = fraction.trim_end_matches('0');
+    let fraction = if fraction.len() < 2 { format!("{fraction:0<2}") } else { fraction.to_string() };
+    format!("${}.{fraction}", micros / 1_000_000)
+}
+
+fn sol(lamports: u64) -> String {
+    format!("{} SOL", lamports as f64 / 1e9)
+}
+
+fn bps(bps: u16) -> String {
+    format!("{}%", bps as f64 / 100.0)
+}
+
+fn days(seconds: i64) -> String {
+    format!("{} days", seconds as f64 / 86_400.0)
+}
+
+fn token_balance(rpc: &RpcClient, account: &Pubkey) -> String {
+    match rpc.get_token_account_balance(account) {
+        Ok(balance) => format!("{} ({account})", balance.ui_amount_string),
+        Err(err) => format!("unavailable: {err} ({account})"),
+    }
+}
+
+fn sol_balance(rpc: &RpcClient, account: &Pubkey) -> String {
+    match rpc.get_balance(account) {
+        Ok(lamports) => format!("{} ({account})", sol(lamports)),
+        Err(err) => format!("unavailable: {err} ({account})"),
+    }
+}
@@ -0,0 +1,762 @@
+//! Events emitted with `emit!`, decoded from `Program data:` log lines.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    discriminator,
+    state::{LockupOption, PoolConfig, VestingSchedule},
+};
+
+macro_rules! events {
+    ($(
+        $variant:ident($name:ident) {
+            $($field:ident: $ty:ty,)*
+        }
+    )*) => {
+        $(
+            #[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+            }
+        )*
+
+        /// Any event the presale program emits.
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum PresaleEvent {
+            $($variant($name),)*
+        }
+
+        impl PresaleEvent {
+            /// Decodes one event payload: 8-byte discriminator followed by the Borsh fields.
+            /// Returns `None` for unknown discriminators or malformed data.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                let (tag, mut body) = data.split_at_checked(8)?;
+                $(
+                    if tag == discriminator("event", stringify!($name)) {
+                        return $name::deserialize(&mut body).ok().map(Self::$variant);
+                    }
+                )*
+                None
+            }
+
+            /// Struct name in the program, e.g. `"BuyTokensEvent"`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant(_) => stringify!($name),)*
+                }
+            }
+
+            /// Event fields as a JSON object keyed by the program's field names.
+            #[cfg(feature = "json")]
+            pub fn to_json(&self) -> serde_json::Value {
+                let mut fields = serde_json::Map::new();
+                match self {
+                    $(Self::$variant(event) => {
+                        $(fields.insert(stringify!($field).into(), json::ToJson::to_json(&event.$field));)*
+                    })*
+                }
+                serde_json::Value::Object(fields)
+            }
+        }
+    };
+}
+
+macro_rules! admin_actions {
+    ($(
+        $variant:ident {
+            $($field:ident: $ty:ty,)*
+        }
+    )*) => {
+        /// What an `AdminActionEvent` changed, with old and new values where state was overwritten.
+        #[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+        pub enum AdminAction {
+            $($variant { $($field: $ty,)* },)*
+        }
+
+        impl AdminAction {
+            /// Variant name in the program, e.g. `"SetStage"`.
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(Self::$variant { .. } => stringify!($variant),)*
+                }
+            }
+        }
+
+        /// The variant name under `kind`, next to its fields.
+        #[cfg(feature = "json")]
+        impl json::ToJson for AdminAction {
+            fn to_json(&self) -> serde_json::Value {
+                let mut fields = serde_json::Map::new();
+                fields.insert("kind".into(), self.kind().into());
+                match self {
+                    $(Self::$variant { $($field,)* } => {
+                        $(fields.insert(stringify!($field).into(), json::ToJson::to_json($field));)*
+                    })*
+                }
+                serde_json::Value::Object(fields)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use serde_json::{json, Value};
+    use solana_pubkey::Pubkey;
+
+    use crate::state::{LockupOption, PoolConfig, VestingSchedule};
+
+    /// Pubkeys as base58, hashes as hex, integers as JSON numbers.
+    pub trait ToJson {
+        fn to_json(&self) -> Value;
+    }
+
+    macro_rules! number {
+        ($($ty:ty),*) => {
+            $(impl ToJson for $ty {
+                fn to_json(&self) -> Value {
+                    Value::from(*self)
+                }
+            })*
+        };
+    }
+
+    number!(bool, u8, u16, u32, u64, i64);
+
+    impl ToJson for Pubkey {
+        fn to_json(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+
+    impl ToJson for [u8; 32] {
+        fn to_json(&self) -> Value {
+            Value::from(self.iter().map(|b| format!("{b:02x}")).collect::<String>())
+        }
+    }
+
+    impl ToJson for VestingSchedule {
+        fn to_json(&self) -> Value {
+            json!({
+                "tge_percent": self.tge_percent,
+                "cliff_seconds": self.cliff_seconds,
+                "vesting_duration": self.vesting_duration,
+            })
+        }
+    }
+
+    impl ToJson for LockupOption {
+        fn to_json(&self) -> Value {
+            json!({
+                "duration": self.duration,
+                "multiplier_bps": self.multiplier_bps,
+            })
+        }
+    }
+
+    impl ToJson for PoolConfig {
+        fn to_json(&self) -> Value {
+            json!({
+                "enabled": self.enabled,
+                "quote_mint": self.quote_mint.to_string(),
+                "raise_percent": self.raise_percent,
+                "token_amount": self.token_amount,
+                "lp_lock_duration": self.lp_lock_duration,
+            })
+        }
+    }
+
+    impl<T: ToJson> ToJson for Vec<T> {
+        fn to_json(&self) -> Value {
+            Value::Array(self.iter().map(ToJson::to_json).collect())
+        }
+    }
+}
+
+impl PresaleEvent {
+    /// The sale the event was emitted for, when the event records it.
+    pub fn presale(&self) -> Option<Pubkey> {
+        match self {
+            Self::BuyTokens(event) => Some(event.presale),
+            Self::BuyTokensByStableCoin(event) => Some(event.presale),
+            Self::UpdateSalePrice(event) => Some(event.presale),
+            Self::ReferralReward(event) => Some(event.presale),
+            Self::ReferralCommission(event) => Some(event.presale),
+            Self::PlatformFee(event) => Some(event.presale),
+            Self::SetReferralRewardMode(event) => Some(event.presale),
+            Self::SetReferralRate(event) => Some(event.presale),
+            Self::SetBuyerBonusRate(event) => Some(event.presale),
+            Self::SetSolPrice(event) => Some(event.presale),
+            Self::SetPaymentAuthority(event) => Some(event.presale),
+            Self::MigratePresale(event) => Some(event.presale),
+            Self::DeliverV1Allocation(event) => Some(event.presale),
+            Self::DepositTokens(event) => Some(event.presale),
+            Self::SetVestingSchedule(event) => Some(event.presale),
+            Self::ClaimTokens(event) => Some(event.presale),
+            Self::CreateGrant(event) => Some(event.presale),
+            Self::ClaimGrant(event) => Some(event.presale),
+            Self::RevokeGrant(event) => Some(event.presale),
+            Self::CreateAirdrop(event) => Some(event.presale),
+            Self::ClaimAirdrop(event) => Some(event.presale),
+            Self::ClawbackAirdrop(event) => Some(event.presale),
+            Self::SetEmissionRate(event) => Some(event.presale),
+            Self::SetLockupOptions(event) => Some(event.presale),
+            Self::SetPrivateBuyerBonus(event) => Some(event.presale),
+            Self::FundRewards(event) => Some(event.presale),
+            Self::Stake(event) => Some(event.presale),
+            Self::Unstake(event) => Some(event.presale),
+            Self::ClaimRewards(event) => Some(event.presale),
+            Self::SetPoolConfig(event) => Some(event.presale),
+            Self::PoolSeeded(event) => Some(event.presale),
+            Self::LpLocked(event) => Some(event.presale),
+            Self::LpUnlocked(event) => Some(event.presale),
+            Self::FinalizePresale(event) => Some(event.presale),
+            Self::AdminAction(event) => Some(event.presale),
+            _ => None,
+        }
+    }
+
+    /// Stage the sale was in when the event was emitted, when the event records it.
+    pub fn sale_stage(&self) -> Option<u8> {
+        match self {
+            Self::BuyTokens(event) => Some(event.sale_stage),
+            Self::UpdateSalePrice(event) => Some(event.sale_stage),
+            Self::BuyTokensByStableCoin(event) => Some(event.sale_stage),
+            Self::SetReferralRewardMode(event) => Some(event.sale_stage),
+            Self::SetReferralRate(event) => Some(event.sale_stage),
+            Self::SetBuyerBonusRate(event) => Some(event.sale_stage),
+            Self::SetSolPrice(event) => Some(event.sale_stage),
+            Self::SetPaymentAuthority(event) => Some(event.sale_stage),
+            Self::DepositTokens(event) => Some(event.sale_stage),
+            Self::SetVestingSchedule(event) => Some(event.current_stage),
+            Self::CreateGrant(event) => Some(event.sale_stage),
+            Self::ClaimGrant(event) => Some(event.sale_stage),
+            Self::RevokeGrant(event) => Some(event.sale_stage),
+            Self::CreateAirdrop(event) => Some(event.sale_stage),
+            Self::ClaimAirdrop(event) => Some(event.sale_stage),
+            Self::ClawbackAirdrop(event) => Some(event.sale_stage),
+            Self::SetPoolConfig(event) => Some(event.sale_stage),
+            Self::PoolSeeded(event) => Some(event.sale_stage),
+            Self::LpLocked(event) => Some(event.sale_stage),
+            Self::LpUnlocked(event) => Some(event.sale_stage),
+            Self::FinalizePresale(event) => Some(event.sale_stage),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes every presale event in a transaction's log messages, in emission order.
+///
+/// Only `Program data:` lines written while the presale program is the innermost running
+/// program are considered, so events from CPI'd programs are skipped.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<PresaleEvent> {
+    let program_id = crate::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                let event = STANDARD.decode(data).ok().and_then(|bytes| PresaleEvent::decode(&bytes));
+                events.extend(event);
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let (Some(id), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match status {
+                "invoke" => stack.push(id),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+events! {
+    BuyTokens(BuyTokensEvent) {
+        buyer: Pubkey,
+        tokens_purchased: u64,
+        bonus_tokens: u64,
+        sol_spent: u64,
+        sol_price_in_usd: u64,
+        payment_type: u8,
+        presale: Pubkey,
+        purchase_seq: u64,
+        sale_stage: u8,
+        price: u64,
+        referrer: Pubkey,
+        total_sold: u64,
+        referral_charged: u64,
+        platform_tokens_charged: u64,
+        total_raised_lamports: u64,
+        slot: u64,
+    }
+    UpdateSalePrice(UpdateSalePriceEvent) {
+        admin: Pubkey,
+        new_price: u64,
+        sale_stage: u8,
+        presale: Pubkey,
+        slot: u64,
+    }
+    BuyTokensByStableCoin(BuyTokensByStableCoinEvent) {
+        buyer: Pubkey,
+        tokens_purchased: u64,
+        bonus_tokens: u64,
+        stable_coin_amount: u64,
+        payment_type: u8,
+        presale: Pubkey,
+        purchase_seq: u64,
+        sale_stage: u8,
+        price: u64,
+        referrer: Pubkey,
+        total_sold: u64,
+        referral_charged: u64,
+        platform_tokens_charged: u64,
+        total_raised_stable: u64,
+        slot: u64,
+    }
+    ReferralReward(ReferralRewardEvent) {
+        referrer: Pubkey,
+        referred_buyer: Pubkey,
+        reward_amount: u64,
+        is_influencer: bool,
+        presale: Pubkey,
+        purchase_seq: u64,
+    }
+    ReferralCommission(ReferralCommissionEvent) {
+        referrer: Pubkey,
+        referred_buyer: Pubkey,
+        commission_amount: u64,
+        payment_currency: u8,
+        is_influencer: bool,
+        settled: bool,
+        presale: Pubkey,
+        purchase_seq: u64,
+    }
+    PlatformFee(PlatformFeeEvent) {
+        presale: Pubkey,
+        buyer: Pubkey,
+        fee_recipient: Pubkey,
+        payment_fee: u64,
+        payment_currency: u8,
+        token_fee: u64,
+        settled: bool,
+        purchase_seq: u64,
+    }
+    SetAcceptedMints(SetAcceptedMintsEvent) {
+        authority: Pubkey,
+        accepted_stable_mints: Vec<Pubkey>,
+    }
+    PlatformUpdated(PlatformUpdatedEvent) {
+        authority: Pubkey,
+        fee_recipient: Pubkey,
+        raise_fee_bps: u16,
+        token_fee_bps: u16,
+        allowed_mints: Vec<Pubkey>,
+    }
+    SetReferralRewardMode(SetReferralRewardModeEvent) {
+        admin: Pubkey,
+        referral_reward_mode: u8,
+        presale: Pubkey,
+        sale_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    SetReferralRate(SetReferralRateEvent) {
+        admin: Pubkey,
+        regular_referral_rate: u8,
+        influencer_referral_rate: u8,
+        presale: Pubkey,
+        sale_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    SetBuyerBonusRate(SetBuyerBonusRateEvent) {
+        admin: Pubkey,
+        buyer_bonus_rate: u8,
+        presale: Pubkey,
+        sale_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    SetSolPrice(SetSolPriceEvent) {
+        admin: Pubkey,
+        sol_price_in_usd: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    SetPaymentAuthority(SetPaymentAuthorityEvent) {
+        admin: Pubkey,
+        payment_authority: Pubkey,
+        presale: Pubkey,
+        sale_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    MigratePresale(MigratePresaleEvent) {
+        admin: Pubkey,
+        presale: Pubkey,
+        sale_id: u64,
+        version: u8,
+    }
+    DeliverV1Allocation(DeliverV1AllocationEvent) {
+        admin: Pubkey,
+        presale: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        v1_undelivered: u64,
+        reserved_tokens: u64,
+        slot: u64,
+    }
+    DepositTokens(DepositTokensEvent) {
+        admin: Pubkey,
+        wallet: Pubkey,
+        amount: u64,
+        total_deposited: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    SetVestingSchedule(SetVestingScheduleEvent) {
+        admin: Pubkey,
+        sale_stage: u8,
+        tge_percent: u8,
+        cliff_seconds: i64,
+        vesting_duration: i64,
+        presale: Pubkey,
+        current_stage: u8,
+        current_price: u64,
+        slot: u64,
+    }
+    ClaimTokens(ClaimTokensEvent) {
+        buyer: Pubkey,
+        amount: u64,
+        total_claimed: u64,
+        presale: Pubkey,
+        reserved_tokens: u64,
+        slot: u64,
+    }
+    CreateGrant(CreateGrantEvent) {
+        admin: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        start: i64,
+        tge_percent: u8,
+        cliff_seconds: i64,
+        vesting_duration: i64,
+        revocable: bool,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    ClaimGrant(ClaimGrantEvent) {
+        beneficiary: Pubkey,
+        amount: u64,
+        total_claimed: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    RevokeGrant(RevokeGrantEvent) {
+        admin: Pubkey,
+        beneficiary: Pubkey,
+        vested_amount: u64,
+        returned_amount: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    CreateAirdrop(CreateAirdropEvent) {
+        admin: Pubkey,
+        airdrop_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        num_recipients: u32,
+        expiry: i64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    ClaimAirdrop(ClaimAirdropEvent) {
+        airdrop_id: u64,
+        claimant: Pubkey,
+        index: u64,
+        amount: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    ClawbackAirdrop(ClawbackAirdropEvent) {
+        admin: Pubkey,
+        airdrop_id: u64,
+        unclaimed_amount: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    SetEmissionRate(SetEmissionRateEvent) {
+        admin: Pubkey,
+        reward_rate: u64,
+        presale: Pubkey,
+        slot: u64,
+    }
+    SetLockupOptions(SetLockupOptionsEvent) {
+        admin: Pubkey,
+        lockup_options: Vec<LockupOption>,
+        presale: Pubkey,
+        slot: u64,
+    }
+    SetPrivateBuyerBonus(SetPrivateBuyerBonusEvent) {
+        admin: Pubkey,
+        private_buyer_bonus_bps: u16,
+        presale: Pubkey,
+        slot: u64,
+    }
+    FundRewards(FundRewardsEvent) {
+        admin: Pubkey,
+        amount: u64,
+        reward_unallocated: u64,
+        presale: Pubkey,
+        slot: u64,
+    }
+    Stake(StakeEvent) {
+        owner: Pubkey,
+        amount: u64,
+        total_staked: u64,
+        multiplier_bps: u16,
+        lock_until: i64,
+        presale: Pubkey,
+        slot: u64,
+    }
+    Unstake(UnstakeEvent) {
+        owner: Pubkey,
+        amount: u64,
+        total_staked: u64,
+        presale: Pubkey,
+        slot: u64,
+    }
+    ClaimRewards(ClaimRewardsEvent) {
+        owner: Pubkey,
+        amount: u64,
+        presale: Pubkey,
+        slot: u64,
+    }
+    SetPoolConfig(SetPoolConfigEvent) {
+        admin: Pubkey,
+        enabled: bool,
+        quote_mint: Pubkey,
+        raise_percent: u8,
+        token_amount: u64,
+        lp_lock_duration: i64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    PoolSeeded(PoolSeededEvent) {
+        admin: Pubkey,
+        pool_state: Pubkey,
+        lp_mint: Pubkey,
+        quote_mint: Pubkey,
+        token_amount: u64,
+        quote_amount: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    LpLocked(LpLockedEvent) {
+        pool_state: Pubkey,
+        lp_mint: Pubkey,
+        amount: u64,
+        unlock_timestamp: i64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    LpUnlocked(LpUnlockedEvent) {
+        admin: Pubkey,
+        lp_mint: Pubkey,
+        amount: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    FinalizePresale(FinalizePresaleEvent) {
+        admin: Pubkey,
+        unsold_presale_tokens: u64,
+        unsold_reward_tokens: u64,
+        burned_tokens: u64,
+        treasury_tokens: u64,
+        liquidity_tokens: u64,
+        presale: Pubkey,
+        sale_stage: u8,
+        slot: u64,
+    }
+    AdminAction(AdminActionEvent) {
+        presale: Pubkey,
+        admin: Pubkey,
+        action: AdminAction,
+        slot: u64,
+        unix_timestamp: i64,
+    }
+}
+
+admin_actions! {
+    Initialize {
+        sale_id: u64,
+        private_price: u64,
+        public_price: u64,
+        private_sale_duration: i64,
+        public_sale_duration: i64,
+        regular_referral_rate: u8,
+        influencer_referral_rate: u8,
+        sale_allocation: u64,
+    }
+    Migrate {
+        old_version: u8,
+        new_version: u8,
+    }
+    SetStage {
+        old_stage: u8,
+        new_stage: u8,
+        old_price: u64,
+        new_price: u64,
+    }
+    UpdateSalePeriod {
+        old_private_sale_duration: i64,
+        new_private_sale_duration: i64,
+        old_public_sale_duration: i64,
+        new_public_sale_duration: i64,
+    }
+    UpdateSalePrice {
+        sale_stage: u8,
+        old_price: u64,
+        new_price: u64,
+    }
+    SetReferralRate {
+        old_regular_rate: u8,
+        new_regular_rate: u8,
+        old_influencer_rate: u8,
+        new_influencer_rate: u8,
+    }
+    SetReferralRewardMode {
+        old_mode: u8,
+        new_mode: u8,
+    }
+    SetBuyerBonusRate {
+        old_rate: u8,
+        new_rate: u8,
+    }
+    SetVestingSchedule {
+        sale_stage: u8,
+        old_schedule: VestingSchedule,
+        new_schedule: VestingSchedule,
+    }
+    SetPoolConfig {
+        old_config: PoolConfig,
+        new_config: PoolConfig,
+    }
+    Finalize {
+        with_pool: bool,
+        tge_timestamp: i64,
+        unsold_presale_tokens: u64,
+        unsold_reward_tokens: u64,
+    }
+    SetSolPrice {
+        old_price: u64,
+        new_price: u64,
+    }
+    SetPaymentAuthority {
+        old_authority: Pubkey,
+        new_authority: Pubkey,
+    }
+    SetAcceptedMints {
+        old_mints: Vec<Pubkey>,
+        new_mints: Vec<Pubkey>,
+    }
+    SetPlatform {
+        old_fee_recipient: Pubkey,
+        new_fee_recipient: Pubkey,
+        old_raise_fee_bps: u16,
+        new_raise_fee_bps: u16,
+        old_token_fee_bps: u16,
+        new_token_fee_bps: u16,
+        old_allowed_mints: Vec<Pubkey>,
+        new_allowed_mints: Vec<Pubkey>,
+    }
+    DepositSaleTokens {
+        amount: u64,
+        old_total_deposited: u64,
+        new_total_deposited: u64,
+    }
+    DepositRewardTokens {
+        amount: u64,
+        old_total_deposited: u64,
+        new_total_deposited: u64,
+    }
+    CreateGrant {
+        beneficiary: Pubkey,
+        amount: u64,
+        start: i64,
+        schedule: VestingSchedule,
+        revocable: bool,
+    }
+    RevokeGrant {
+        beneficiary: Pubkey,
+        old_amount: u64,
+        new_amount: u64,
+        returned_amount: u64,
+    }
+    CreateAirdrop {
+        airdrop_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        num_recipients: u32,
+        expiry: i64,
+    }
+    ClawbackAirdrop {
+        airdrop_id: u64,
+        unclaimed_amount: u64,
+    }
+    UnlockLp {
+        lp_mint: Pubkey,
+        amount: u64,
+    }
+    SetEmissionRate {
+        old_rate: u64,
+        new_rate: u64,
+    }
+    SetLockupOptions {
+        old_options: Vec<LockupOption>,
+        new_options: Vec<LockupOption>,
+    }
+    SetPrivateBuyerBonus {
+        old_bps: u16,
+        new_bps: u16,
+    }
+    InitStakePool {
+        reward_rate: u64,
+        lockup_options: Vec<LockupOption>,
+    }
+    FundRewards {
+        amount: u64,
+        old_reward_unallocated: u64,
+        new_reward_unallocated: u64,
+    }
+    DeliverV1Allocation {
+        recipient: Pubkey,
+        amount: u64,
+        old_undelivered: u64,
+        new_undelivered: u64,
+    }
+}
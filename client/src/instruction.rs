@@ -0,0 +1,694 @@
+//! Instruction builders, one struct per program instruction.
+//!
+//! Fields are the instruction's accounts, in `#[derive(Accounts)]` order, followed by its
+//! arguments. System, token and loader accounts are filled in. Call `.instruction()` to get an
+//! [`Instruction`] ready to sign.
+//!
+//! Accounts the program `init`s without seeds (`Initialize::presale_wallet` and
+//! `Initialize::referral_wallet`) are fresh keypairs and must co-sign.
+
+use borsh::BorshSerialize;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    discriminator, pda,
+    state::{InitializeArgs, LockupOption},
+    ASSOCIATED_TOKEN_PROGRAM_ID, RENT_SYSVAR_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+macro_rules! field_type {
+    (optional_writable) => { Option<Pubkey> };
+    (optional_signer) => { Option<Pubkey> };
+    ($kind:ident) => { Pubkey };
+}
+
+macro_rules! account_meta {
+    (signer_writable, $key:expr) => { AccountMeta::new($key, true) };
+    (signer, $key:expr) => { AccountMeta::new_readonly($key, true) };
+    (writable, $key:expr) => { AccountMeta::new($key, false) };
+    (readonly, $key:expr) => { AccountMeta::new_readonly($key, false) };
+    // Anchor reads the program id as `None`
+    (optional_writable, $key:expr) => {
+        match $key {
+            Some(key) => AccountMeta::new(key, false),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        }
+    };
+    (optional_signer, $key:expr) => {
+        match $key {
+            Some(key) => AccountMeta::new_readonly(key, true),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        }
+    };
+}
+
+macro_rules! instructions {
+    ($(
+        $name:ident => $ix:literal {
+            accounts { $($account:ident: $kind:ident,)* }
+            $(programs { $($program:ident: $program_id:expr,)* })?
+            $(args { $($arg:ident: $ty:ty,)* })?
+        }
+    )*) => {$(
+        #[doc = concat!("Builds `", $ix, "`.")]
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            $(pub $account: field_type!($kind),)*
+            $($(pub $arg: $ty,)*)?
+        }
+
+        impl $name {
+            pub fn instruction(&self) -> Instruction {
+                #[allow(unused_mut)]
+                let mut data = discriminator("global", $ix).to_vec();
+                $($(
+                    self.$arg.serialize(&mut data).expect("writing to a Vec cannot fail");
+                )*)?
+
+                Instruction {
+                    program_id: crate::ID,
+                    accounts: vec![
+                        $(account_meta!($kind, self.$account),)*
+                        $($(AccountMeta::new_readonly($program_id, false),)*)?
+                    ],
+                    data,
+                }
+            }
+        }
+    )*};
+}
+
+instructions! {
+    InitConfig => "init_config" {
+        accounts {
+            authority: signer_writable,
+            config: writable,
+        }
+        programs {
+            program: crate::ID,
+            program_data: pda::program_data().0,
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        args {
+            accepted_stable_mints: Vec<Pubkey>,
+        }
+    }
+    SetAcceptedMints => "set_accepted_mints" {
+        accounts {
+            authority: signer,
+            config: writable,
+        }
+        args {
+            accepted_stable_mints: Vec<Pubkey>,
+        }
+    }
+    InitPlatform => "init_platform" {
+        accounts {
+            authority: signer_writable,
+            config: readonly,
+            platform: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        args {
+            fee_recipient: Pubkey,
+            raise_fee_bps: u16,
+            token_fee_bps: u16,
+            allowed_mints: Vec<Pubkey>,
+        }
+    }
+    UpdatePlatform => "update_platform" {
+        accounts {
+            authority: signer,
+            platform: writable,
+        }
+        args {
+            fee_recipient: Pubkey,
+            raise_fee_bps: u16,
+            token_fee_bps: u16,
+            allowed_mints: Vec<Pubkey>,
+        }
+    }
+    Initialize => "initialize" {
+        accounts {
+            admin: signer_writable,
+            config: writable,
+            platform: readonly,
+            presale: writable,
+            token_mint: readonly,
+            presale_wallet: signer_writable,
+            referral_wallet: signer_writable,
+            merchant_wallet: writable,
+            treasury_wallet: readonly,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        }
+        args {
+            args: InitializeArgs,
+        }
+    }
+    MigratePresale => "migrate_presale" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            config: writable,
+            presale_wallet: readonly,
+            referral_wallet: readonly,
+            treasury_wallet: readonly,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+    }
+    DeliverV1Allocation => "deliver_v1_allocation" {
+        accounts {
+            admin: signer,
+            presale: writable,
+            presale_wallet: writable,
+            recipient_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+        }
+    }
+    DepositSaleTokens => "deposit_sale_tokens" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            presale_wallet: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+        }
+    }
+    DepositRewardTokens => "deposit_reward_tokens" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            referral_wallet: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+        }
+    }
+    SetStage => "set_stage" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+    }
+    UpdateSalePeriod => "update_sale_period" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            new_private_sale_duration: i64,
+            new_public_sale_duration: i64,
+        }
+    }
+    BuyTokens => "buy_tokens" {
+        accounts {
+            buyer: signer_writable,
+            presale: writable,
+            presale_wallet: writable,
+            referral_wallet: writable,
+            merchant_wallet: writable,
+            platform_fee_wallet: writable,
+            platform_fee_token_account: writable,
+            referrer_wallet: optional_writable,
+            payment_authority: optional_signer,
+            buyer_ledger: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        args {
+            payment_type: u8,
+            lamports_sent: u64,
+            referrer: Pubkey,
+            is_influencer: bool,
+        }
+    }
+    CheckPresaleTokenBalance => "check_presale_token_balance" {
+        accounts {
+            presale: readonly,
+            presale_wallet: readonly,
+        }
+    }
+    AuditReserves => "audit_reserves" {
+        accounts {
+            presale: readonly,
+            presale_wallet: readonly,
+        }
+    }
+    CheckRewardTokenBalance => "check_reward_token_balance" {
+        accounts {
+            presale: readonly,
+            referral_wallet: readonly,
+        }
+    }
+    GetSaleStatus => "get_sale_status" {
+        accounts {
+            presale: readonly,
+        }
+    }
+    QuotePurchase => "quote_purchase" {
+        accounts {
+            presale: readonly,
+            config: readonly,
+        }
+        args {
+            payment_mint: Pubkey,
+            amount: u64,
+            referrer: Pubkey,
+            is_influencer: bool,
+            referrer_vouched: bool,
+        }
+    }
+    UpdateSalePrice => "update_sale_price" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            new_price: u64,
+        }
+    }
+    BuyTokensByStableCoin => "buy_tokens_by_stable_coin" {
+        accounts {
+            buyer: signer_writable,
+            presale: writable,
+            presale_wallet: writable,
+            referral_wallet: writable,
+            buyer_stable_coin_account: writable,
+            merchant_stable_coin_account: writable,
+            referrer_stable_coin_account: optional_writable,
+            payment_authority: optional_signer,
+            platform_fee_stable_account: writable,
+            platform_fee_token_account: writable,
+            buyer_ledger: writable,
+            stable_coin_mint: readonly,
+            config: readonly,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        args {
+            payment_type: u8,
+            stable_coin_amount: u64,
+            referrer: Pubkey,
+            is_influencer: bool,
+        }
+    }
+    SetReferralRate => "set_referral_rate" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            regular_referral_rate: u8,
+            influencer_referral_rate: u8,
+        }
+    }
+    SetReferralRewardMode => "set_referral_reward_mode" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            referral_reward_mode: u8,
+        }
+    }
+    SetBuyerBonusRate => "set_buyer_bonus_rate" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            buyer_bonus_rate: u8,
+        }
+    }
+    SetSolPrice => "set_sol_price" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            sol_price_in_usd: u64,
+        }
+    }
+    SetPaymentAuthority => "set_payment_authority" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            payment_authority: Pubkey,
+        }
+    }
+    SetVestingSchedule => "set_vesting_schedule" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+        }
+        args {
+            sale_stage: u8,
+            tge_percent: u8,
+            cliff_seconds: i64,
+            vesting_duration: i64,
+        }
+    }
+    ClaimTokens => "claim_tokens" {
+        accounts {
+            buyer: signer_writable,
+            presale: writable,
+            buyer_ledger: writable,
+            presale_wallet: writable,
+            buyer_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    CreateGrant => "create_grant" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            beneficiary: readonly,
+            grant: writable,
+            token_mint: readonly,
+            grant_vault: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+            start: i64,
+            tge_percent: u8,
+            cliff_seconds: i64,
+            vesting_duration: i64,
+            revocable: bool,
+        }
+    }
+    ClaimGrant => "claim_grant" {
+        accounts {
+            beneficiary: signer,
+            presale: readonly,
+            grant: writable,
+            grant_vault: writable,
+            beneficiary_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    RevokeGrant => "revoke_grant" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            grant: writable,
+            grant_vault: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    CreateAirdrop => "create_airdrop" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            distributor: writable,
+            token_mint: readonly,
+            airdrop_vault: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            airdrop_id: u64,
+            merkle_root: [u8; 32],
+            total_amount: u64,
+            num_recipients: u32,
+            expiry: i64,
+        }
+    }
+    ClaimAirdrop => "claim_airdrop" {
+        accounts {
+            claimant: signer,
+            presale: readonly,
+            distributor: writable,
+            airdrop_vault: writable,
+            claimant_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            index: u64,
+            amount: u64,
+            proof: Vec<[u8; 32]>,
+        }
+    }
+    ClawbackAirdrop => "clawback_airdrop" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            distributor: writable,
+            airdrop_vault: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    InitStakePool => "init_stake_pool" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            token_mint: readonly,
+            stake_pool: writable,
+            stake_vault: writable,
+            reward_vault: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            reward_rate: u64,
+            lockup_options: Vec<LockupOption>,
+        }
+    }
+    SetEmissionRate => "set_emission_rate" {
+        accounts {
+            admin: signer,
+            stake_pool: writable,
+        }
+        args {
+            reward_rate: u64,
+        }
+    }
+    SetLockupOptions => "set_lockup_options" {
+        accounts {
+            admin: signer,
+            stake_pool: writable,
+        }
+        args {
+            lockup_options: Vec<LockupOption>,
+        }
+    }
+    FundRewards => "fund_rewards" {
+        accounts {
+            admin: signer,
+            stake_pool: writable,
+            reward_vault: writable,
+            admin_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+        }
+    }
+    Stake => "stake" {
+        accounts {
+            owner: signer_writable,
+            stake_pool: writable,
+            stake_position: writable,
+            stake_vault: writable,
+            owner_token_account: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+            lockup_option: u8,
+        }
+    }
+    Unstake => "unstake" {
+        accounts {
+            owner: signer,
+            stake_pool: writable,
+            stake_position: writable,
+            stake_vault: writable,
+            owner_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            amount: u64,
+        }
+    }
+    ClaimRewards => "claim_rewards" {
+        accounts {
+            owner: signer,
+            stake_pool: writable,
+            stake_position: writable,
+            reward_vault: writable,
+            owner_token_account: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    SetPrivateBuyerBonus => "set_private_buyer_bonus" {
+        accounts {
+            admin: signer,
+            stake_pool: writable,
+        }
+        args {
+            private_buyer_bonus_bps: u16,
+        }
+    }
+    StakeFromClaim => "stake_from_claim" {
+        accounts {
+            buyer: signer_writable,
+            presale: writable,
+            buyer_ledger: writable,
+            presale_wallet: writable,
+            stake_pool: writable,
+            stake_position: writable,
+            stake_vault: writable,
+        }
+        programs {
+            system_program: SYSTEM_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        args {
+            lockup_option: u8,
+        }
+    }
+    FinalizePresale => "finalize_presale" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            presale_wallet: writable,
+            referral_wallet: writable,
+            liquidity_wallet: writable,
+            treasury_wallet: writable,
+            token_mint: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    SetPoolConfig => "set_pool_config" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            config: readonly,
+        }
+        args {
+            enabled: bool,
+            quote_mint: Pubkey,
+            raise_percent: u8,
+            token_amount: u64,
+            lp_lock_duration: i64,
+        }
+    }
+    FinalizePresaleWithPool => "finalize_presale_with_pool" {
+        accounts {
+            admin: signer_writable,
+            presale: writable,
+            presale_wallet: writable,
+            referral_wallet: writable,
+            liquidity_wallet: writable,
+            treasury_wallet: writable,
+            token_mint: writable,
+            quote_mint: readonly,
+            admin_token_account: writable,
+            admin_quote_account: writable,
+            merchant: signer,
+            merchant_quote_account: writable,
+            cp_swap_program: readonly,
+            amm_config: readonly,
+            pool_authority: readonly,
+            pool_state: writable,
+            lp_mint: writable,
+            admin_lp_token: writable,
+            token_0_vault: writable,
+            token_1_vault: writable,
+            create_pool_fee: writable,
+            observation_state: writable,
+            lp_lock: writable,
+            lp_vault: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            rent: RENT_SYSVAR_ID,
+        }
+        args {
+            open_time: u64,
+        }
+    }
+    UnlockLp => "unlock_lp" {
+        accounts {
+            admin: signer_writable,
+            presale: readonly,
+            lp_lock: writable,
+            lp_vault: writable,
+            admin_lp_token: writable,
+        }
+        programs {
+            token_program: TOKEN_PROGRAM_ID,
+        }
+    }
+    GetLpLockStatus => "get_lp_lock_status" {
+        accounts {
+            presale: readonly,
+            lp_lock: readonly,
+            lp_vault: readonly,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Off-chain client for the DYAWN presale program.
+//!
+//! - [`instruction`]: one builder per program instruction, producing a ready-to-sign [`Instruction`]
+//! - [`pda`]: program-derived addresses, seeded exactly like the program's `constant.rs`
+//! - [`state`]: account layouts, decoded from raw account data
+//! - [`event`]: `emit!` events, decoded from transaction logs
+//! - [`merkle`]: airdrop leaf and node hashing, matching `claim_airdrop`
+//!
+//! [`Instruction`]: solana_instruction::Instruction
+
+use sha2::{Digest, Sha256};
+use solana_pubkey::{pubkey, Pubkey};
+
+pub mod event;
+pub mod instruction;
+pub mod merkle;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod state;
+
+pub use solana_instruction;
+pub use solana_pubkey;
+
+/// Presale program id, matches `declare_id!` in the program.
+pub const ID: Pubkey = pubkey!("9dKRRg5H1q9ja6GDkxjCUvf9FSAP9xhDjX4uM3jodWS");
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+pub const RENT_SYSVAR_ID: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("account data is not a {0}")]
+    AccountDiscriminator(&'static str),
+
+    #[error("presale account is still in the v1 layout, run migrate_presale first")]
+    UnmigratedPresale,
+
+    #[error("failed to deserialize {0}: {1}")]
+    Deserialize(&'static str, #[source] std::io::Error),
+
+    #[cfg(feature = "rpc")]
+    #[error(transparent)]
+    Rpc(#[from] Box<solana_rpc_client_api::client_error::Error>),
+
+    #[cfg(feature = "rpc")]
+    #[error("simulation failed: {0}")]
+    Simulation(String),
+
+    #[error("{0} returned no data")]
+    NoReturnData(&'static str),
+}
+
+/// First 8 bytes of `sha256("<namespace>:<name>")`, Anchor's instruction, account and event tag.
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = Sha256::new()
+        .chain_update(namespace)
+        .chain_update(":")
+        .chain_update(name)
+        .finalize();
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
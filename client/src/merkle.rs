@@ -0,0 +1,75 @@
+//! Airdrop Merkle tree, hashed exactly like `claim_airdrop` and `verify_merkle_proof`.
+
+use sha3::{Digest, Keccak256};
+use solana_pubkey::Pubkey;
+
+/// Leaf hash, must match `claim_airdrop` in the program.
+pub fn leaf_hash(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update([0u8])
+        .chain_update(index.to_le_bytes())
+        .chain_update(claimant.as_ref())
+        .chain_update(amount.to_le_bytes())
+        .finalize()
+        .into()
+}
+
+/// Inner node hash over the sorted pair, must match `verify_merkle_proof` in the program.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    Keccak256::new()
+        .chain_update([1u8])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Folds a proof onto a leaf, the same walk `verify_merkle_proof` does on chain.
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| node_hash(&node, sibling)) == *root
+}
+
+/// All tree levels from the leaves up to the root. An odd node is promoted unchanged.
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree over `leaves`, which must not be empty.
+    pub fn new(leaves: Vec<[u8; 32]>) -> Self {
+        assert!(!leaves.is_empty(), "a Merkle tree needs at least one leaf");
+
+        let mut levels = vec![leaves];
+        while levels.last().map_or(0, Vec::len) > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().expect("tree has a root level")[0]
+    }
+
+    pub fn proof(&self, mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
@@ -0,0 +1,95 @@
+//! Program-derived addresses. Seeds mirror `program/src/constant.rs`.
+
+use solana_pubkey::Pubkey;
+
+use crate::{ASSOCIATED_TOKEN_PROGRAM_ID, BPF_LOADER_UPGRADEABLE_ID, ID, TOKEN_PROGRAM_ID};
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const PLATFORM_SEED: &[u8] = b"platform";
+pub const PRESALE_SEED: &[u8] = b"presale"; // + sale id (u64 LE)
+pub const PRESALE_V1_SEED: &[u8] = b"rreee123"; // + admin
+pub const BUYER_LEDGER_SEED: &[u8] = b"buyer_ledger";
+pub const GRANT_SEED: &[u8] = b"vesting_grant";
+pub const GRANT_VAULT_SEED: &[u8] = b"grant_vault";
+pub const AIRDROP_SEED: &[u8] = b"airdrop";
+pub const AIRDROP_VAULT_SEED: &[u8] = b"airdrop_vault";
+pub const STAKE_POOL_SEED: &[u8] = b"stake_pool";
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+pub const STAKE_POSITION_SEED: &[u8] = b"stake_position";
+pub const LP_LOCK_SEED: &[u8] = b"lp_lock";
+
+/// Global `ProgramConfig`, hands out sale ids.
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+}
+
+/// Launchpad `Platform` with the fee terms.
+pub fn platform() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PLATFORM_SEED], &ID)
+}
+
+/// `Presale` for a sale id. The next sale gets `ProgramConfig::next_sale_id`.
+pub fn presale(sale_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRESALE_SEED, &sale_id.to_le_bytes()], &ID)
+}
+
+/// `Presale` created before sale ids, keyed by its admin. Migrated sales keep this address.
+pub fn presale_v1(admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRESALE_V1_SEED, admin.as_ref()], &ID)
+}
+
+pub fn buyer_ledger(presale: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUYER_LEDGER_SEED, presale.as_ref(), buyer.as_ref()], &ID)
+}
+
+pub fn grant(presale: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GRANT_SEED, presale.as_ref(), beneficiary.as_ref()], &ID)
+}
+
+pub fn grant_vault(grant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GRANT_VAULT_SEED, grant.as_ref()], &ID)
+}
+
+pub fn airdrop(presale: &Pubkey, airdrop_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AIRDROP_SEED, presale.as_ref(), &airdrop_id.to_le_bytes()], &ID)
+}
+
+pub fn airdrop_vault(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AIRDROP_VAULT_SEED, distributor.as_ref()], &ID)
+}
+
+/// Stake pool of a sale, one per sale.
+pub fn stake_pool(presale: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_POOL_SEED, presale.as_ref()], &ID)
+}
+
+pub fn stake_vault(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_VAULT_SEED, stake_pool.as_ref()], &ID)
+}
+
+pub fn reward_vault(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_VAULT_SEED, stake_pool.as_ref()], &ID)
+}
+
+pub fn stake_position(stake_pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_POSITION_SEED, stake_pool.as_ref(), owner.as_ref()], &ID)
+}
+
+pub fn lp_lock(presale: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LP_LOCK_SEED, presale.as_ref()], &ID)
+}
+
+/// The program's `ProgramData` account, needed by `init_config` to check the upgrade authority.
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID)
+}
+
+/// Associated token account of `owner` for `mint` under the classic token program.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
@@ -0,0 +1,94 @@
+//! Blocking account fetches, behind the `rpc` feature.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_transaction::Transaction;
+
+use crate::{
+    instruction, pda,
+    state::{AccountState, BuyerLedger, Platform, Presale, ProgramConfig, PurchaseQuote, SaleStatus},
+    Error, Result,
+};
+
+/// Fetches and decodes any presale program account.
+pub fn fetch<T: AccountState>(client: &RpcClient, address: &Pubkey) -> Result<T> {
+    let data = client.get_account_data(address).map_err(Box::new)?;
+    T::decode(&data)
+}
+
+pub fn fetch_config(client: &RpcClient) -> Result<ProgramConfig> {
+    fetch(client, &pda::config().0)
+}
+
+pub fn fetch_platform(client: &RpcClient) -> Result<Platform> {
+    fetch(client, &pda::platform().0)
+}
+
+pub fn fetch_presale(client: &RpcClient, sale_id: u64) -> Result<Presale> {
+    fetch(client, &pda::presale(sale_id).0)
+}
+
+/// A buyer's ledger, `None` if they have not bought into the sale yet.
+pub fn fetch_buyer_ledger(client: &RpcClient, presale: &Pubkey, buyer: &Pubkey) -> Result<Option<BuyerLedger>> {
+    let address = pda::buyer_ledger(presale, buyer).0;
+    let account = client
+        .get_account_with_commitment(&address, client.commitment())
+        .map_err(Box::new)?
+        .value;
+    account.map(|account| BuyerLedger::decode(&account.data)).transpose()
+}
+
+/// Largest return data the runtime accepts.
+const MAX_RETURN_DATA: usize = 1024;
+
+/// Simulates a view instruction and decodes what the program returned with `set_return_data`.
+///
+/// `payer` only has to exist with enough SOL for the fee, nothing is signed or sent.
+pub fn view<T: BorshDeserialize>(client: &RpcClient, instruction: Instruction, payer: &Pubkey) -> Result<T> {
+    let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or("view");
+    let transaction = Transaction::new_with_payer(&[instruction], Some(payer));
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(client.commitment()),
+        ..Default::default()
+    };
+    let result = client
+        .simulate_transaction_with_config(&transaction, config)
+        .map_err(Box::new)?
+        .value;
+    if let Some(err) = result.err {
+        return Err(Error::Simulation(err.to_string()));
+    }
+
+    let data = result
+        .return_data
+        .filter(|data| data.program_id == crate::ID.to_string())
+        .ok_or(Error::NoReturnData(name))?;
+    let mut bytes = STANDARD
+        .decode(&data.data.0)
+        .map_err(|err| Error::Deserialize(name, std::io::Error::other(err)))?;
+
+    // The runtime trims trailing zero bytes off return data, put them back for Borsh
+    bytes.resize(bytes.len() + MAX_RETURN_DATA, 0);
+    T::deserialize(&mut bytes.as_slice()).map_err(|err| Error::Deserialize(name, err))
+}
+
+pub fn sale_status(client: &RpcClient, sale_id: u64, payer: &Pubkey) -> Result<SaleStatus> {
+    let instruction = instruction::GetSaleStatus {
+        presale: pda::presale(sale_id).0,
+    };
+    view(client, instruction.instruction(), payer)
+}
+
+pub fn quote_purchase(
+    client: &RpcClient,
+    quote: &instruction::QuotePurchase,
+    payer: &Pubkey,
+) -> Result<PurchaseQuote> {
+    view(client, quote.instruction(), payer)
+}
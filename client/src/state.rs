@@ -0,0 +1,346 @@
+//! Account layouts and view return types. Field order mirrors `program/src/presale.rs`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+use crate::{discriminator, pda, Error, Result};
+
+/// Size of a `Presale` account created before the layout was versioned.
+pub const PRESALE_V1_SPACE: usize = 244;
+
+/// Stable mints a sale tracks its raise in, `MAX_ACCEPTED_MINTS` in the program.
+pub const MAX_ACCEPTED_MINTS: usize = 4;
+
+/// A program-owned account that can be decoded from raw account data.
+pub trait AccountState: BorshDeserialize {
+    /// Struct name in the program, the `account:<NAME>` discriminator preimage.
+    const NAME: &'static str;
+
+    /// Checks the discriminator and deserializes the rest. Trailing bytes are ignored.
+    fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != discriminator("account", Self::NAME) {
+            return Err(Error::AccountDiscriminator(Self::NAME));
+        }
+        Self::deserialize(&mut &data[8..]).map_err(|err| Error::Deserialize(Self::NAME, err))
+    }
+}
+
+macro_rules! account_state {
+    ($($name:ident),* $(,)?) => {
+        $(impl AccountState for $name {
+            const NAME: &'static str = stringify!($name);
+        })*
+    };
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub tge_percent: u8,
+    pub cliff_seconds: i64,
+    pub vesting_duration: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StableRaised {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub enabled: bool,
+    pub quote_mint: Pubkey,
+    pub raise_percent: u8,
+    pub token_amount: u64,
+    pub lp_lock_duration: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnsoldDisposition {
+    pub burn_percent: u8,
+    pub treasury_percent: u8,
+    pub liquidity_percent: u8,
+}
+
+/// Sale parameters of `initialize`. Durations are in days, referral rates in %.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InitializeArgs {
+    pub private_price: u64,
+    pub public_price: u64,
+    pub private_sale_duration: i64,
+    pub public_sale_duration: i64,
+    pub regular_referral_rate: u8,
+    pub influencer_referral_rate: u8,
+    pub unsold_disposition: UnsoldDisposition,
+    pub sale_allocation: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockupOption {
+    pub duration: i64,
+    pub multiplier_bps: u16,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProgramConfig {
+    pub authority: Pubkey,
+    pub next_sale_id: u64,
+    pub accepted_stable_mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Platform {
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub raise_fee_bps: u16,
+    pub token_fee_bps: u16,
+    pub allowed_mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Presale {
+    pub version: u8,
+    pub sale_id: u64,
+    pub admin: Pubkey,
+    pub presale_start: i64,
+    pub private_price: u64,
+    pub public_price: u64,
+    pub current_price: u64,
+    pub private_sale_duration: i64,
+    pub public_sale_duration: i64,
+    pub sale_stage: u8,
+    pub total_sold: u64,
+    pub referral_charged: u64,
+    pub pool_created: bool,
+    pub presale_wallet: Pubkey,
+    pub referral_wallet: Pubkey,
+    pub merchant_wallet: Pubkey,
+    pub regular_referral_rate: u8,
+    pub influencer_referral_rate: u8,
+    pub referral_reward_mode: u8,
+    pub buyer_bonus_rate: u8,
+    pub private_vesting: VestingSchedule,
+    pub public_vesting: VestingSchedule,
+    pub tge_timestamp: i64,
+    pub token_mint: Pubkey,
+    pub total_raised_lamports: u64,
+    pub total_raised_stable: u64,
+    pub raised_by_stable_mint: [StableRaised; MAX_ACCEPTED_MINTS],
+    pub pool_config: PoolConfig,
+    pub unsold_disposition: UnsoldDisposition,
+    pub treasury_wallet: Pubkey,
+    pub reserved_tokens: u64,
+    pub sale_allocation: u64,
+    pub total_deposited: u64,
+    pub total_reward_deposited: u64,
+    pub platform_fee_recipient: Pubkey,
+    pub platform_raise_fee_bps: u16,
+    pub platform_token_fee_bps: u16,
+    pub platform_tokens_charged: u64,
+    pub bump: u8,
+    pub purchase_count: u64,
+    pub sol_price_in_usd: u64,
+    pub payment_authority: Pubkey,
+    pub v1_seeds: bool,
+    pub v1_undelivered: u64,
+    pub reserved: [u8; 7],
+}
+
+impl Presale {
+    /// The sale's address. Sales migrated from v1 keep their admin-keyed PDA.
+    pub fn address(&self) -> Pubkey {
+        if self.v1_seeds {
+            pda::presale_v1(&self.admin).0
+        } else {
+            pda::presale(self.sale_id).0
+        }
+    }
+
+    /// Whole tokens still available to buyers, same as the program's `remaining_tokens`.
+    pub fn remaining_tokens(&self) -> u64 {
+        self.total_deposited
+            .saturating_sub(self.total_sold)
+            .saturating_sub(self.platform_tokens_charged)
+    }
+}
+
+impl AccountState for Presale {
+    const NAME: &'static str = "Presale";
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        // v1 accounts share the discriminator but not the layout
+        if data.len() == PRESALE_V1_SPACE && data[..8] == discriminator("account", Self::NAME) {
+            return Err(Error::UnmigratedPresale);
+        }
+        if data.len() < 8 || data[..8] != discriminator("account", Self::NAME) {
+            return Err(Error::AccountDiscriminator(Self::NAME));
+        }
+        Self::deserialize(&mut &data[8..]).map_err(|err| Error::Deserialize(Self::NAME, err))
+    }
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuyerLedger {
+    pub presale: Pubkey,
+    pub buyer: Pubkey,
+    pub private_purchased: u64,
+    pub public_purchased: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingGrant {
+    pub presale: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vault: Pubkey,
+    pub total_amount: u64,
+    pub claimed: u64,
+    pub start: i64,
+    pub schedule: VestingSchedule,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AirdropDistributor {
+    pub presale: Pubkey,
+    pub airdrop_id: u64,
+    pub merkle_root: [u8; 32],
+    pub vault: Pubkey,
+    pub total_amount: u64,
+    pub total_claimed: u64,
+    pub num_recipients: u32,
+    pub expiry: i64,
+    pub clawed_back: bool,
+    pub bump: u8,
+    pub claim_bitmap: Vec<u8>,
+}
+
+impl AirdropDistributor {
+    pub fn is_claimed(&self, index: u64) -> bool {
+        let byte = (index / 8) as usize;
+        self.claim_bitmap.get(byte).is_some_and(|bits| bits & (1 << (index % 8)) != 0)
+    }
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakePool {
+    pub admin: Pubkey,
+    pub presale: Pubkey,
+    pub token_mint: Pubkey,
+    pub stake_vault: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_rate: u64,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    pub total_staked: u64,
+    pub total_weighted: u64,
+    pub reward_unallocated: u64,
+    pub private_buyer_bonus_bps: u16,
+    pub lockup_options: Vec<LockupOption>,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakePosition {
+    pub stake_pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub weighted_amount: u64,
+    pub multiplier_bps: u16,
+    pub bonus_bps: u16,
+    pub lock_until: i64,
+    pub reward_per_token_paid: u128,
+    pub rewards_owed: u64,
+    pub bump: u8,
+    pub bonus_until: i64,
+    pub settled_at: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LpLock {
+    pub presale: Pubkey,
+    pub lp_mint: Pubkey,
+    pub vault: Pubkey,
+    pub locked_amount: u64,
+    pub unlock_timestamp: i64,
+    pub unlocked: bool,
+    pub bump: u8,
+}
+
+account_state!(
+    ProgramConfig,
+    Platform,
+    BuyerLedger,
+    VestingGrant,
+    AirdropDistributor,
+    StakePool,
+    StakePosition,
+    LpLock,
+);
+
+/// Return data of `audit_reserves`.
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReserveAudit {
+    pub wallet_balance: u64,
+    pub outstanding_claims: u64,
+    pub surplus: u64,
+    pub solvent: bool,
+}
+
+/// Return data of `get_sale_status`. Token counts are whole tokens except `reserved_tokens`.
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SaleStatus {
+    pub sale_id: u64,
+    pub sale_stage: u8,
+    /// Stage the sale schedule puts it in at the cluster clock, ahead of `set_stage`.
+    pub effective_stage: u8,
+    pub private_price: u64,
+    pub public_price: u64,
+    pub current_price: u64,
+    pub presale_start: i64,
+    pub stage_end: i64,
+    pub seconds_remaining: i64,
+    pub total_sold: u64,
+    pub remaining_tokens: u64,
+    pub reserved_tokens: u64,
+    pub reward_liability: u64,
+    pub remaining_rewards: u64,
+    pub total_raised_lamports: u64,
+    pub total_raised_stable: u64,
+    pub purchase_count: u64,
+    pub purchases_open: bool,
+    pub pool_created: bool,
+}
+
+/// Return data of `quote_purchase`. Token amounts are whole tokens, fees are payment base units.
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PurchaseQuote {
+    pub tokens_out: u64,
+    pub bonus_tokens: u64,
+    pub referral_reward: u64,
+    pub referral_commission: u64,
+    pub platform_fee: u64,
+    pub platform_token_fee: u64,
+    pub sale_stage: u8,
+    pub price: u64,
+    pub effective_price: u64,
+    pub remaining_tokens: u64,
+    pub remaining_rewards: u64,
+    /// Program error code the purchase would fail with, `None` if it would go through.
+    pub limit: Option<u32>,
+}
+
+/// Return data of `get_lp_lock_status`.
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LpLockStatus {
+    pub lp_mint: Pubkey,
+    pub locked_amount: u64,
+    pub unlock_timestamp: i64,
+    pub seconds_remaining: i64,
+    pub unlocked: bool,
+}
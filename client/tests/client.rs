@@ -0,0 +1,190 @@
+//! Encoding checks for the client against Anchor's wire format.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use presale_client::{
+    discriminator,
+    event::{parse_logs, PresaleEvent},
+    instruction::{BuyTokens, Initialize, SetStage},
+    merkle::{leaf_hash, verify, MerkleTree},
+    pda,
+    state::{AccountState, InitializeArgs, Presale, ProgramConfig, UnsoldDisposition, PRESALE_V1_SPACE},
+    Error, ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn instruction_data_starts_with_the_anchor_sighash() {
+    // Well-known Anchor discriminator for `initialize`
+    assert_eq!(discriminator("global", "initialize"), [175, 175, 109, 31, 13, 152, 155, 237]);
+
+    let admin = Pubkey::new_unique();
+    let (presale, _) = pda::presale(0);
+    let ix = SetStage { admin, presale }.instruction();
+
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(ix.data, discriminator("global", "set_stage"));
+    assert_eq!(ix.accounts.len(), 2);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert!(!ix.accounts[1].is_signer && ix.accounts[1].is_writable);
+}
+
+#[test]
+fn initialize_appends_args_and_fixed_programs() {
+    let ix = Initialize {
+        admin: Pubkey::new_unique(),
+        config: pda::config().0,
+        platform: pda::platform().0,
+        presale: pda::presale(3).0,
+        token_mint: Pubkey::new_unique(),
+        presale_wallet: Pubkey::new_unique(),
+        referral_wallet: Pubkey::new_unique(),
+        merchant_wallet: Pubkey::new_unique(),
+        treasury_wallet: Pubkey::new_unique(),
+        args: InitializeArgs {
+            private_price: 3500,
+            public_price: 7000,
+            private_sale_duration: 15,
+            public_sale_duration: 60,
+            regular_referral_rate: 5,
+            influencer_referral_rate: 10,
+            unsold_disposition: UnsoldDisposition {
+                burn_percent: 0,
+                treasury_percent: 0,
+                liquidity_percent: 100,
+            },
+            sale_allocation: 1_000,
+        },
+    }
+    .instruction();
+
+    // 4 x u64/i64 + 2 x u8 + 3 x u8 + u64
+    assert_eq!(ix.data.len(), 8 + 32 + 2 + 3 + 8);
+    assert_eq!(ix.data[8..16], 3500u64.to_le_bytes());
+    assert_eq!(ix.accounts.len(), 12);
+    assert!(ix.accounts[5].is_signer, "presale_wallet is a fresh keypair");
+    assert!(ix.accounts[6].is_signer, "referral_wallet is a fresh keypair");
+    assert_eq!(ix.accounts[9].pubkey, SYSTEM_PROGRAM_ID);
+    assert_eq!(ix.accounts[10].pubkey, TOKEN_PROGRAM_ID);
+}
+
+#[test]
+fn missing_optional_accounts_are_passed_as_the_program_id() {
+    let buyer = Pubkey::new_unique();
+    let presale = pda::presale(0).0;
+    let mut builder = BuyTokens {
+        buyer,
+        presale,
+        presale_wallet: Pubkey::new_unique(),
+        referral_wallet: Pubkey::new_unique(),
+        merchant_wallet: Pubkey::new_unique(),
+        platform_fee_wallet: Pubkey::new_unique(),
+        platform_fee_token_account: Pubkey::new_unique(),
+        referrer_wallet: None,
+        payment_authority: None,
+        buyer_ledger: pda::buyer_ledger(&presale, &buyer).0,
+        payment_type: 0,
+        lamports_sent: 1_000_000_000,
+        referrer: Pubkey::default(),
+        is_influencer: false,
+    };
+
+    let ix = builder.instruction();
+    assert_eq!(ix.accounts[7].pubkey, ID);
+    assert!(!ix.accounts[7].is_writable);
+    assert_eq!(ix.accounts[8].pubkey, ID);
+    assert!(!ix.accounts[8].is_signer);
+
+    let referrer = Pubkey::new_unique();
+    let payment_authority = Pubkey::new_unique();
+    builder.referrer_wallet = Some(referrer);
+    builder.payment_authority = Some(payment_authority);
+    let ix = builder.instruction();
+    assert_eq!(ix.accounts[7].pubkey, referrer);
+    assert!(ix.accounts[7].is_writable);
+    assert_eq!(ix.accounts[8].pubkey, payment_authority);
+    assert!(ix.accounts[8].is_signer && !ix.accounts[8].is_writable);
+}
+
+#[test]
+fn accounts_are_checked_against_their_discriminator() {
+    let authority = Pubkey::new_unique();
+    let mut data = discriminator("account", "ProgramConfig").to_vec();
+    authority.serialize(&mut data).unwrap();
+    7u64.serialize(&mut data).unwrap();
+    vec![Pubkey::new_unique()].serialize(&mut data).unwrap();
+    data.push(254);
+    data.resize(data.len() + 64, 0); // unused space
+
+    let config = ProgramConfig::decode(&data).unwrap();
+    assert_eq!(config.authority, authority);
+    assert_eq!(config.next_sale_id, 7);
+    assert_eq!(config.accepted_stable_mints.len(), 1);
+    assert_eq!(config.bump, 254);
+
+    assert!(matches!(Presale::decode(&data), Err(Error::AccountDiscriminator("Presale"))));
+
+    let mut v1 = discriminator("account", "Presale").to_vec();
+    v1.resize(PRESALE_V1_SPACE, 0);
+    assert!(matches!(Presale::decode(&v1), Err(Error::UnmigratedPresale)));
+}
+
+fn program_data(event: &str, fields: &[u8]) -> String {
+    let mut data = discriminator("event", event).to_vec();
+    data.extend_from_slice(fields);
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+#[test]
+fn events_are_parsed_only_from_the_presale_program() {
+    let buyer = Pubkey::new_unique();
+    let mut fields = Vec::new();
+    (buyer, 100u64, 5u64, 1_000_000_000u64, 150u64, 0u8).serialize(&mut fields).unwrap();
+    // Sale context and the counters after the purchase
+    (Pubkey::new_unique(), 3u64, 2u8, 7_000u64, Pubkey::default(), 100u64, 5u64, 1u64, 980_000_000u64, 42u64)
+        .serialize(&mut fields)
+        .unwrap();
+
+    let logs = vec![
+        format!("Program {ID} invoke [1]"),
+        "Program log: Instruction: BuyTokens".to_string(),
+        format!("Program {TOKEN_PROGRAM_ID} invoke [2]"),
+        program_data("BuyTokensEvent", &fields),
+        format!("Program {TOKEN_PROGRAM_ID} success"),
+        program_data("BuyTokensEvent", &fields),
+        program_data("SomeOtherEvent", &fields),
+        format!("Program {ID} consumed 42000 of 200000 compute units"),
+        format!("Program {ID} success"),
+    ];
+
+    let events = parse_logs(&logs);
+    assert_eq!(events.len(), 1);
+    let PresaleEvent::BuyTokens(event) = &events[0] else {
+        panic!("expected BuyTokensEvent, got {}", events[0].name());
+    };
+    assert_eq!(event.buyer, buyer);
+    assert_eq!(event.tokens_purchased, 100);
+    assert_eq!(event.bonus_tokens, 5);
+    assert_eq!((event.purchase_seq, event.sale_stage, event.price), (3, 2, 7_000));
+    assert_eq!(event.total_raised_lamports, 980_000_000);
+    assert_eq!(event.slot, 42);
+}
+
+#[test]
+fn merkle_proofs_verify_for_every_leaf() {
+    for count in [1usize, 2, 3, 5, 8, 13] {
+        let claims: Vec<(Pubkey, u64)> = (0..count).map(|i| (Pubkey::new_unique(), 10 * (i as u64 + 1))).collect();
+        let leaves = claims
+            .iter()
+            .enumerate()
+            .map(|(index, (claimant, amount))| leaf_hash(index as u64, claimant, *amount))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::new(leaves.clone());
+
+        for (index, leaf) in leaves.into_iter().enumerate() {
+            assert!(verify(&tree.proof(index), &tree.root(), leaf));
+        }
+        let forged = leaf_hash(0, &claims[0].0, claims[0].1 + 1);
+        assert!(!verify(&tree.proof(0), &tree.root(), forged));
+    }
+}
@@ -0,0 +1,400 @@
+use std::path::Path;
+
+use anyhow::Result;
+use presale_client::event::{parse_logs, PresaleEvent};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use crate::IndexedTransaction;
+
+const SCHEMA: &str = "
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS transactions (
+    signature  TEXT PRIMARY KEY,
+    slot       INTEGER NOT NULL,
+    block_time INTEGER,
+    finalized  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS events (
+    signature   TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    data        TEXT NOT NULL,
+    presale     TEXT,
+    sale_stage  INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- payment_currency: 0 = SOL (lamports), 1 = stable coin (base units)
+CREATE TABLE IF NOT EXISTS purchases (
+    signature        TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index      INTEGER NOT NULL,
+    buyer            TEXT NOT NULL,
+    tokens_purchased INTEGER NOT NULL,
+    bonus_tokens     INTEGER NOT NULL,
+    payment_amount   INTEGER NOT NULL,
+    payment_currency INTEGER NOT NULL,
+    payment_type     INTEGER NOT NULL,
+    purchase_seq     INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS referral_rewards (
+    signature      TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index    INTEGER NOT NULL,
+    referrer       TEXT NOT NULL,
+    referred_buyer TEXT NOT NULL,
+    reward_amount  INTEGER NOT NULL,
+    is_influencer  INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS referral_commissions (
+    signature         TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index       INTEGER NOT NULL,
+    referrer          TEXT NOT NULL,
+    referred_buyer    TEXT NOT NULL,
+    commission_amount INTEGER NOT NULL,
+    payment_currency  INTEGER NOT NULL,
+    is_influencer     INTEGER NOT NULL,
+    settled           INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS platform_fees (
+    signature        TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index      INTEGER NOT NULL,
+    presale          TEXT NOT NULL,
+    buyer            TEXT NOT NULL,
+    fee_recipient    TEXT NOT NULL,
+    payment_fee      INTEGER NOT NULL,
+    payment_currency INTEGER NOT NULL,
+    token_fee        INTEGER NOT NULL,
+    settled          INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS claims (
+    signature   TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index INTEGER NOT NULL,
+    buyer       TEXT NOT NULL,
+    amount      INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- changes: the action's fields as JSON, old and new values side by side
+CREATE TABLE IF NOT EXISTS admin_actions (
+    signature      TEXT NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    event_index    INTEGER NOT NULL,
+    presale        TEXT NOT NULL,
+    admin          TEXT NOT NULL,
+    action         TEXT NOT NULL,
+    changes        TEXT NOT NULL,
+    slot           INTEGER NOT NULL,
+    unix_timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Recreated on open so its definition follows the tables.
+const VIEWS: &str = "
+-- Whole tokens. Bonus tokens are charged to the referral allocation, not the buyer ledger.
+DROP VIEW IF EXISTS buyer_balances;
+CREATE VIEW buyer_balances AS
+SELECT
+    events.presale,
+    buyer,
+    SUM(purchased)                AS purchased,
+    SUM(bonus)                    AS bonus,
+    SUM(claimed)                  AS claimed,
+    SUM(purchased) - SUM(claimed) AS unclaimed
+FROM (
+    SELECT signature, event_index, buyer, tokens_purchased AS purchased, bonus_tokens AS bonus, 0 AS claimed
+    FROM purchases
+    UNION ALL
+    SELECT signature, event_index, buyer, 0, 0, amount FROM claims
+)
+JOIN events USING (signature, event_index)
+GROUP BY events.presale, buyer;
+";
+
+/// Newest signature already indexed, `getSignaturesForAddress` stops there.
+const CURSOR_KEY: &str = "cursor";
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+
+        // Databases created before events were attributed to a sale and stage or purchases were numbered
+        add_column(&conn, "events", "presale TEXT")?;
+        add_column(&conn, "events", "sale_stage INTEGER")?;
+        add_column(&conn, "purchases", "purchase_seq INTEGER")?;
+
+        conn.execute_batch(VIEWS)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Stores a transaction and its events. Returns how many new events were written, 0 when
+    /// the transaction is already indexed. Re-indexing at `finalized` upgrades the row.
+    pub fn insert(&mut self, transaction: &IndexedTransaction, finalized: bool) -> Result<usize> {
+        if transaction.failed {
+            return Ok(0);
+        }
+        let events: Vec<_> = top_level_invocations(&transaction.logs)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(instruction, logs)| {
+                let fallback = transaction.presale(instruction);
+                parse_logs(logs).into_iter().map(move |event| {
+                    let presale = event.presale().map(|presale| presale.to_string());
+                    (presale.or(fallback.map(str::to_string)), event)
+                })
+            })
+            .collect();
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let db = self.conn.transaction()?;
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, finalized) VALUES (?1, ?2, ?3, ?4)",
+            params![transaction.signature, transaction.slot, transaction.block_time, finalized],
+        )?;
+        if inserted == 0 {
+            if finalized {
+                db.execute(
+                    "UPDATE transactions SET finalized = 1 WHERE signature = ?1",
+                    params![transaction.signature],
+                )?;
+                db.commit()?;
+            }
+            return Ok(0);
+        }
+
+        for (index, (presale, event)) in events.iter().enumerate() {
+            insert_event(&db, &transaction.signature, index, presale.as_deref(), event)?;
+        }
+        db.commit()?;
+
+        Ok(events.len())
+    }
+
+    pub fn cursor(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM sync_state WHERE key = ?1", [CURSOR_KEY], |row| row.get(0))
+            .optional()?)
+    }
+
+    pub fn set_cursor(&self, signature: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO sync_state (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = ?2",
+            params![CURSOR_KEY, signature],
+        )?;
+        Ok(())
+    }
+
+    /// Transactions indexed below `finalized`, with their slots.
+    pub fn pending(&self) -> Result<Vec<(String, u64)>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT signature, slot FROM transactions WHERE finalized = 0 ORDER BY slot")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn mark_finalized(&self, signature: &str) -> Result<()> {
+        self.conn
+            .execute("UPDATE transactions SET finalized = 1 WHERE signature = ?1", [signature])?;
+        Ok(())
+    }
+
+    /// Drops a transaction from an abandoned fork with all its events, and rewinds the cursor
+    /// to the newest finalized transaction so anything after it is fetched again.
+    pub fn remove(&self, signature: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM transactions WHERE signature = ?1", [signature])?;
+
+        let newest: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT signature FROM transactions WHERE finalized = 1 ORDER BY slot DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match newest {
+            Some(signature) => self.set_cursor(&signature),
+            None => {
+                self.conn.execute("DELETE FROM sync_state WHERE key = ?1", [CURSOR_KEY])?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Adds `definition` to `table` unless a column of that name exists.
+fn add_column(conn: &Connection, table: &str, definition: &str) -> Result<()> {
+    let column = definition.split(' ').next().unwrap_or(definition);
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"))?
+        .exists([column])?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {definition}"), [])?;
+    }
+    Ok(())
+}
+
+/// Splits logs at each top-level `invoke [1]`, one slice per transaction instruction.
+fn top_level_invocations(logs: &[String]) -> Vec<&[String]> {
+    let starts: Vec<usize> = logs
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("Program ") && line.ends_with(" invoke [1]"))
+        .map(|(index, _)| index)
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| &logs[start..starts.get(i + 1).copied().unwrap_or(logs.len())])
+        .collect()
+}
+
+fn insert_event(
+    db: &Transaction,
+    signature: &str,
+    index: usize,
+    presale: Option<&str>,
+    event: &PresaleEvent,
+) -> Result<()> {
+    let data = event.to_json();
+    db.execute(
+        "INSERT INTO events (signature, event_index, name, data, presale, sale_stage)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![signature, index, event.name(), data.to_string(), presale, event.sale_stage()],
+    )?;
+
+    match event {
+        PresaleEvent::BuyTokens(event) => {
+            db.execute(
+                "INSERT INTO purchases VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8)",
+                params![
+                    signature,
+                    index,
+                    event.buyer.to_string(),
+                    event.tokens_purchased,
+                    event.bonus_tokens,
+                    event.sol_spent,
+                    event.payment_type,
+                    event.purchase_seq,
+                ],
+            )?;
+        }
+        PresaleEvent::BuyTokensByStableCoin(event) => {
+            db.execute(
+                "INSERT INTO purchases VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7, ?8)",
+                params![
+                    signature,
+                    index,
+                    event.buyer.to_string(),
+                    event.tokens_purchased,
+                    event.bonus_tokens,
+                    event.stable_coin_amount,
+                    event.payment_type,
+                    event.purchase_seq,
+                ],
+            )?;
+        }
+        PresaleEvent::ReferralReward(event) => {
+            db.execute(
+                "INSERT INTO referral_rewards VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    index,
+                    event.referrer.to_string(),
+                    event.referred_buyer.to_string(),
+                    event.reward_amount,
+                    event.is_influencer,
+                ],
+            )?;
+        }
+        PresaleEvent::ReferralCommission(event) => {
+            db.execute(
+                "INSERT INTO referral_commissions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    signature,
+                    index,
+                    event.referrer.to_string(),
+                    event.referred_buyer.to_string(),
+                    event.commission_amount,
+                    event.payment_currency,
+                    event.is_influencer,
+                    event.settled,
+                ],
+            )?;
+        }
+        PresaleEvent::PlatformFee(event) => {
+            db.execute(
+                "INSERT INTO platform_fees VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    signature,
+                    index,
+                    event.presale.to_string(),
+                    event.buyer.to_string(),
+                    event.fee_recipient.to_string(),
+                    event.payment_fee,
+                    event.payment_currency,
+                    event.token_fee,
+                    event.settled,
+                ],
+            )?;
+        }
+        PresaleEvent::ClaimTokens(event) => {
+            db.execute(
+                "INSERT INTO claims VALUES (?1, ?2, ?3, ?4)",
+                params![signature, index, event.buyer.to_string(), event.amount],
+            )?;
+        }
+        PresaleEvent::AdminAction(event) => {
+            db.execute(
+                "INSERT INTO admin_actions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    signature,
+                    index,
+                    event.presale.to_string(),
+                    event.admin.to_string(),
+                    event.action.kind(),
+                    data["action"].to_string(),
+                    event.slot,
+                    event.unix_timestamp,
+                ],
+            )?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
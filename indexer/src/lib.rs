@@ -0,0 +1,109 @@
+//! Rebuilds presale history from transaction logs.
+//!
+//! Every `emit!` event is decoded with `presale_client::event` and stored in SQLite keyed by
+//! `(signature, event_index)`, so indexing the same transaction twice is a no-op. Purchases,
+//! referral rewards, commissions, platform fees and claims also land in typed tables, and the
+//! `buyer_balances` view sums them per sale and buyer.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+pub mod db;
+pub mod reconcile;
+pub mod source;
+
+/// The parts of a `getTransaction` result the indexer needs.
+#[derive(Clone, Debug)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Failed transactions are skipped, their state changes were rolled back.
+    pub failed: bool,
+    pub logs: Vec<String>,
+    /// Top-level instructions in order, empty when the dump has no message.
+    pub instructions: Vec<TopLevelInstruction>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TopLevelInstruction {
+    pub program_id: String,
+    pub accounts: Vec<String>,
+}
+
+impl IndexedTransaction {
+    /// Parses a `getTransaction` result with `"encoding": "json"`, or a whole JSON-RPC response
+    /// wrapping one. A top-level `"signature"` field takes precedence over the transaction's.
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let value = value.get("result").unwrap_or(value);
+        if value.is_null() {
+            bail!("transaction not found");
+        }
+
+        let signature = value
+            .get("signature")
+            .or_else(|| value.pointer("/transaction/signatures/0"))
+            .and_then(Value::as_str)
+            .context("missing transaction signature, dump with \"encoding\": \"json\"")?
+            .to_string();
+        let slot = value.get("slot").and_then(Value::as_u64).context("missing slot")?;
+        let block_time = value.get("blockTime").and_then(Value::as_i64);
+
+        let meta = value.get("meta").context("missing transaction meta")?;
+        let failed = !meta.get("err").is_none_or(Value::is_null);
+        let logs = meta
+            .get("logMessages")
+            .and_then(Value::as_array)
+            .map(|logs| logs.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            signature,
+            slot,
+            block_time,
+            failed,
+            logs,
+            instructions: instructions(value, meta),
+        })
+    }
+
+    /// The sale a top-level instruction ran against. Every instruction that emits purchase,
+    /// referral, fee or claim events takes the `Presale` account right after the signer.
+    pub fn presale(&self, instruction: usize) -> Option<&str> {
+        let instruction = self.instructions.get(instruction)?;
+        if instruction.program_id != presale_client::ID.to_string() {
+            return None;
+        }
+        instruction.accounts.get(1).map(String::as_str)
+    }
+}
+
+/// Resolves account indexes against the static keys followed by the lookup table addresses.
+fn instructions(value: &Value, meta: &Value) -> Vec<TopLevelInstruction> {
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|keys| keys.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+
+    let mut keys = strings(value.pointer("/transaction/message/accountKeys"));
+    keys.extend(strings(meta.pointer("/loadedAddresses/writable")));
+    keys.extend(strings(meta.pointer("/loadedAddresses/readonly")));
+    let key = |index: &Value| index.as_u64().and_then(|index| keys.get(index as usize)).cloned();
+
+    let Some(instructions) = value.pointer("/transaction/message/instructions").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    instructions
+        .iter()
+        .map(|instruction| TopLevelInstruction {
+            program_id: instruction.get("programIdIndex").and_then(key).unwrap_or_default(),
+            accounts: instruction
+                .get("accounts")
+                .and_then(Value::as_array)
+                .map(|accounts| accounts.iter().filter_map(key).collect())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
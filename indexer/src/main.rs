@@ -0,0 +1,158 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use presale_client::rpc::fetch_presale;
+use presale_indexer::{
+    db::Store,
+    reconcile::{self, Report},
+    source::{self, SyncReport},
+};
+use solana_commitment_config::CommitmentLevel;
+use solana_rpc_client::rpc_client::RpcClient;
+
+#[derive(Parser)]
+#[command(name = "presale-indexer", version, about = "Index DYAWN presale events into SQLite")]
+struct Cli {
+    /// SQLite database, created if missing
+    #[arg(long, global = true, env = "PRESALE_INDEXER_DB", default_value = "presale.db")]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch presale transactions from an RPC node, e.g. a `solana-test-validator --ledger <dir>`
+    Sync {
+        /// RPC endpoint
+        #[arg(long, short = 'u', env = "PRESALE_RPC_URL", default_value = "https://api.devnet.solana.com")]
+        url: String,
+
+        /// `confirmed` rows are re-checked on every pass until finalized or dropped
+        #[arg(long, value_enum, default_value_t = Commitment::Finalized)]
+        commitment: Commitment,
+
+        /// Keep polling
+        #[arg(long)]
+        follow: bool,
+
+        /// Seconds between polls with `--follow`
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+    },
+
+    /// Index a JSONL dump of `getTransaction` results, one per line
+    Backfill {
+        #[arg(long)]
+        jsonl: PathBuf,
+    },
+
+    /// Compare indexed events with a sale's on-chain counters and export CSV reports
+    #[command(group(ArgGroup::new("account").required(true).args(["sale_id", "snapshot"])))]
+    Reconcile {
+        /// Fetch the live `Presale` account of this sale
+        #[arg(long)]
+        sale_id: Option<u64>,
+
+        /// RPC endpoint used with `--sale-id`
+        #[arg(long, short = 'u', env = "PRESALE_RPC_URL", default_value = "https://api.devnet.solana.com")]
+        url: String,
+
+        /// `solana account --output json` dump of the `Presale` account
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+
+        /// Directory for checks.csv, buyers.csv and receipts.csv
+        #[arg(long, default_value = "reconcile")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Commitment {
+    Confirmed,
+    Finalized,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut store = Store::open(&cli.db)?;
+
+    match cli.command {
+        Command::Sync {
+            url,
+            commitment,
+            follow,
+            interval,
+        } => {
+            let rpc = RpcClient::new(url);
+            let commitment = match commitment {
+                Commitment::Confirmed => CommitmentLevel::Confirmed,
+                Commitment::Finalized => CommitmentLevel::Finalized,
+            };
+            loop {
+                print_report(&source::sync(&mut store, &rpc, commitment)?);
+                if !follow {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        Command::Backfill { jsonl } => print_report(&source::backfill_jsonl(&mut store, &jsonl)?),
+        Command::Reconcile {
+            sale_id,
+            url,
+            snapshot,
+            out,
+        } => {
+            let state = match (sale_id, snapshot) {
+                (_, Some(path)) => reconcile::load_snapshot(&path)?,
+                (Some(sale_id), None) => fetch_presale(&RpcClient::new(url), sale_id)
+                    .with_context(|| format!("fetching sale {sale_id}"))?,
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            let report = reconcile::reconcile(&store, &state)?;
+            report.write_csv(&out)?;
+            print_reconciliation(&report);
+            println!("CSV written to {}", out.display());
+
+            let discrepancies = report.discrepancies();
+            if discrepancies > 0 {
+                bail!("{discrepancies} counters do not match the index");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &SyncReport) {
+    println!(
+        "{} transactions, {} new events, {} finalized, {} dropped",
+        report.transactions, report.events, report.finalized, report.dropped
+    );
+}
+
+fn print_reconciliation(report: &Report) {
+    println!("Sale {}", report.presale);
+    for check in &report.checks {
+        let status = if check.ok() { "ok" } else { "MISMATCH" };
+        println!(
+            "  {:<24} indexed {:>24}  on-chain {:>24}  {status}",
+            check.counter, check.indexed, check.on_chain
+        );
+    }
+    println!("  {} buyers", report.buyers.len());
+    if !report.missing_purchases.is_empty() {
+        let missing: Vec<String> = report.missing_purchases.iter().map(u64::to_string).collect();
+        println!("  missing purchases: #{}", missing.join(", #"));
+    }
+    if report.unattributed_events > 0 {
+        println!(
+            "  warning: {} events have no sale, re-index them from dumps that include the message",
+            report.unattributed_events
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Compares what the indexer saw for one sale with the counters on its `Presale` account.
+//!
+//! Every counter the program keeps for purchases has an event-side sum. A mismatch means the
+//! index is missing transactions (or holds ones from a dropped fork), or that the account was
+//! changed by something that does not emit.
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use presale_client::state::{AccountState, Presale};
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::Store;
+
+/// One on-chain counter next to the matching sum of indexed events.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub counter: &'static str,
+    pub indexed: i128,
+    pub on_chain: i128,
+    pub difference: i128,
+}
+
+impl Check {
+    fn new(counter: &'static str, indexed: i64, on_chain: u64) -> Self {
+        let (indexed, on_chain) = (i128::from(indexed), i128::from(on_chain));
+        Self {
+            counter,
+            indexed,
+            on_chain,
+            difference: indexed - on_chain,
+        }
+    }
+
+    pub fn ok(&self) -> bool {
+        self.difference == 0
+    }
+}
+
+/// Whole tokens, and the payments made on-chain (Web2 purchases are paid off-chain).
+#[derive(Debug, Serialize)]
+pub struct BuyerTotal {
+    pub buyer: String,
+    pub purchased: i64,
+    pub bonus: i64,
+    pub claimed: i64,
+    pub unclaimed: i64,
+    pub lamports_paid: i64,
+    pub stable_paid: i64,
+}
+
+/// What reached the merchant wallet in one currency: Web3 payments less the commissions and
+/// platform fees split off in the same transaction.
+#[derive(Debug, Serialize)]
+pub struct MerchantReceipt {
+    pub currency: &'static str,
+    pub gross: i64,
+    pub referral_commissions: i64,
+    pub platform_fees: i64,
+    pub net: i64,
+    pub on_chain: u64,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub presale: String,
+    /// Purchase, referral, fee and claim events indexed without a sale. Only events emitted
+    /// before they recorded their sale, from dumps without the transaction message.
+    pub unattributed_events: i64,
+    pub checks: Vec<Check>,
+    /// Purchase sequence numbers up to `Presale::purchase_count` the index has no event for.
+    pub missing_purchases: Vec<u64>,
+    pub buyers: Vec<BuyerTotal>,
+    pub receipts: Vec<MerchantReceipt>,
+}
+
+impl Report {
+    pub fn discrepancies(&self) -> usize {
+        self.checks.iter().filter(|check| !check.ok()).count()
+    }
+
+    /// Writes `checks.csv`, `buyers.csv` and `receipts.csv` into `dir`, creating it if needed.
+    pub fn write_csv(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        write_csv(&dir.join("checks.csv"), &self.checks)?;
+        write_csv(&dir.join("buyers.csv"), &self.buyers)?;
+        write_csv(&dir.join("receipts.csv"), &self.receipts)
+    }
+}
+
+fn write_csv<T: Serialize>(path: &Path, rows: &[T]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path).with_context(|| format!("creating {}", path.display()))?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reconciles the indexed events of the sale `state` belongs to against its counters.
+pub fn reconcile(store: &Store, state: &Presale) -> Result<Report> {
+    let presale = state.address().to_string();
+    let sum = |sql: &str| -> Result<i64> {
+        Ok(store.connection().query_row(sql, [&presale], |row| row.get(0))?)
+    };
+
+    let sold = sum(
+        "SELECT COALESCE(SUM(tokens_purchased), 0) FROM purchases
+         JOIN events USING (signature, event_index) WHERE events.presale = ?1",
+    )?;
+    let rewards = sum(
+        "SELECT COALESCE(SUM(reward_amount), 0) FROM referral_rewards
+         JOIN events USING (signature, event_index) WHERE events.presale = ?1",
+    )?;
+    let bonus = sum(
+        "SELECT COALESCE(SUM(bonus_tokens), 0) FROM purchases
+         JOIN events USING (signature, event_index) WHERE events.presale = ?1",
+    )?;
+    let token_fees = sum("SELECT COALESCE(SUM(token_fee), 0) FROM platform_fees WHERE presale = ?1")?;
+    let claimed = sum(
+        "SELECT COALESCE(SUM(amount), 0) FROM claims
+         JOIN events USING (signature, event_index) WHERE events.presale = ?1",
+    )?;
+
+    let purchases = sum(
+        "SELECT COUNT(*) FROM purchases
+         JOIN events USING (signature, event_index) WHERE events.presale = ?1 AND purchase_seq IS NOT NULL",
+    )?;
+
+    let receipts = [("SOL", 0, state.total_raised_lamports), ("stable", 1, state.total_raised_stable)]
+        .into_iter()
+        .map(|(currency, code, on_chain)| receipt(store, &presale, currency, code, on_chain))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut checks = vec![
+        Check::new("purchase_count", purchases, state.purchase_count),
+        Check::new("total_sold", sold, state.total_sold),
+        Check::new("referral_charged", rewards + bonus, state.referral_charged),
+        Check::new("platform_tokens_charged", token_fees, state.platform_tokens_charged),
+        // Whole tokens in the ledger and events, base units on the account
+        Check::new("reserved_tokens", (sold - claimed) * 1_000_000_000, state.reserved_tokens),
+    ];
+    checks.extend(receipts.iter().map(|receipt| {
+        let counter = match receipt.currency {
+            "SOL" => "total_raised_lamports",
+            _ => "total_raised_stable",
+        };
+        Check::new(counter, receipt.net, receipt.on_chain)
+    }));
+
+    let unattributed_events = store.connection().query_row(
+        "SELECT COUNT(*) FROM events WHERE presale IS NULL AND name IN (
+             'BuyTokensEvent', 'BuyTokensByStableCoinEvent', 'ReferralRewardEvent',
+             'ReferralCommissionEvent', 'PlatformFeeEvent', 'ClaimTokensEvent'
+         )",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(Report {
+        missing_purchases: missing_purchases(store, &presale, state.purchase_count)?,
+        buyers: buyers(store, &presale)?,
+        presale,
+        unattributed_events,
+        checks,
+        receipts,
+    })
+}
+
+fn receipt(
+    store: &Store,
+    presale: &str,
+    currency: &'static str,
+    code: u8,
+    on_chain: u64,
+) -> Result<MerchantReceipt> {
+    let sum = |sql: &str| -> Result<i64> {
+        Ok(store.connection().query_row(sql, params![presale, code], |row| row.get(0))?)
+    };
+
+    let gross = sum(
+        "SELECT COALESCE(SUM(payment_amount), 0) FROM purchases
+         JOIN events USING (signature, event_index)
+         WHERE events.presale = ?1 AND payment_currency = ?2 AND payment_type = 0",
+    )?;
+    let referral_commissions = sum(
+        "SELECT COALESCE(SUM(commission_amount), 0) FROM referral_commissions
+         JOIN events USING (signature, event_index)
+         WHERE events.presale = ?1 AND payment_currency = ?2 AND settled = 1",
+    )?;
+    let platform_fees = sum(
+        "SELECT COALESCE(SUM(payment_fee), 0) FROM platform_fees
+         WHERE presale = ?1 AND payment_currency = ?2 AND settled = 1",
+    )?;
+
+    Ok(MerchantReceipt {
+        currency,
+        gross,
+        referral_commissions,
+        platform_fees,
+        net: gross - referral_commissions - platform_fees,
+        on_chain,
+    })
+}
+
+fn missing_purchases(store: &Store, presale: &str, purchase_count: u64) -> Result<Vec<u64>> {
+    let mut statement = store.connection().prepare(
+        "SELECT DISTINCT purchase_seq FROM purchases
+         JOIN events USING (signature, event_index)
+         WHERE events.presale = ?1 AND purchase_seq IS NOT NULL
+         ORDER BY purchase_seq",
+    )?;
+    let seen = statement
+        .query_map([presale], |row| row.get::<_, u64>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok((1..=purchase_count).filter(|seq| seen.binary_search(seq).is_err()).collect())
+}
+
+fn buyers(store: &Store, presale: &str) -> Result<Vec<BuyerTotal>> {
+    let mut statement = store.connection().prepare(
+        "SELECT buyer, SUM(purchased), SUM(bonus), SUM(claimed), SUM(lamports), SUM(stable)
+         FROM (
+             SELECT buyer, tokens_purchased AS purchased, bonus_tokens AS bonus, 0 AS claimed,
+                    CASE WHEN payment_type = 0 AND payment_currency = 0 THEN payment_amount ELSE 0 END AS lamports,
+                    CASE WHEN payment_type = 0 AND payment_currency = 1 THEN payment_amount ELSE 0 END AS stable
+             FROM purchases JOIN events USING (signature, event_index)
+             WHERE events.presale = ?1
+             UNION ALL
+             SELECT buyer, 0, 0, amount, 0, 0
+             FROM claims JOIN events USING (signature, event_index)
+             WHERE events.presale = ?1
+         )
+         GROUP BY buyer
+         ORDER BY buyer",
+    )?;
+    let rows = statement.query_map([presale], |row| {
+        let purchased: i64 = row.get(1)?;
+        let claimed: i64 = row.get(3)?;
+        Ok(BuyerTotal {
+            buyer: row.get(0)?,
+            purchased,
+            bonus: row.get(2)?,
+            claimed,
+            unclaimed: purchased - claimed,
+            lamports_paid: row.get(4)?,
+            stable_paid: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Reads a `Presale` account from a JSON dump: `solana account --output json`, a
+/// `getAccountInfo` response or its `value`, with the data base64 encoded.
+pub fn load_snapshot(path: &Path) -> Result<Presale> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let value: Value = serde_json::from_str(&text).with_context(|| format!("{}: invalid JSON", path.display()))?;
+
+    let data = ["/account/data", "/result/value/data", "/value/data", "/data"]
+        .iter()
+        .find_map(|pointer| value.pointer(pointer))
+        .context("no account data in snapshot")?;
+    let (encoded, encoding) = match data {
+        Value::Array(parts) => (
+            parts.first().and_then(Value::as_str),
+            parts.get(1).and_then(Value::as_str),
+        ),
+        Value::String(encoded) => (Some(encoded.as_str()), Some("base64")),
+        _ => (None, None),
+    };
+    let Some(encoded) = encoded else {
+        bail!("account data is not a string");
+    };
+    if encoding != Some("base64") {
+        bail!("account data must be base64 encoded, got {}", encoding.unwrap_or("nothing"));
+    }
+
+    Ok(Presale::decode(&STANDARD.decode(encoded)?)?)
+}
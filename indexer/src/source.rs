@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::request::RpcRequest;
+use solana_signature::Signature;
+
+use crate::{db::Store, IndexedTransaction};
+
+/// `getSignaturesForAddress` page size, the RPC maximum.
+const PAGE_SIZE: usize = 1_000;
+
+/// `getSignatureStatuses` accepts at most this many signatures per call.
+const STATUS_BATCH: usize = 256;
+
+/// Totals for one indexing pass.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub transactions: usize,
+    pub events: usize,
+    pub finalized: usize,
+    pub dropped: usize,
+}
+
+/// Indexes every presale transaction newer than the stored cursor, oldest first.
+///
+/// At `confirmed` rows are stored as not yet finalized and settled by [`settle`] on later
+/// passes. `processed` is rejected, its blocks can still be skipped.
+pub fn sync(store: &mut Store, rpc: &RpcClient, commitment: CommitmentLevel) -> Result<SyncReport> {
+    anyhow::ensure!(
+        commitment != CommitmentLevel::Processed,
+        "index at confirmed or finalized, processed blocks can be rolled back"
+    );
+    let finalized = commitment == CommitmentLevel::Finalized;
+
+    let mut report = if finalized { SyncReport::default() } else { settle(store, rpc)? };
+
+    let until = store.cursor()?.map(|signature| Signature::from_str(&signature)).transpose()?;
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            &presale_client::ID,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(PAGE_SIZE),
+                commitment: Some(CommitmentConfig { commitment }),
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        let full = page.len() == PAGE_SIZE;
+        signatures.extend(page.into_iter().filter(|status| status.err.is_none()));
+        if !full {
+            break;
+        }
+    }
+
+    // Newest first from the RPC, index oldest first so the cursor only moves forward
+    for status in signatures.iter().rev() {
+        let value: Value = rpc
+            .send(
+                RpcRequest::GetTransaction,
+                json!([
+                    status.signature,
+                    {
+                        "encoding": "json",
+                        "commitment": commitment.to_string(),
+                        "maxSupportedTransactionVersion": 0,
+                    }
+                ]),
+            )
+            .with_context(|| format!("fetching {}", status.signature))?;
+        let transaction = IndexedTransaction::from_json(&value)
+            .with_context(|| format!("parsing {}", status.signature))?;
+
+        report.transactions += 1;
+        report.events += store.insert(&transaction, finalized)?;
+        store.set_cursor(&status.signature)?;
+    }
+
+    Ok(report)
+}
+
+/// Promotes rows indexed at `confirmed` once their block is finalized and deletes the ones
+/// whose block was abandoned by a fork.
+pub fn settle(store: &Store, rpc: &RpcClient) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+    let pending = store.pending()?;
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    let finalized_slot = rpc.get_slot_with_commitment(CommitmentConfig::finalized())?;
+
+    for batch in pending.chunks(STATUS_BATCH) {
+        let signatures = batch
+            .iter()
+            .map(|(signature, _)| Signature::from_str(signature))
+            .collect::<Result<Vec<_>, _>>()?;
+        let statuses = rpc.get_signature_statuses_with_history(&signatures)?.value;
+
+        for ((signature, slot), status) in batch.iter().zip(statuses) {
+            match status {
+                Some(status) if status.satisfies_commitment(CommitmentConfig::finalized()) => {
+                    store.mark_finalized(signature)?;
+                    report.finalized += 1;
+                }
+                // The cluster finalized past this slot without including the transaction
+                None if *slot <= finalized_slot => {
+                    store.remove(signature)?;
+                    report.dropped += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Indexes a JSONL dump, one `getTransaction` result per line. Dumps are treated as
+/// finalized, take them at `finalized` commitment.
+pub fn backfill_jsonl(store: &mut Store, path: &Path) -> Result<SyncReport> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut report = SyncReport::default();
+
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value =
+            serde_json::from_str(&line).with_context(|| format!("line {}: invalid JSON", line_number + 1))?;
+        let transaction =
+            IndexedTransaction::from_json(&value).with_context(|| format!("line {}", line_number + 1))?;
+
+        report.transactions += 1;
+        report.events += store.insert(&transaction, true)?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,376 @@
+//! Indexing from JSONL dumps: idempotency, typed tables and commitment handling.
+
+use std::io::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use presale_client::{
+    discriminator, pda,
+    state::{AccountState, Presale},
+    ID,
+};
+use presale_indexer::{db::Store, reconcile, source::backfill_jsonl, IndexedTransaction};
+use serde_json::{json, Value};
+use solana_pubkey::Pubkey;
+
+fn program_data(event: &str, fields: impl BorshSerialize) -> String {
+    let mut data = discriminator("event", event).to_vec();
+    fields.serialize(&mut data).unwrap();
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+fn transaction(signature: &str, slot: u64, err: Value, events: Vec<String>) -> Value {
+    let mut logs = vec![format!("Program {ID} invoke [1]")];
+    logs.extend(events);
+    logs.push(format!("Program {ID} success"));
+    json!({
+        "slot": slot,
+        "blockTime": 1_700_000_000 + slot as i64,
+        "meta": { "err": err, "logMessages": logs },
+        "transaction": { "signatures": [signature] },
+    })
+}
+
+/// Sale context and post-purchase counters that follow the payment fields of both buy events.
+fn sale_context(presale: Pubkey, purchase_seq: u64) -> impl BorshSerialize {
+    (presale, purchase_seq, 2u8, 7_000u64, Pubkey::default(), 0u64, 0u64, 0u64, 0u64, 0u64)
+}
+
+fn buy(presale: Pubkey, purchase_seq: u64, buyer: Pubkey, tokens: u64, bonus: u64, lamports: u64) -> String {
+    program_data(
+        "BuyTokensEvent",
+        ((buyer, tokens, bonus, lamports, 150u64, 0u8), sale_context(presale, purchase_seq)),
+    )
+}
+
+fn claim(presale: Pubkey, buyer: Pubkey, amount: u64) -> String {
+    program_data("ClaimTokensEvent", (buyer, amount, amount, presale, 0u64, 0u64))
+}
+
+fn count(store: &Store, table: &str) -> i64 {
+    store
+        .connection()
+        .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn backfill_is_idempotent_and_rebuilds_balances() {
+    let presale = pda::presale(1).0;
+    let buyer = Pubkey::new_unique();
+    let referrer = Pubkey::new_unique();
+
+    let dump = [
+        transaction(
+            "sig1",
+            10,
+            Value::Null,
+            vec![
+                program_data("ReferralRewardEvent", (referrer, buyer, 5u64, false, presale, 1u64)),
+                buy(presale, 1, buyer, 100, 2, 1_000_000_000),
+            ],
+        ),
+        transaction(
+            "sig2",
+            11,
+            Value::Null,
+            vec![program_data(
+                "BuyTokensByStableCoinEvent",
+                ((buyer, 50u64, 0u64, 175_000u64, 0u8), sale_context(presale, 2)),
+            )],
+        ),
+        transaction(
+            "sig3",
+            12,
+            Value::Null,
+            vec![claim(presale, buyer, 30)],
+        ),
+        // Rolled back on chain, must not count
+        transaction(
+            "sig4",
+            13,
+            json!({ "InstructionError": [0, { "Custom": 6000 }] }),
+            vec![buy(presale, 3, buyer, 999, 0, 1)],
+        ),
+    ];
+
+    let mut file = tempfile();
+    for transaction in &dump {
+        writeln!(file.1, "{transaction}").unwrap();
+    }
+
+    let mut store = Store::open_in_memory().unwrap();
+    let first = backfill_jsonl(&mut store, &file.0).unwrap();
+    assert_eq!(first.transactions, 4);
+    assert_eq!(first.events, 4);
+
+    let second = backfill_jsonl(&mut store, &file.0).unwrap();
+    assert_eq!(second.events, 0);
+    assert_eq!(count(&store, "events"), 4);
+    assert_eq!(count(&store, "purchases"), 2);
+    assert_eq!(count(&store, "referral_rewards"), 1);
+
+    // Attributed through the sale recorded in each event, the dump has no messages
+    let (sale, purchased, bonus, claimed, unclaimed): (String, i64, i64, i64, i64) = store
+        .connection()
+        .query_row(
+            "SELECT presale, purchased, bonus, claimed, unclaimed FROM buyer_balances WHERE buyer = ?1",
+            [buyer.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .unwrap();
+    assert_eq!(sale, presale.to_string());
+    assert_eq!((purchased, bonus, claimed, unclaimed), (150, 2, 30, 120));
+
+    let data: String = store
+        .connection()
+        .query_row(
+            "SELECT data FROM events WHERE signature = 'sig1' AND event_index = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    let data: Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(data["buyer"], buyer.to_string());
+    assert_eq!(data["tokens_purchased"], 100);
+    assert_eq!(data["purchase_seq"], 1);
+
+    std::fs::remove_file(&file.0).unwrap();
+}
+
+#[test]
+fn confirmed_rows_are_promoted_or_dropped() {
+    let presale = pda::presale(1).0;
+    let buyer = Pubkey::new_unique();
+    let mut store = Store::open_in_memory().unwrap();
+
+    let kept = transaction("kept", 20, Value::Null, vec![buy(presale, 1, buyer, 10, 0, 1)]);
+    let forked = transaction("forked", 21, Value::Null, vec![buy(presale, 2, buyer, 20, 0, 1)]);
+    let kept = IndexedTransaction::from_json(&kept).unwrap();
+    let forked = IndexedTransaction::from_json(&forked).unwrap();
+
+    assert_eq!(store.insert(&kept, false).unwrap(), 1);
+    assert_eq!(store.insert(&forked, false).unwrap(), 1);
+    store.set_cursor("forked").unwrap();
+    assert_eq!(store.pending().unwrap().len(), 2);
+
+    // Seen again at finalized
+    assert_eq!(store.insert(&kept, true).unwrap(), 0);
+    assert_eq!(store.pending().unwrap(), vec![("forked".to_string(), 21)]);
+
+    store.remove("forked").unwrap();
+    assert_eq!(count(&store, "purchases"), 1);
+    assert_eq!(count(&store, "events"), 1);
+    assert_eq!(store.cursor().unwrap().as_deref(), Some("kept"));
+}
+
+#[test]
+fn rpc_responses_and_plain_results_both_parse() {
+    let result = transaction("sig", 5, Value::Null, vec![]);
+    let wrapped = json!({ "jsonrpc": "2.0", "id": 1, "result": result });
+
+    let parsed = IndexedTransaction::from_json(&wrapped).unwrap();
+    assert_eq!(parsed.signature, "sig");
+    assert_eq!(parsed.slot, 5);
+    assert_eq!(parsed.block_time, Some(1_700_000_005));
+    assert!(!parsed.failed);
+
+    assert!(IndexedTransaction::from_json(&json!({ "result": null })).is_err());
+}
+
+/// A transaction whose only instruction runs against `presale`, so its events are attributed.
+fn sale_transaction(signature: &str, slot: u64, signer: Pubkey, presale: Pubkey, events: Vec<String>) -> Value {
+    let mut value = transaction(signature, slot, Value::Null, events);
+    value["transaction"]["message"] = json!({
+        "accountKeys": [signer.to_string(), presale.to_string(), ID.to_string()],
+        "instructions": [{ "programIdIndex": 2, "accounts": [0, 1] }],
+    });
+    value
+}
+
+#[test]
+fn reconcile_matches_counters_and_flags_drift() {
+    let sale_id = 7;
+    let presale = pda::presale(sale_id).0;
+    let (alice, bob, referrer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut store = Store::open_in_memory().unwrap();
+    let dump = [
+        sale_transaction(
+            "buy-sol",
+            30,
+            alice,
+            presale,
+            vec![
+                program_data(
+                    "PlatformFeeEvent",
+                    (presale, alice, Pubkey::new_unique(), 10_000u64, 0u8, 1u64, true, 1u64),
+                ),
+                program_data(
+                    "ReferralCommissionEvent",
+                    (referrer, alice, 50_000u64, 0u8, false, true, presale, 1u64),
+                ),
+                buy(presale, 1, alice, 100, 5, 1_000_000),
+            ],
+        ),
+        sale_transaction(
+            "buy-stable",
+            31,
+            bob,
+            presale,
+            vec![
+                program_data("ReferralRewardEvent", (referrer, bob, 4u64, false, presale, 2u64)),
+                program_data(
+                    "BuyTokensByStableCoinEvent",
+                    ((bob, 40u64, 0u64, 140_000u64, 0u8), sale_context(presale, 2)),
+                ),
+            ],
+        ),
+        // Paid off-chain, no merchant receipt
+        sale_transaction(
+            "buy-web2",
+            32,
+            bob,
+            presale,
+            vec![program_data(
+                "BuyTokensEvent",
+                ((bob, 10u64, 0u64, 500_000u64, 150u64, 1u8), sale_context(presale, 3)),
+            )],
+        ),
+        // No message, the claim records its sale itself
+        transaction("claim", 33, Value::Null, vec![claim(presale, alice, 30)]),
+        // Another sale's purchase must not leak in
+        sale_transaction(
+            "other-sale",
+            34,
+            bob,
+            pda::presale(8).0,
+            vec![buy(pda::presale(8).0, 1, bob, 1_000, 0, 1)],
+        ),
+        // No message, finalize records its sale and stage itself
+        transaction(
+            "finalize",
+            35,
+            Value::Null,
+            vec![program_data(
+                "FinalizePresaleEvent",
+                ((alice, 0u64, 0u64, 0u64, 0u64, 0u64), (presale, 3u8, 35u64)),
+            )],
+        ),
+    ];
+    for value in &dump {
+        store.insert(&IndexedTransaction::from_json(value).unwrap(), true).unwrap();
+    }
+
+    let finalized: (String, u8) = store
+        .connection()
+        .query_row(
+            "SELECT presale, sale_stage FROM events WHERE signature = 'finalize'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(finalized, (presale.to_string(), 3));
+
+    // Snapshot dumped with `solana account --output json`, counters set to what the events imply
+    let mut data = discriminator("account", Presale::NAME).to_vec();
+    data.resize(1_024, 0);
+    let snapshot = json!({ "account": { "data": [STANDARD.encode(&data), "base64"] } });
+    let mut file = tempfile();
+    write!(file.1, "{snapshot}").unwrap();
+    let mut state = reconcile::load_snapshot(&file.0).unwrap();
+    std::fs::remove_file(&file.0).unwrap();
+
+    state.sale_id = sale_id;
+    state.purchase_count = 3;
+    state.total_sold = 150;
+    state.referral_charged = 9;
+    state.platform_tokens_charged = 1;
+    state.reserved_tokens = 120_000_000_000;
+    state.total_raised_lamports = 940_000;
+    state.total_raised_stable = 140_000;
+
+    let report = reconcile::reconcile(&store, &state).unwrap();
+    assert_eq!(report.discrepancies(), 0, "{:#?}", report.checks);
+    assert_eq!(report.unattributed_events, 0);
+    assert!(report.missing_purchases.is_empty());
+
+    let sol = &report.receipts[0];
+    assert_eq!((sol.gross, sol.referral_commissions, sol.platform_fees), (1_000_000, 50_000, 10_000));
+    assert_eq!(sol.net, 940_000);
+    assert_eq!(report.receipts[1].net, 140_000);
+
+    let alice_total = report.buyers.iter().find(|total| total.buyer == alice.to_string()).unwrap();
+    assert_eq!((alice_total.purchased, alice_total.bonus, alice_total.unclaimed), (100, 5, 70));
+    assert_eq!(alice_total.lamports_paid, 1_000_000);
+    let bob_total = report.buyers.iter().find(|total| total.buyer == bob.to_string()).unwrap();
+    assert_eq!((bob_total.purchased, bob_total.lamports_paid, bob_total.stable_paid), (50, 0, 140_000));
+
+    // A purchase the index never saw
+    state.purchase_count = 4;
+    state.total_sold = 151;
+    let report = reconcile::reconcile(&store, &state).unwrap();
+    assert_eq!(report.discrepancies(), 2);
+    assert_eq!(report.missing_purchases, vec![4]);
+    let drift: Vec<_> = report.checks.iter().filter(|check| !check.ok()).map(|check| check.counter).collect();
+    assert_eq!(drift, ["purchase_count", "total_sold"]);
+
+    let out = std::env::temp_dir().join(format!("presale-reconcile-{}", Pubkey::new_unique()));
+    report.write_csv(&out).unwrap();
+    let checks = std::fs::read_to_string(out.join("checks.csv")).unwrap();
+    assert!(checks.starts_with("counter,indexed,on_chain,difference\n"));
+    assert!(checks.contains("total_sold,150,151,-1"));
+    std::fs::remove_dir_all(&out).unwrap();
+}
+
+#[test]
+fn admin_actions_keep_old_and_new_values() {
+    let presale = pda::presale(3).0;
+    let admin = Pubkey::new_unique();
+    // Borsh enums: variant index, then the variant's fields
+    let set_stage = (2u8, (1u8, 2u8, 3_500u64, 7_000u64));
+    let vesting = (8u8, (1u8, (100u8, 0i64, 0i64), (20u8, 86_400i64, 2_592_000i64)));
+    let events = vec![
+        program_data("AdminActionEvent", (presale, admin, set_stage, 90u64, 1_700_000_090i64)),
+        program_data("AdminActionEvent", (presale, admin, vesting, 91u64, 1_700_000_091i64)),
+    ];
+
+    let mut store = Store::open_in_memory().unwrap();
+    let parsed = IndexedTransaction::from_json(&transaction("admin", 91, Value::Null, events)).unwrap();
+    assert_eq!(store.insert(&parsed, true).unwrap(), 2);
+
+    let rows: Vec<(String, String, String, i64)> = store
+        .connection()
+        .prepare("SELECT presale, action, changes, slot FROM admin_actions ORDER BY event_index")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.0 == presale.to_string()));
+
+    let changes: Value = serde_json::from_str(&rows[0].2).unwrap();
+    assert_eq!((rows[0].1.as_str(), rows[0].3), ("SetStage", 90));
+    assert_eq!((changes["old_stage"].as_u64(), changes["new_stage"].as_u64()), (Some(1), Some(2)));
+    assert_eq!(changes["new_price"], 7_000);
+
+    let changes: Value = serde_json::from_str(&rows[1].2).unwrap();
+    assert_eq!(rows[1].1, "SetVestingSchedule");
+    assert_eq!(changes["old_schedule"]["tge_percent"], 100);
+    assert_eq!(changes["new_schedule"]["cliff_seconds"], 86_400);
+
+    // The event's own presale field attributes it without the transaction message
+    let attributed: i64 = store
+        .connection()
+        .query_row("SELECT COUNT(*) FROM events WHERE presale = ?1", [presale.to_string()], |row| row.get(0))
+        .unwrap();
+    assert_eq!(attributed, 2);
+}
+
+/// A fresh JSONL file in the temp directory.
+fn tempfile() -> (std::path::PathBuf, std::fs::File) {
+    let path = std::env::temp_dir().join(format!("presale-indexer-{}.jsonl", Pubkey::new_unique()));
+    let file = std::fs::File::create(&path).unwrap();
+    (path, file)
+}
@@ -1,5 +1,36 @@
 use solana_program::{pubkey, pubkey::Pubkey};
 // ✅ Constants for PDA Seeds
-pub const PRESALE_SEED: &[u8] = b"rreee123";
-pub const USDC_ADDRESS: Pubkey = pubkey!("3kCfXjhhszFBS9Fm7jkdCM2Bq1HG1N8fJBGDct165kjA");
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const PLATFORM_SEED: &[u8] = b"platform";
+pub const PRESALE_SEED: &[u8] = b"presale"; // + sale id (u64 LE)
+pub const BUYER_LEDGER_SEED: &[u8] = b"buyer_ledger";
+pub const GRANT_SEED: &[u8] = b"vesting_grant";
+pub const GRANT_VAULT_SEED: &[u8] = b"grant_vault";
+pub const AIRDROP_SEED: &[u8] = b"airdrop";
+pub const AIRDROP_VAULT_SEED: &[u8] = b"airdrop_vault";
+// Claim bitmap must fit in the 10KB limit for accounts created through CPI
+pub const MAX_AIRDROP_RECIPIENTS: u32 = 80_000;
+pub const STAKE_POOL_SEED: &[u8] = b"stake_pool";
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+pub const STAKE_POSITION_SEED: &[u8] = b"stake_position";
+pub const MAX_LOCKUP_OPTIONS: usize = 4;
+pub const MAX_PRIVATE_BUYER_BONUS_BPS: u16 = 10_000; // +1x on top of the lockup multiplier
+pub const LP_LOCK_SEED: &[u8] = b"lp_lock";
+pub const MAX_ALLOWED_MINTS: usize = 16;
+pub const MAX_PLATFORM_FEE_BPS: u16 = 1_000; // 10%
+// Presale account layout. v1 accounts were sized by hand and keyed by admin, see `migrate_presale`
+pub const PRESALE_VERSION: u8 = 2;
+pub const PRESALE_V1_SEED: &[u8] = b"rreee123"; // + admin
+pub const PRESALE_V1_SPACE: usize = 244;
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000; // Scale for reward-per-token accumulator
+// Default accepted stable coin for `init_config`, picked by the `mainnet` / `devnet` cargo feature.
+// The live list is `ProgramConfig::accepted_stable_mints`.
+#[cfg(all(feature = "mainnet", feature = "devnet"))]
+compile_error!("the `mainnet` and `devnet` features are mutually exclusive");
+#[cfg(feature = "mainnet")]
+pub const DEFAULT_USDC_MINT: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+#[cfg(not(feature = "mainnet"))]
+pub const DEFAULT_USDC_MINT: Pubkey = pubkey!("3kCfXjhhszFBS9Fm7jkdCM2Bq1HG1N8fJBGDct165kjA");
+pub const MAX_ACCEPTED_MINTS: usize = 4;
 pub const USDC_DECIMALS: u64 = 1_000_000; // USDC has 6 decimals
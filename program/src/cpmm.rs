@@ -0,0 +1,185 @@
+//! Raydium CPMM (CP-Swap) interface used by `finalize_presale_with_pool`:
+//! program id, seeds, the `AmmConfig` account and the `initialize` CPI.
+//! Same layout as Raydium's `raydium-cpmm-cpi`, which is only published on GitHub.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+
+declare_id!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+pub mod create_pool_fee_receiver {
+    use anchor_lang::prelude::*;
+    declare_id!("DNXgeM9EiiaAbaWvwjHj9fQQLAX5ZsfHyvmYUNRAdNC8");
+}
+
+pub const AMM_CONFIG_SEED: &[u8] = b"amm_config";
+pub const AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+pub const POOL_SEED: &[u8] = b"pool";
+pub const POOL_LP_MINT_SEED: &[u8] = b"pool_lp_mint";
+pub const POOL_VAULT_SEED: &[u8] = b"pool_vault";
+pub const OBSERVATION_SEED: &[u8] = b"observation";
+
+const INITIALIZE_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+
+#[derive(Clone)]
+pub struct RaydiumCpmm;
+
+impl Id for RaydiumCpmm {
+    fn id() -> Pubkey {
+        ID
+    }
+}
+
+/// Fee tier a pool is created under, owned by the CPMM program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct AmmConfig {
+    pub bump: u8,
+    pub disable_create_pool: bool,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+    pub padding: [u64; 16],
+}
+
+impl Discriminator for AmmConfig {
+    const DISCRIMINATOR: &'static [u8] = &[218, 244, 33, 104, 203, 203, 43, 111];
+}
+
+impl Owner for AmmConfig {
+    fn owner() -> Pubkey {
+        ID
+    }
+}
+
+impl AccountSerialize for AmmConfig {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer
+            .write_all(Self::DISCRIMINATOR)
+            .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        AnchorSerialize::serialize(self, writer).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        Ok(())
+    }
+}
+
+impl AccountDeserialize for AmmConfig {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        require!(
+            buf.len() >= Self::DISCRIMINATOR.len(),
+            ErrorCode::AccountDiscriminatorNotFound
+        );
+        require!(
+            &buf[..Self::DISCRIMINATOR.len()] == Self::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = &buf[Self::DISCRIMINATOR.len()..];
+        AnchorDeserialize::deserialize(&mut data).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+#[cfg(feature = "idl-build")]
+impl anchor_lang::IdlBuild for AmmConfig {}
+
+/// Accounts of the CPMM `initialize` instruction, in program order.
+pub struct Initialize<'info> {
+    pub creator: AccountInfo<'info>,
+    pub amm_config: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub pool_state: AccountInfo<'info>,
+    pub token_0_mint: AccountInfo<'info>,
+    pub token_1_mint: AccountInfo<'info>,
+    pub lp_mint: AccountInfo<'info>,
+    pub creator_token_0: AccountInfo<'info>,
+    pub creator_token_1: AccountInfo<'info>,
+    pub creator_lp_token: AccountInfo<'info>,
+    pub token_0_vault: AccountInfo<'info>,
+    pub token_1_vault: AccountInfo<'info>,
+    pub create_pool_fee: AccountInfo<'info>,
+    pub observation_state: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_0_program: AccountInfo<'info>,
+    pub token_1_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+impl ToAccountMetas for Initialize<'_> {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.creator.key(), true),
+            AccountMeta::new_readonly(self.amm_config.key(), false),
+            AccountMeta::new_readonly(self.authority.key(), false),
+            AccountMeta::new(self.pool_state.key(), false),
+            AccountMeta::new_readonly(self.token_0_mint.key(), false),
+            AccountMeta::new_readonly(self.token_1_mint.key(), false),
+            AccountMeta::new(self.lp_mint.key(), false),
+            AccountMeta::new(self.creator_token_0.key(), false),
+            AccountMeta::new(self.creator_token_1.key(), false),
+            AccountMeta::new(self.creator_lp_token.key(), false),
+            AccountMeta::new(self.token_0_vault.key(), false),
+            AccountMeta::new(self.token_1_vault.key(), false),
+            AccountMeta::new(self.create_pool_fee.key(), false),
+            AccountMeta::new(self.observation_state.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            AccountMeta::new_readonly(self.token_0_program.key(), false),
+            AccountMeta::new_readonly(self.token_1_program.key(), false),
+            AccountMeta::new_readonly(self.associated_token_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.rent.key(), false),
+        ]
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for Initialize<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.creator.clone(),
+            self.amm_config.clone(),
+            self.authority.clone(),
+            self.pool_state.clone(),
+            self.token_0_mint.clone(),
+            self.token_1_mint.clone(),
+            self.lp_mint.clone(),
+            self.creator_token_0.clone(),
+            self.creator_token_1.clone(),
+            self.creator_lp_token.clone(),
+            self.token_0_vault.clone(),
+            self.token_1_vault.clone(),
+            self.create_pool_fee.clone(),
+            self.observation_state.clone(),
+            self.token_program.clone(),
+            self.token_0_program.clone(),
+            self.token_1_program.clone(),
+            self.associated_token_program.clone(),
+            self.system_program.clone(),
+            self.rent.clone(),
+        ]
+    }
+}
+
+/// Creates the pool and deposits both sides from the creator's token accounts.
+pub fn initialize<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Initialize<'info>>,
+    init_amount_0: u64,
+    init_amount_1: u64,
+    open_time: u64,
+) -> Result<()> {
+    let mut data = INITIALIZE_DISCRIMINATOR.to_vec();
+    (init_amount_0, init_amount_1, open_time).serialize(&mut data)?;
+
+    let instruction = Instruction {
+        program_id: ID,
+        accounts: ctx.to_account_metas(None),
+        data,
+    };
+
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
@@ -1,13 +1,19 @@
+// Anchor 0.31's generated IDL instructions still call the deprecated `AccountInfo::realloc`
+#![cfg_attr(not(feature = "no-idl"), allow(deprecated))]
+
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{self, get_associated_token_address, AssociatedToken},
     token::{self, Mint, Token, TokenAccount, Transfer as TokenTransfer},
 };
+use solana_program::keccak;
 use solana_program::program::invoke;
-use solana_program::system_instruction;
+use solana_system_interface::instruction as system_instruction;
 
 pub mod constant;
+pub mod cpmm;
 use constant::*;
+use cpmm::{create_pool_fee_receiver, AmmConfig, RaydiumCpmm};
 
 declare_id!("9dKRRg5H1q9ja6GDkxjCUvf9FSAP9xhDjX4uM3jodWS"); // Replace with actual program ID
 
@@ -15,20 +21,99 @@ declare_id!("9dKRRg5H1q9ja6GDkxjCUvf9FSAP9xhDjX4uM3jodWS"); // Replace with actu
 pub mod presale {
     use super::*;
 
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        private_price: u64,
-        public_price: u64,
-        private_sale_duration: i64,
-        public_sale_duration: i64,
-        regular_referral_rate: u8,
-        influencer_referral_rate: u8,
+    pub fn init_config(
+        ctx: Context<InitConfig>,
+        accepted_stable_mints: Vec<Pubkey>, // Empty = the build's default USDC mint
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.authority = ctx.accounts.authority.key();
+        config.next_sale_id = 0;
+        config.bump = ctx.bumps.config;
+
+        let accepted_stable_mints = if accepted_stable_mints.is_empty() {
+            vec![DEFAULT_USDC_MINT]
+        } else {
+            accepted_stable_mints
+        };
+        let config_key = config.key();
+        apply_accepted_mints(config, config_key, accepted_stable_mints)?;
+
+        msg!("Program config initialized by {}", config.authority);
+
+        Ok(())
+    }
+
+    pub fn set_accepted_mints(
+        ctx: Context<SetAcceptedMints>,
+        accepted_stable_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        let config_key = ctx.accounts.config.key();
+        apply_accepted_mints(&mut ctx.accounts.config, config_key, accepted_stable_mints)
+    }
+
+    pub fn init_platform(
+        ctx: Context<InitPlatform>,
+        fee_recipient: Pubkey,
+        raise_fee_bps: u16,         // Platform cut of every on-chain payment
+        token_fee_bps: u16,         // Platform cut of every token sale, paid from the sale supply
+        allowed_mints: Vec<Pubkey>, // Sale token mints that may list, empty = any
+    ) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+        platform.authority = ctx.accounts.authority.key();
+        platform.bump = ctx.bumps.platform;
+
+        let platform_key = platform.key();
+        apply_platform_settings(
+            platform,
+            platform_key,
+            fee_recipient,
+            raise_fee_bps,
+            token_fee_bps,
+            allowed_mints,
+        )
+    }
+
+    pub fn update_platform(
+        ctx: Context<UpdatePlatform>,
+        fee_recipient: Pubkey,
+        raise_fee_bps: u16,
+        token_fee_bps: u16,
+        allowed_mints: Vec<Pubkey>,
     ) -> Result<()> {
+        let platform_key = ctx.accounts.platform.key();
+        apply_platform_settings(
+            &mut ctx.accounts.platform,
+            platform_key,
+            fee_recipient,
+            raise_fee_bps,
+            token_fee_bps,
+            allowed_mints,
+        )
+    }
+
+    pub fn initialize(ctx: Context<Initialize>, args: InitializeArgs) -> Result<()> {
+        let InitializeArgs {
+            private_price,
+            public_price,
+            private_sale_duration,
+            public_sale_duration,
+            regular_referral_rate,
+            influencer_referral_rate,
+            unsold_disposition,
+            sale_allocation,
+        } = args;
+        let config = &mut ctx.accounts.config;
         let presale = &mut ctx.accounts.presale;
 
         // Use the correct way to retrieve bump
         let bump = ctx.bumps.presale;
 
+        // ✅ Each sale takes the next id from the program config
+        presale.version = PRESALE_VERSION;
+        presale.sale_id = config.next_sale_id;
+        config.next_sale_id += 1;
+
         // Assign admin and presale parameters
         presale.admin = ctx.accounts.admin.key();
         presale.private_price = private_price;
@@ -39,6 +124,11 @@ pub mod presale {
         presale.public_sale_duration = public_sale_duration * 86400;
         presale.sale_stage = 0; // 0 = Not Started
         presale.total_sold = 0;
+        presale.purchase_count = 0;
+        presale.reserved_tokens = 0;
+        presale.sale_allocation = sale_allocation;
+        presale.total_deposited = 0;
+        presale.total_reward_deposited = 0;
         presale.pool_created = false; // Liquidity pool flag
 
         // Validate and store referral rates
@@ -46,6 +136,55 @@ pub mod presale {
         require!(influencer_referral_rate <= 100, PresaleError::InvalidRate);
         presale.regular_referral_rate = regular_referral_rate;
         presale.influencer_referral_rate = influencer_referral_rate;
+        presale.referral_reward_mode = 0; // 0 = Sale tokens
+        presale.buyer_bonus_rate = 0; // Buyer-side referral bonus disabled by default
+
+        // Default vesting: everything unlocks at TGE (when the pool is created)
+        presale.private_vesting = VestingSchedule {
+            tge_percent: 100,
+            cliff_seconds: 0,
+            vesting_duration: 0,
+        };
+        presale.public_vesting = presale.private_vesting;
+        presale.tge_timestamp = 0;
+        presale.total_raised_lamports = 0;
+        presale.total_raised_stable = 0;
+        presale.raised_by_stable_mint = Default::default();
+        presale.pool_config = PoolConfig::default(); // Pool seeding disabled by default
+
+        // Validate and store the unsold token policy
+        require!(
+            unsold_disposition.burn_percent as u16
+                + unsold_disposition.treasury_percent as u16
+                + unsold_disposition.liquidity_percent as u16
+                == 100,
+            PresaleError::InvalidUnsoldDisposition
+        );
+        presale.unsold_disposition = unsold_disposition;
+        presale.treasury_wallet = ctx.accounts.treasury_wallet.key();
+
+        // ✅ Anyone can list a sale, as long as the platform accepts the mint
+        let platform = &ctx.accounts.platform;
+        require!(
+            platform.allowed_mints.is_empty()
+                || platform.allowed_mints.contains(&ctx.accounts.token_mint.key()),
+            PresaleError::MintNotAllowed
+        );
+
+        // Fees are fixed for the life of the sale, later platform changes do not apply
+        presale.platform_fee_recipient = platform.fee_recipient;
+        presale.platform_raise_fee_bps = platform.raise_fee_bps;
+        presale.platform_token_fee_bps = platform.token_fee_bps;
+        presale.platform_tokens_charged = 0;
+
+        // ✅ A referral commission plus the platform fee must fit in the payment
+        require!(
+            payment_split_fits(regular_referral_rate, influencer_referral_rate, platform.raise_fee_bps),
+            PresaleError::InvalidRate
+        );
+
+        // Sale token mint, shared by presale, referral and vesting grant vaults
+        presale.token_mint = ctx.accounts.token_mint.key();
 
         // Assign storage wallets in Presale state
         presale.presale_wallet = ctx.accounts.presale_wallet.key();
@@ -55,8 +194,24 @@ pub mod presale {
         // Store the bump seed
         presale.bump = bump;
 
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::Initialize {
+                sale_id: presale.sale_id,
+                private_price,
+                public_price,
+                private_sale_duration: presale.private_sale_duration,
+                public_sale_duration: presale.public_sale_duration,
+                regular_referral_rate,
+                influencer_referral_rate,
+                sale_allocation,
+            },
+        )?;
+
         msg!(
-            "Presale contract initialized! Referral: {}%, Influencer: {}%",
+            "Presale {} initialized! Referral: {}%, Influencer: {}%",
+            presale.sale_id,
             regular_referral_rate,
             influencer_referral_rate
         );
@@ -64,6 +219,265 @@ pub mod presale {
         Ok(())
     }
 
+    pub fn migrate_presale(ctx: Context<MigratePresale>) -> Result<()> {
+        let presale_info = ctx.accounts.presale.to_account_info();
+
+        // ✅ Only accounts still in the v1 layout (identified by their size) can be migrated
+        let v1 = {
+            let data = presale_info.try_borrow_data()?;
+            require!(
+                data.len() == PRESALE_V1_SPACE && data[..8] == Presale::DISCRIMINATOR[..],
+                PresaleError::AlreadyMigrated
+            );
+            PresaleV1::deserialize(&mut &data[8..])?
+        };
+
+        // ✅ Ensure the caller is the admin of that sale, v1 sale PDAs are keyed by their admin
+        require!(
+            v1.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+        require!(
+            Pubkey::create_program_address(
+                &[PRESALE_V1_SEED, v1.admin.as_ref(), &[v1.bump]],
+                ctx.program_id,
+            )
+            .ok()
+                == Some(presale_info.key()),
+            PresaleError::Unauthorized
+        );
+
+        // ✅ The sale's own wallets supply the mint and the supply v1 never recorded
+        require!(
+            ctx.accounts.presale_wallet.key() == v1.presale_wallet
+                && ctx.accounts.referral_wallet.key() == v1.referral_wallet,
+            PresaleError::InvalidTokenAccount
+        );
+
+        // ✅ Top up rent for the larger layout, paid by the admin
+        let new_space = 8 + Presale::INIT_SPACE;
+        let rent_due = Rent::get()?
+            .minimum_balance(new_space)
+            .saturating_sub(presale_info.lamports());
+        if rent_due > 0 {
+            invoke(
+                &system_instruction::transfer(&ctx.accounts.admin.key(), &presale_info.key(), rent_due),
+                &[
+                    ctx.accounts.admin.to_account_info(),
+                    presale_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        presale_info.resize(new_space)?;
+
+        // ✅ Give the sale an id, it keeps its v1 address
+        let config = &mut ctx.accounts.config;
+        let sale_id = config.next_sale_id;
+        config.next_sale_id += 1;
+
+        // ✅ Rewrite the account in the current layout, keeping the discriminator
+        let presale = Presale::from_v1(
+            v1,
+            sale_id,
+            &ctx.accounts.presale_wallet,
+            &ctx.accounts.referral_wallet,
+            ctx.accounts.treasury_wallet.key(),
+        )?;
+        let mut data = presale_info.try_borrow_mut_data()?;
+        presale.serialize(&mut &mut data[8..])?;
+
+        emit!(MigratePresaleEvent {
+            admin: ctx.accounts.admin.key(),
+            presale: presale_info.key(),
+            sale_id: presale.sale_id,
+            version: presale.version,
+        });
+
+        // v1 accounts predate the version field
+        emit_admin_action(
+            presale_info.key(),
+            presale.admin,
+            AdminAction::Migrate {
+                old_version: 1,
+                new_version: presale.version,
+            },
+        )?;
+
+        msg!(
+            "Presale {} migrated to version {}",
+            presale.sale_id,
+            presale.version
+        );
+
+        Ok(())
+    }
+
+    pub fn deliver_v1_allocation(ctx: Context<DeliverV1Allocation>, amount: u64) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+
+        // ✅ v1 buyers have no ledger to claim with, the admin delivers from the v1 records
+        require!(
+            amount > 0 && amount <= presale.v1_undelivered,
+            PresaleError::InvalidAmount
+        );
+        // ✅ Same gate as `claim_tokens`
+        require!(presale.pool_created, PresaleError::LiquidityPoolNotCreated);
+
+        let base_units = amount
+            .checked_mul(1_000_000_000) // Adjust decimals
+            .ok_or(PresaleError::MathOverflow)?;
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.presale_wallet.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            base_units,
+        )?;
+
+        let presale = &mut ctx.accounts.presale;
+        presale.v1_undelivered -= amount;
+        presale.reserved_tokens = presale.reserved_tokens.saturating_sub(base_units);
+
+        emit!(DeliverV1AllocationEvent {
+            admin: ctx.accounts.admin.key(),
+            presale: presale.key(),
+            recipient: ctx.accounts.recipient_token_account.owner,
+            amount,
+            v1_undelivered: presale.v1_undelivered,
+            reserved_tokens: presale.reserved_tokens,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::DeliverV1Allocation {
+                recipient: ctx.accounts.recipient_token_account.owner,
+                amount,
+                old_undelivered: presale.v1_undelivered + amount,
+                new_undelivered: presale.v1_undelivered,
+            },
+        )?;
+
+        msg!(
+            "Delivered {} v1 tokens to {}, {} left to deliver",
+            amount,
+            ctx.accounts.recipient_token_account.owner,
+            presale.v1_undelivered
+        );
+
+        Ok(())
+    }
+
+    pub fn deposit_sale_tokens(ctx: Context<DepositSaleTokens>, amount: u64) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
+
+        require!(amount > 0, PresaleError::InvalidAmount);
+        require!(presale.sale_stage < 3, PresaleError::SaleAlreadyEnded);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.presale_wallet.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount * 1_000_000_000, // Adjust decimals
+        )?;
+
+        let old_total_deposited = presale.total_deposited;
+        presale.total_deposited += amount;
+
+        emit!(DepositTokensEvent {
+            admin: ctx.accounts.admin.key(),
+            wallet: presale.presale_wallet,
+            amount,
+            total_deposited: presale.total_deposited,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::DepositSaleTokens {
+                amount,
+                old_total_deposited,
+                new_total_deposited: presale.total_deposited,
+            },
+        )?;
+
+        msg!(
+            "{} sale tokens deposited ({} / {} allocation)",
+            amount,
+            presale.total_deposited,
+            presale.sale_allocation
+        );
+
+        Ok(())
+    }
+
+    pub fn deposit_reward_tokens(ctx: Context<DepositRewardTokens>, amount: u64) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
+
+        require!(amount > 0, PresaleError::InvalidAmount);
+        require!(presale.sale_stage < 3, PresaleError::SaleAlreadyEnded);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.referral_wallet.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount * 1_000_000_000, // Adjust decimals
+        )?;
+
+        let old_total_deposited = presale.total_reward_deposited;
+        presale.total_reward_deposited += amount;
+
+        emit!(DepositTokensEvent {
+            admin: ctx.accounts.admin.key(),
+            wallet: presale.referral_wallet,
+            amount,
+            total_deposited: presale.total_reward_deposited,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::DepositRewardTokens {
+                amount,
+                old_total_deposited,
+                new_total_deposited: presale.total_reward_deposited,
+            },
+        )?;
+
+        msg!("{} reward tokens deposited", amount);
+
+        Ok(())
+    }
+
     pub fn set_stage(ctx: Context<SetStage>) -> Result<()> {
         let presale = &mut ctx.accounts.presale;
 
@@ -74,10 +488,15 @@ pub mod presale {
         );
 
         let clock = Clock::get()?; // Get current Solana timestamp
+        let (old_stage, old_price) = (presale.sale_stage, presale.current_price);
 
         match presale.sale_stage {
             0 => {
-                // Not Started → Start Private Sale
+                // Not Started → Start Private Sale, only once the sale allocation is deposited
+                require!(
+                    presale.total_deposited >= presale.sale_allocation,
+                    PresaleError::AllocationNotDeposited
+                );
                 presale.presale_start = clock.unix_timestamp;
                 presale.current_price = presale.private_price;
                 presale.sale_stage = 1;
@@ -110,6 +529,17 @@ pub mod presale {
             }
         }
 
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetStage {
+                old_stage,
+                new_stage: presale.sale_stage,
+                old_price,
+                new_price: presale.current_price,
+            },
+        )?;
+
         Ok(())
     }
 
@@ -129,10 +559,24 @@ pub mod presale {
         // Ensure the presale has not already ended
         require!(presale.sale_stage < 3, PresaleError::SaleAlreadyEnded);
 
+        let old_private_sale_duration = presale.private_sale_duration;
+        let old_public_sale_duration = presale.public_sale_duration;
+
         // Convert days to seconds
         presale.private_sale_duration = new_private_sale_duration * 86400;
         presale.public_sale_duration = new_public_sale_duration * 86400;
 
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::UpdateSalePeriod {
+                old_private_sale_duration,
+                new_private_sale_duration: presale.private_sale_duration,
+                old_public_sale_duration,
+                new_public_sale_duration: presale.public_sale_duration,
+            },
+        )?;
+
         msg!(
             "Updated sale period: Private Sale = {} days, Public Sale = {} days",
             new_private_sale_duration,
@@ -146,12 +590,15 @@ pub mod presale {
         ctx: Context<BuyTokens>,
         payment_type: u8, // 0 = Web3, 1 = Web2
         lamports_sent: u64,
-        sol_price_in_usd: u64,
         referrer: Pubkey,    // ✅ Optional referrer address
         is_influencer: bool, // ✅ True if referrer is an influencer (backend-provided)
     ) -> Result<()> {
         let presale = &mut ctx.accounts.presale;
         let buyer = &ctx.accounts.buyer;
+        let sol_price_in_usd = presale.sol_price_in_usd; // ✅ Admin-set, never taken from the buyer
+
+        // ✅ Web2 payments settle off-chain, only the sale's payment authority can vouch for them
+        check_payment_authority(presale, payment_type, ctx.accounts.payment_authority.as_ref())?;
 
         // Ensure presale is active (Private Sale or Public Sale)
         require!(
@@ -159,37 +606,99 @@ pub mod presale {
             PresaleError::PresaleNotActive
         );
 
-        let amount_in_usd = (lamports_sent * sol_price_in_usd) / 1_000_000_000;
+        // ✅ Buyers cannot refer themselves
+        require!(referrer != buyer.key(), PresaleError::InvalidReferrer);
+        // ✅ Currency commissions are real transfers, the payment authority vouches for the referrer
+        check_referrer_vouched(presale, referrer, ctx.accounts.payment_authority.as_ref())?;
+
+        // ✅ Checked math shared with `quote_purchase`
+        let amounts = presale.purchase_amounts(
+            true,
+            lamports_sent,
+            referrer != Pubkey::default(),
+            is_influencer,
+            co_signed_by_payment_authority(presale, ctx.accounts.payment_authority.as_ref()),
+        )?;
 
         // ✅ Ensure SOL price is at least $1
-        require!(amount_in_usd >= 1, PresaleError::InvalidPrice);
+        require!(amounts.amount_in_usd >= 1, PresaleError::InvalidPrice);
 
-        let tokens_to_purchase = (amount_in_usd * 1_000_000) / presale.current_price;
+        let tokens_to_purchase = amounts.tokens;
+        let platform_token_fee = amounts.platform_token_fee;
 
         // Ensure enough tokens exist
-        let available_tokens = ctx.accounts.presale_wallet.amount;
-        let remaining_tokens = available_tokens - presale.total_sold * 1_000_000_000; // ✅ Adjust for sold tokens
+        let remaining_tokens = presale.remaining_tokens(); // ✅ Supply comes from recorded deposits
 
         require!(
-            remaining_tokens >= tokens_to_purchase * 1_000_000_000, // ✅ Ensure enough tokens remain
+            // ✅ Ensure enough tokens remain
+            remaining_tokens >= checked_sum(tokens_to_purchase, platform_token_fee)?,
             PresaleError::InsufficientTokens
         );
 
+        // ✅ Commission paid in SOL when the sale rewards referrers in the payment currency
+        let referral_commission = amounts.referral_commission;
+
+        // ✅ Platform cut of the payment
+        let platform_fee = amounts.platform_fee;
+
         // If Web3 payment, ensure enough SOL is sent
         if payment_type == 0 {
             require!(
-                lamports_sent
-                    >= ((tokens_to_purchase * presale.current_price)
-                        / (1_000_000 * sol_price_in_usd)),
+                lamports_sent as u128
+                    >= (tokens_to_purchase as u128 * presale.current_price as u128)
+                        / (1_000_000 * sol_price_in_usd as u128),
                 PresaleError::InsufficientFunds
             );
 
+            // ✅ Split the commission off the payment straight to the referrer
+            if referral_commission > 0 {
+                let referrer_wallet = ctx
+                    .accounts
+                    .referrer_wallet
+                    .as_ref()
+                    .ok_or(PresaleError::InvalidReferrer)?;
+                require!(
+                    referrer_wallet.key() == referrer,
+                    PresaleError::InvalidReferrer
+                );
+
+                invoke(
+                    &system_instruction::transfer(&buyer.key(), &referrer, referral_commission),
+                    &[
+                        ctx.accounts.buyer.to_account_info(),
+                        referrer_wallet.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+
+            // ✅ Route the platform fee in the same transaction
+            if platform_fee > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        &buyer.key(),
+                        &presale.platform_fee_recipient,
+                        platform_fee,
+                    ),
+                    &[
+                        ctx.accounts.buyer.to_account_info(),
+                        ctx.accounts.platform_fee_wallet.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+
+            // ✅ Track funds raised for pool seeding at finalize
+            let merchant_amount = amounts.merchant_amount;
+            presale.total_raised_lamports =
+                checked_sum(presale.total_raised_lamports, merchant_amount)?;
+
             // ✅ Transfer SOL to the stored merchant wallet in `Presale`
             invoke(
                 &system_instruction::transfer(
                     &buyer.key(),
                     &presale.merchant_wallet, // ✅ Using stored merchant wallet
-                    lamports_sent,
+                    merchant_amount,
                 ),
                 &[
                     ctx.accounts.buyer.to_account_info(),           // ✅ Buyer
@@ -199,30 +708,140 @@ pub mod presale {
             )?;
         }
 
-        // ✅ Update `total_sold`
-        presale.total_sold += tokens_to_purchase;
+        // ✅ Update `total_sold` and reserve the tokens until the buyer claims them
+        presale.total_sold = checked_sum(presale.total_sold, tokens_to_purchase)?;
+        presale.reserved_tokens =
+            checked_sum(presale.reserved_tokens, base_units(tokens_to_purchase)?)?;
 
-        // ✅ Calculate referral reward if referrer exists
-        if referrer != Pubkey::default() {
-            let referral_percentage = if is_influencer {
-                presale.influencer_referral_rate
-            } else {
-                presale.regular_referral_rate
-            };
+        // ✅ Number the purchase, every event it emits carries the sequence so gaps show up
+        presale.purchase_count = checked_sum(presale.purchase_count, 1)?;
+        let purchase_seq = presale.purchase_count;
+
+        // ✅ Platform cut of the sold tokens, paid out of the sale supply
+        if platform_token_fee > 0 {
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.presale_wallet.to_account_info(),
+                        to: ctx.accounts.platform_fee_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                base_units(platform_token_fee)?, // Adjust decimals
+            )?;
+
+            presale.platform_tokens_charged =
+                checked_sum(presale.platform_tokens_charged, platform_token_fee)?;
+        }
+
+        if platform_fee > 0 || platform_token_fee > 0 {
+            emit!(PlatformFeeEvent {
+                presale: presale.key(),
+                buyer: buyer.key(),
+                fee_recipient: presale.platform_fee_recipient,
+                payment_fee: platform_fee,
+                payment_currency: 0,
+                token_fee: platform_token_fee,
+                settled: payment_type == 0,
+                purchase_seq,
+            });
+        }
+
+        if referral_commission > 0 {
+            emit!(ReferralCommissionEvent {
+                referrer,
+                referred_buyer: buyer.key(),
+                commission_amount: referral_commission,
+                payment_currency: 0,
+                is_influencer,
+                settled: payment_type == 0,
+                presale: presale.key(),
+                purchase_seq,
+            });
+
+            msg!(
+                "Referrer {} earned {} lamports commission from buyer {}",
+                referrer,
+                referral_commission,
+                buyer.key()
+            );
+        }
+
+        // ✅ Buyer-side bonus for vouched referrals, sourced from the referral wallet
+        let bonus_tokens = amounts.bonus_tokens;
+
+        if bonus_tokens > 0 {
+            let remaining_rewards = presale.remaining_rewards();
+
+            require!(
+                remaining_rewards >= bonus_tokens,
+                PresaleError::InsufficientRewardTokens
+            );
+
+            // ✅ Bonus tokens are charged against the referral allocation
+            presale.referral_charged = checked_sum(presale.referral_charged, bonus_tokens)?;
+
+            // ✅ Move the bonus next to the sold tokens so the buyer claims it with them
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.referral_wallet.to_account_info(),
+                        to: ctx.accounts.presale_wallet.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                base_units(bonus_tokens)?, // Adjust decimals
+            )?;
+            presale.reserved_tokens = checked_sum(presale.reserved_tokens, base_units(bonus_tokens)?)?;
 
-            let referral_reward = (tokens_to_purchase * referral_percentage as u64) / 100;
+            msg!(
+                "Buyer {} received {} bonus tokens for using referrer {}",
+                buyer.key(),
+                bonus_tokens,
+                referrer
+            );
+        }
+
+        // ✅ Record the purchase and its bonus in the buyer's ledger for vesting claims
+        let buyer_ledger = &mut ctx.accounts.buyer_ledger;
+        if buyer_ledger.buyer == Pubkey::default() {
+            buyer_ledger.presale = presale.key();
+            buyer_ledger.buyer = buyer.key();
+            buyer_ledger.bump = ctx.bumps.buyer_ledger;
+        }
+        let purchased = checked_sum(tokens_to_purchase, bonus_tokens)?;
+        if presale.sale_stage == 1 {
+            buyer_ledger.private_purchased = checked_sum(buyer_ledger.private_purchased, purchased)?;
+        } else {
+            buyer_ledger.public_purchased = checked_sum(buyer_ledger.public_purchased, purchased)?;
+        }
+
+        // ✅ Calculate referral reward if referrer exists
+        if referrer != Pubkey::default() && presale.referral_reward_mode == 0 {
+            let referral_reward = amounts.referral_reward;
 
             // Ensure enough tokens exist
-            let available_rewards = ctx.accounts.referral_wallet.amount;
-            let remaining_rewards = available_rewards - presale.referral_charged * 1_000_000_000; // ✅ Adjust for sold tokens
+            let remaining_rewards = presale.remaining_rewards(); // ✅ Supply comes from recorded deposits
 
             require!(
-                remaining_rewards >= referral_reward * 1_000_000_000, // ✅ Ensure enough reward tokens remain
+                remaining_rewards >= referral_reward, // ✅ Ensure enough reward tokens remain
                 PresaleError::InsufficientRewardTokens
             );
 
             // ✅ Update `referral_charged`
-            presale.referral_charged += referral_reward;
+            presale.referral_charged = checked_sum(presale.referral_charged, referral_reward)?;
 
             if referral_reward > 0 {
                 emit!(ReferralRewardEvent {
@@ -230,6 +849,8 @@ pub mod presale {
                     referred_buyer: buyer.key(),
                     reward_amount: referral_reward,
                     is_influencer,
+                    presale: presale.key(),
+                    purchase_seq,
                 });
 
                 msg!(
@@ -245,9 +866,20 @@ pub mod presale {
         emit!(BuyTokensEvent {
             buyer: buyer.key(),
             tokens_purchased: tokens_to_purchase,
+            bonus_tokens,
             sol_spent: lamports_sent,
             sol_price_in_usd,
             payment_type,
+            presale: presale.key(),
+            purchase_seq,
+            sale_stage: presale.sale_stage,
+            price: presale.current_price,
+            referrer,
+            total_sold: presale.total_sold,
+            referral_charged: presale.referral_charged,
+            platform_tokens_charged: presale.platform_tokens_charged,
+            total_raised_lamports: presale.total_raised_lamports,
+            slot: Clock::get()?.slot,
         });
 
         msg!(
@@ -263,25 +895,41 @@ pub mod presale {
 
     pub fn check_presale_token_balance(ctx: Context<CheckPresaleTokenBalance>) -> Result<u64> {
         let presale = &ctx.accounts.presale;
-        let available_tokens = ctx.accounts.presale_wallet.amount;
 
-        // Calculate remaining tokens after sold tokens
-        let remaining_tokens = available_tokens - (presale.total_sold * 1_000_000_000); // Adjust for decimals
+        // Calculate remaining tokens from deposits after sold tokens
+        let remaining_tokens = presale.remaining_tokens() * 1_000_000_000; // Adjust for decimals
 
         msg!("Available presale tokens: {}", remaining_tokens);
 
         Ok(remaining_tokens)
     }
 
-    pub fn check_reward_token_balance(ctx: Context<CheckRewardTokenBalance>) -> Result<u64> {
+    pub fn audit_reserves(ctx: Context<AuditReserves>) -> Result<ReserveAudit> {
         let presale = &ctx.accounts.presale;
-        let referral_wallet = &ctx.accounts.referral_wallet;
+        let wallet_balance = ctx.accounts.presale_wallet.amount;
+
+        let audit = ReserveAudit {
+            wallet_balance,
+            outstanding_claims: presale.reserved_tokens,
+            surplus: wallet_balance.saturating_sub(presale.reserved_tokens),
+            solvent: wallet_balance >= presale.reserved_tokens,
+        };
+
+        msg!(
+            "Presale wallet holds {} for {} outstanding claims (solvent: {})",
+            audit.wallet_balance,
+            audit.outstanding_claims,
+            audit.solvent
+        );
+
+        Ok(audit)
+    }
 
-        // ✅ Fetch the balance of the referral wallet
-        let available_rewards = referral_wallet.amount;
+    pub fn check_reward_token_balance(ctx: Context<CheckRewardTokenBalance>) -> Result<u64> {
+        let presale = &ctx.accounts.presale;
 
-        // ✅ Retrieve the total referral rewards charged so far
-        let remaining_rewards = available_rewards - presale.referral_charged * 1_000_000_000; // Adjust decimals
+        // ✅ Deposited reward tokens minus the referral rewards charged so far
+        let remaining_rewards = base_units(presale.remaining_rewards())?; // Adjust decimals
 
         msg!(
             "Referrer Account has {} tokens available in the referral wallet",
@@ -291,6 +939,143 @@ pub mod presale {
         Ok(remaining_rewards)
     }
 
+    pub fn get_sale_status(ctx: Context<GetSaleStatus>) -> Result<SaleStatus> {
+        let presale = &ctx.accounts.presale;
+        let now = Clock::get()?.unix_timestamp;
+
+        // ✅ Stages only advance through `set_stage`, the schedule says where the sale should be
+        let private_end = presale.presale_start + presale.private_sale_duration;
+        let public_end = private_end + presale.public_sale_duration;
+        let effective_stage = match presale.sale_stage {
+            0 => 0,
+            1 | 2 if now >= public_end => 3,
+            1 if now >= private_end => 2,
+            stage => stage,
+        };
+        let stage_end = match effective_stage {
+            1 => private_end,
+            2 => public_end,
+            _ => 0,
+        };
+
+        let status = SaleStatus {
+            sale_id: presale.sale_id,
+            sale_stage: presale.sale_stage,
+            effective_stage,
+            private_price: presale.private_price,
+            public_price: presale.public_price,
+            current_price: presale.current_price,
+            presale_start: presale.presale_start,
+            stage_end,
+            seconds_remaining: (stage_end - now).max(0),
+            total_sold: presale.total_sold,
+            remaining_tokens: presale.remaining_tokens(),
+            reserved_tokens: presale.reserved_tokens,
+            reward_liability: presale.referral_charged,
+            remaining_rewards: presale.remaining_rewards(),
+            total_raised_lamports: presale.total_raised_lamports,
+            total_raised_stable: presale.total_raised_stable,
+            purchase_count: presale.purchase_count,
+            purchases_open: presale.sale_stage == 1 || presale.sale_stage == 2,
+            pool_created: presale.pool_created,
+        };
+
+        msg!(
+            "Sale {} stage {} (by clock {}), {} sold, {} remaining",
+            status.sale_id,
+            status.sale_stage,
+            status.effective_stage,
+            status.total_sold,
+            status.remaining_tokens
+        );
+
+        Ok(status)
+    }
+
+    pub fn quote_purchase(
+        ctx: Context<QuotePurchase>,
+        payment_mint: Pubkey,   // Native mint for SOL, otherwise an accepted stable coin
+        amount: u64,            // Lamports for SOL, whole stable coins like `buy_tokens_by_stable_coin`
+        referrer: Pubkey,       // ✅ Optional referrer address
+        is_influencer: bool,
+        referrer_vouched: bool, // Quote as if the payment authority co-signs the referrer
+    ) -> Result<PurchaseQuote> {
+        let presale = &ctx.accounts.presale;
+        let is_sol = payment_mint == token::spl_token::native_mint::ID;
+
+        // ✅ Same math as the buy instructions, without moving funds
+        let has_referrer = referrer != Pubkey::default();
+        let amounts =
+            presale.purchase_amounts(is_sol, amount, has_referrer, is_influencer, referrer_vouched)?;
+        let PurchaseAmounts {
+            amount_in_usd,
+            tokens: tokens_out,
+            platform_token_fee,
+            platform_fee,
+            referral_commission,
+            referral_reward,
+            bonus_tokens,
+            ..
+        } = amounts;
+
+        let remaining_tokens = presale.remaining_tokens();
+        let remaining_rewards = presale.remaining_rewards();
+
+        // ✅ First check the buy would fail, in the order the buy instructions run them
+        let limit = if presale.sale_stage != 1 && presale.sale_stage != 2 {
+            Some(PresaleError::PresaleNotActive)
+        } else if has_referrer && presale.referral_reward_mode == 1 && !referrer_vouched {
+            Some(PresaleError::ReferrerNotVouched)
+        } else if !is_sol && !ctx.accounts.config.accepted_stable_mints.contains(&payment_mint) {
+            Some(PresaleError::InvalidStableToken)
+        } else if amount_in_usd < 1 {
+            Some(PresaleError::InvalidPrice)
+        } else if remaining_tokens < checked_sum(tokens_out, platform_token_fee)? {
+            Some(PresaleError::InsufficientTokens)
+        } else if remaining_rewards < checked_sum(bonus_tokens, referral_reward)? {
+            Some(PresaleError::InsufficientRewardTokens)
+        } else {
+            None
+        };
+
+        // Micro-USD per token actually paid, rounded up so truncated tokens show against `price`
+        let effective_price = if tokens_out > 0 {
+            let micro_usd = if is_sol {
+                amount as u128 * presale.sol_price_in_usd as u128 / 1_000
+            } else {
+                amount as u128 * 1_000_000
+            };
+            micro_usd.div_ceil(tokens_out as u128) as u64
+        } else {
+            0
+        };
+
+        let quote = PurchaseQuote {
+            tokens_out,
+            bonus_tokens,
+            referral_reward,
+            referral_commission,
+            platform_fee,
+            platform_token_fee,
+            sale_stage: presale.sale_stage,
+            price: presale.current_price,
+            effective_price,
+            remaining_tokens,
+            remaining_rewards,
+            limit: limit.map(u32::from),
+        };
+
+        msg!(
+            "Quote: {} tokens (+{} bonus) at {} micro-USD each, limit {:?}",
+            quote.tokens_out,
+            quote.bonus_tokens,
+            quote.effective_price,
+            quote.limit
+        );
+
+        Ok(quote)
+    }
+
     pub fn update_sale_price(ctx: Context<UpdateSalePrice>, new_price: u64) -> Result<()> {
         let presale = &mut ctx.accounts.presale;
 
@@ -306,6 +1091,8 @@ pub mod presale {
             PresaleError::PresaleNotActive
         );
 
+        let old_price = presale.current_price;
+
         // ✅ Update price based on the current sale stage
         match presale.sale_stage {
             1 => {
@@ -324,8 +1111,20 @@ pub mod presale {
             admin: ctx.accounts.admin.key(),
             new_price,
             sale_stage: presale.sale_stage,
+            presale: presale.key(),
+            slot: Clock::get()?.slot,
         });
 
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::UpdateSalePrice {
+                sale_stage: presale.sale_stage,
+                old_price,
+                new_price,
+            },
+        )?;
+
         msg!(
             "Sale price updated to {} for stage {}",
             new_price,
@@ -345,9 +1144,16 @@ pub mod presale {
         let presale = &mut ctx.accounts.presale;
         let buyer = &ctx.accounts.buyer;
 
-        // ✅ Ensure only USDT or USDC is used
+        // ✅ Web2 payments settle off-chain, only the sale's payment authority can vouch for them
+        check_payment_authority(presale, payment_type, ctx.accounts.payment_authority.as_ref())?;
+
+        // ✅ Ensure only stable coins accepted by the program config are used
         require!(
-            ctx.accounts.stable_coin_mint.key() == USDC_ADDRESS,
+            ctx.accounts
+                .config
+                .accepted_stable_mints
+                .contains(&ctx.accounts.stable_coin_mint.key())
+                && 10u64.pow(ctx.accounts.stable_coin_mint.decimals as u32) == USDC_DECIMALS,
             PresaleError::InvalidStableToken
         );
 
@@ -360,19 +1166,82 @@ pub mod presale {
             PresaleError::PresaleNotActive
         );
 
-        // ✅ Convert stable coin amount to token amount
-        let tokens_to_purchase = (stable_coin_amount * 1_000_000) / presale.current_price;
+        // ✅ Buyers cannot refer themselves
+        require!(referrer != buyer.key(), PresaleError::InvalidReferrer);
+        // ✅ Currency commissions are real transfers, the payment authority vouches for the referrer
+        check_referrer_vouched(presale, referrer, ctx.accounts.payment_authority.as_ref())?;
+
+        // ✅ Convert stable coin amount to token amount, checked math shared with `quote_purchase`
+        let amounts = presale.purchase_amounts(
+            false,
+            stable_coin_amount,
+            referrer != Pubkey::default(),
+            is_influencer,
+            co_signed_by_payment_authority(presale, ctx.accounts.payment_authority.as_ref()),
+        )?;
+        let tokens_to_purchase = amounts.tokens;
+        let platform_token_fee = amounts.platform_token_fee;
 
         // ✅ Ensure enough tokens exist
-        let available_tokens = ctx.accounts.presale_wallet.amount;
-        let remaining_tokens = available_tokens - presale.total_sold * 1_000_000_000;
+        let remaining_tokens = presale.remaining_tokens();
 
         require!(
-            remaining_tokens >= tokens_to_purchase * 1_000_000_000,
+            remaining_tokens >= checked_sum(tokens_to_purchase, platform_token_fee)?,
             PresaleError::InsufficientTokens
         );
 
+        // ✅ Commission paid in USDC when the sale rewards referrers in the payment currency
+        let referral_commission = amounts.referral_commission;
+
+        // ✅ Platform cut of the payment
+        let platform_fee = amounts.platform_fee;
+
         if payment_type == 0 {
+            // ✅ Split the commission off the payment straight to the referrer's USDC account
+            if referral_commission > 0 {
+                let referrer_stable_coin_account = ctx
+                    .accounts
+                    .referrer_stable_coin_account
+                    .as_ref()
+                    .ok_or(PresaleError::InvalidReferrer)?;
+                require!(
+                    referrer_stable_coin_account.owner == referrer
+                        && referrer_stable_coin_account.mint == ctx.accounts.stable_coin_mint.key(),
+                    PresaleError::InvalidReferrer
+                );
+
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        TokenTransfer {
+                            from: ctx.accounts.buyer_stable_coin_account.to_account_info(),
+                            to: referrer_stable_coin_account.to_account_info(),
+                            authority: ctx.accounts.buyer.to_account_info(),
+                        },
+                    ),
+                    referral_commission,
+                )?;
+            }
+
+            // ✅ Route the platform fee in the same transaction
+            if platform_fee > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        TokenTransfer {
+                            from: ctx.accounts.buyer_stable_coin_account.to_account_info(),
+                            to: ctx.accounts.platform_fee_stable_account.to_account_info(),
+                            authority: ctx.accounts.buyer.to_account_info(),
+                        },
+                    ),
+                    platform_fee,
+                )?;
+            }
+
+            // ✅ Track funds raised for pool seeding at finalize
+            let merchant_amount = amounts.merchant_amount;
+            presale.record_stable_raised(ctx.accounts.stable_coin_mint.key(), merchant_amount)?;
+
             // ✅ Transfer stable coins to the merchant wallet
             token::transfer(
                 CpiContext::new(
@@ -383,57 +1252,180 @@ pub mod presale {
                         authority: ctx.accounts.buyer.to_account_info(),
                     },
                 ),
-                stable_coin_amount * USDC_DECIMALS, // Convert to correct decimal
+                merchant_amount,
             )?;
         }
 
-        // ✅ Update `total_sold`
-        presale.total_sold += tokens_to_purchase;
+        // ✅ Update `total_sold` and reserve the tokens until the buyer claims them
+        presale.total_sold = checked_sum(presale.total_sold, tokens_to_purchase)?;
+        presale.reserved_tokens =
+            checked_sum(presale.reserved_tokens, base_units(tokens_to_purchase)?)?;
 
-        // ✅ Calculate referral reward if referrer exists
-        if referrer != Pubkey::default() {
-            let referral_percentage = if is_influencer {
-                presale.influencer_referral_rate
-            } else {
-                presale.regular_referral_rate
-            };
+        // ✅ Number the purchase, every event it emits carries the sequence so gaps show up
+        presale.purchase_count = checked_sum(presale.purchase_count, 1)?;
+        let purchase_seq = presale.purchase_count;
 
-            let referral_reward = (tokens_to_purchase * referral_percentage as u64) / 100;
+        // ✅ Platform cut of the sold tokens, paid out of the sale supply
+        if platform_token_fee > 0 {
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
 
-            // Ensure enough tokens exist
-            let available_rewards = ctx.accounts.referral_wallet.amount;
-            let remaining_rewards = available_rewards - presale.referral_charged * 1_000_000_000; // ✅ Adjust for sold tokens
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.presale_wallet.to_account_info(),
+                        to: ctx.accounts.platform_fee_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                base_units(platform_token_fee)?, // Adjust decimals
+            )?;
+
+            presale.platform_tokens_charged =
+                checked_sum(presale.platform_tokens_charged, platform_token_fee)?;
+        }
+
+        if platform_fee > 0 || platform_token_fee > 0 {
+            emit!(PlatformFeeEvent {
+                presale: presale.key(),
+                buyer: buyer.key(),
+                fee_recipient: presale.platform_fee_recipient,
+                payment_fee: platform_fee,
+                payment_currency: 1,
+                token_fee: platform_token_fee,
+                settled: payment_type == 0,
+                purchase_seq,
+            });
+        }
+
+        if referral_commission > 0 {
+            emit!(ReferralCommissionEvent {
+                referrer,
+                referred_buyer: buyer.key(),
+                commission_amount: referral_commission,
+                payment_currency: 1,
+                is_influencer,
+                settled: payment_type == 0,
+                presale: presale.key(),
+                purchase_seq,
+            });
+
+            msg!(
+                "Referrer {} earned {} USDC base units commission from buyer {}",
+                referrer,
+                referral_commission,
+                buyer.key()
+            );
+        }
+
+        // ✅ Buyer-side bonus for vouched referrals, sourced from the referral wallet
+        let bonus_tokens = amounts.bonus_tokens;
+
+        if bonus_tokens > 0 {
+            let remaining_rewards = presale.remaining_rewards();
 
             require!(
-                remaining_rewards >= referral_reward * 1_000_000_000, // ✅ Ensure enough reward tokens remain
+                remaining_rewards >= bonus_tokens,
                 PresaleError::InsufficientRewardTokens
             );
 
-            // ✅ Update `referral_charged`
-            presale.referral_charged += referral_reward;
-
-            if referral_reward > 0 {
-                emit!(ReferralRewardEvent {
-                    referrer,
-                    referred_buyer: buyer.key(),
-                    reward_amount: referral_reward,
-                    is_influencer,
-                });
+            // ✅ Bonus tokens are charged against the referral allocation
+            presale.referral_charged = checked_sum(presale.referral_charged, bonus_tokens)?;
 
-                msg!(
-                    "Referrer {} received {} tokens from buyer {}",
-                    referrer,
-                    referral_reward,
-                    buyer.key()
-                );
-            }
-        }
+            // ✅ Move the bonus next to the sold tokens so the buyer claims it with them
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.referral_wallet.to_account_info(),
+                        to: ctx.accounts.presale_wallet.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                base_units(bonus_tokens)?, // Adjust decimals
+            )?;
+            presale.reserved_tokens = checked_sum(presale.reserved_tokens, base_units(bonus_tokens)?)?;
+
+            msg!(
+                "Buyer {} received {} bonus tokens for using referrer {}",
+                buyer.key(),
+                bonus_tokens,
+                referrer
+            );
+        }
+
+        // ✅ Record the purchase and its bonus in the buyer's ledger for vesting claims
+        let buyer_ledger = &mut ctx.accounts.buyer_ledger;
+        if buyer_ledger.buyer == Pubkey::default() {
+            buyer_ledger.presale = presale.key();
+            buyer_ledger.buyer = buyer.key();
+            buyer_ledger.bump = ctx.bumps.buyer_ledger;
+        }
+        let purchased = checked_sum(tokens_to_purchase, bonus_tokens)?;
+        if presale.sale_stage == 1 {
+            buyer_ledger.private_purchased = checked_sum(buyer_ledger.private_purchased, purchased)?;
+        } else {
+            buyer_ledger.public_purchased = checked_sum(buyer_ledger.public_purchased, purchased)?;
+        }
+
+        // ✅ Calculate referral reward if referrer exists
+        if referrer != Pubkey::default() && presale.referral_reward_mode == 0 {
+            let referral_reward = amounts.referral_reward;
+
+            // Ensure enough tokens exist
+            let remaining_rewards = presale.remaining_rewards(); // ✅ Supply comes from recorded deposits
+
+            require!(
+                remaining_rewards >= referral_reward, // ✅ Ensure enough reward tokens remain
+                PresaleError::InsufficientRewardTokens
+            );
+
+            // ✅ Update `referral_charged`
+            presale.referral_charged = checked_sum(presale.referral_charged, referral_reward)?;
+
+            if referral_reward > 0 {
+                emit!(ReferralRewardEvent {
+                    referrer,
+                    referred_buyer: buyer.key(),
+                    reward_amount: referral_reward,
+                    is_influencer,
+                    presale: presale.key(),
+                    purchase_seq,
+                });
+
+                msg!(
+                    "Referrer {} received {} tokens from buyer {}",
+                    referrer,
+                    referral_reward,
+                    buyer.key()
+                );
+            }
+        }
 
         emit!(BuyTokensByStableCoinEvent {
             buyer: buyer.key(),
             tokens_purchased: tokens_to_purchase,
+            bonus_tokens,
             stable_coin_amount,
             payment_type,
+            presale: presale.key(),
+            purchase_seq,
+            sale_stage: presale.sale_stage,
+            price: presale.current_price,
+            referrer,
+            total_sold: presale.total_sold,
+            referral_charged: presale.referral_charged,
+            platform_tokens_charged: presale.platform_tokens_charged,
+            total_raised_stable: presale.total_raised_stable,
+            slot: Clock::get()?.slot,
         });
 
         msg!(
@@ -464,6 +1456,21 @@ pub mod presale {
             regular_referral_rate <= 100 && influencer_referral_rate <= 100,
             PresaleError::InvalidRate
         );
+        require!(
+            payment_split_fits(
+                regular_referral_rate,
+                influencer_referral_rate,
+                presale.platform_raise_fee_bps
+            ),
+            PresaleError::InvalidRate
+        );
+
+        let action = AdminAction::SetReferralRate {
+            old_regular_rate: presale.regular_referral_rate,
+            new_regular_rate: regular_referral_rate,
+            old_influencer_rate: presale.influencer_referral_rate,
+            new_influencer_rate: influencer_referral_rate,
+        };
 
         // ✅ Update referral rates
         presale.regular_referral_rate = regular_referral_rate;
@@ -474,8 +1481,14 @@ pub mod presale {
             admin: ctx.accounts.admin.key(),
             regular_referral_rate,
             influencer_referral_rate,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
         });
 
+        emit_admin_action(presale.key(), presale.admin, action)?;
+
         msg!(
             "Referral rates updated: Regular = {}%, Influencer = {}%",
             regular_referral_rate,
@@ -485,333 +1498,3900 @@ pub mod presale {
         Ok(())
     }
 
-    pub fn finalize_presale(ctx: Context<FinalizePresale>) -> Result<()> {
-        // ✅ 1. Extract the bump first before borrowing presale mutably
-        let presale_info = ctx.accounts.presale.to_account_info(); // ✅ Extract AccountInfo before mutable borrow
-        let admin_key = ctx.accounts.admin.key(); // ✅ Extract admin key before mutable borrow
-        let bump = ctx.bumps.presale;
+    pub fn set_referral_reward_mode(
+        ctx: Context<SetReferralRewardMode>,
+        referral_reward_mode: u8, // 0 = Sale tokens, 1 = Payment currency (SOL/USDC)
+    ) -> Result<()> {
         let presale = &mut ctx.accounts.presale;
 
         // ✅ Ensure the caller is the admin
-        require!(presale.admin == admin_key, PresaleError::Unauthorized);
-
-        // ✅ 1. Ensure presale has ended
-        require!(presale.sale_stage == 3, PresaleError::PresaleActive);
-
-        // ✅ 2. Check if liquidity pool has already been created
         require!(
-            !presale.pool_created,
-            PresaleError::LiquidityPoolAlreadyCreated
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
         );
 
-        // ✅ 3. Calculate unsold presale tokens
-        let available_presale_tokens = ctx.accounts.presale_wallet.amount;
-        let unsold_presale_tokens =
-            available_presale_tokens.saturating_sub(presale.total_sold * 1_000_000_000); // Adjust decimals
+        require!(referral_reward_mode <= 1, PresaleError::InvalidReferralRewardMode);
 
-        // ✅ 4. Calculate unsold reward tokens
-        let available_reward_tokens = ctx.accounts.referral_wallet.amount;
-        let unsold_reward_tokens =
-            available_reward_tokens.saturating_sub(presale.referral_charged * 1_000_000_000); // Adjust decimals
+        let old_mode = presale.referral_reward_mode;
+        presale.referral_reward_mode = referral_reward_mode;
 
-        let seeds: &[&[u8]] = &[
-            PRESALE_SEED,
-            admin_key.as_ref(), // ✅ Use extracted key instead of ctx.accounts.presale.admin
-            &[bump],
-        ];
+        emit!(SetReferralRewardModeEvent {
+            admin: ctx.accounts.admin.key(),
+            referral_reward_mode,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
+        });
 
-        let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
-        // ✅ Transfer unsold presale tokens to liquidity wallet if any exist
-        if unsold_presale_tokens > 0 {
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    TokenTransfer {
-                        from: ctx.accounts.presale_wallet.to_account_info(),
-                        to: ctx.accounts.liquidity_wallet.to_account_info(),
-                        authority: presale_info.clone(), // ✅ Use the extracted value here
-                    },
-                    signer_seeds,
-                ),
-                unsold_presale_tokens,
-            )?;
-        }
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetReferralRewardMode {
+                old_mode,
+                new_mode: referral_reward_mode,
+            },
+        )?;
 
-        // ✅ Transfer unsold referral tokens to liquidity wallet if any exist
-        if unsold_reward_tokens > 0 {
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    TokenTransfer {
-                        from: ctx.accounts.referral_wallet.to_account_info(),
-                        to: ctx.accounts.liquidity_wallet.to_account_info(),
-                        authority: presale_info.clone(), // ✅ Use the extracted value here
-                    },
-                    signer_seeds,
-                ),
-                unsold_reward_tokens,
-            )?;
-        }
+        msg!("Referral reward mode updated to {}", referral_reward_mode);
 
-        // ✅ 5. Mark liquidity pool as created
-        presale.pool_created = true;
+        Ok(())
+    }
 
-        // ✅ 6. Emit an event for tracking
-        emit!(FinalizePresaleEvent {
+    pub fn set_buyer_bonus_rate(ctx: Context<SetBuyerBonusRate>, buyer_bonus_rate: u8) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
+
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        // ✅ Ensure bonus rate is between 0-100%
+        require!(buyer_bonus_rate <= 100, PresaleError::InvalidRate);
+
+        let old_rate = presale.buyer_bonus_rate;
+        presale.buyer_bonus_rate = buyer_bonus_rate;
+
+        emit!(SetBuyerBonusRateEvent {
             admin: ctx.accounts.admin.key(),
-            unsold_presale_tokens,
-            unsold_reward_tokens,
+            buyer_bonus_rate,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
         });
 
-        msg!(
-            "Presale finalized! {} unsold presale tokens & {} unsold referral tokens moved to liquidity wallet.",
-            unsold_presale_tokens,
-            unsold_reward_tokens
-        );
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetBuyerBonusRate {
+                old_rate,
+                new_rate: buyer_bonus_rate,
+            },
+        )?;
+
+        msg!("Buyer referral bonus updated: {}%", buyer_bonus_rate);
 
         Ok(())
     }
 
-}
+    pub fn set_sol_price(ctx: Context<SetSolPrice>, sol_price_in_usd: u64) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
 
-#[derive(Accounts)]
-#[instruction(private_price: u64, public_price: u64, private_sale_duration: i64, public_sale_duration: i64, regular_referral_rate: u8, influencer_referral_rate: u8)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // Admin who deploys the contract
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
 
-    #[account(
-        init,
-        payer = admin,
-        seeds = [PRESALE_SEED, admin.key().as_ref()],  // Derive Presale PDA
-        bump,
-        space = 8 +  // Discriminator
-               32 +  // Admin pubkey
-               8 +   //  Presale start
-               8 +   // Private price
-               8 +  // Public price
-               8 +  // current price
-               8 +  // Private sale duration
-               8 +  // Public sale duration
-               8 +  // Sale stage
-               8 +  // Total sold
-               8 +  // Referral Charged
-               1 +  // Pool created flag
-               32 + // Presale wallet
-               32 + // Referral wallet
-               32 + // Liquidity wallet
-               32 + // Merchant wallet
-               1 +  // Regular referral rate
-               1 +   // Influencer referral rate
-               1
-    )]
-    pub presale: Account<'info, Presale>, // Stores presale details
+        require!(sol_price_in_usd > 0, PresaleError::InvalidPrice);
 
-    pub token_mint: Account<'info, Mint>, // DYAWN token mint
+        let old_price = presale.sol_price_in_usd;
+        presale.sol_price_in_usd = sol_price_in_usd;
 
-    #[account(init, payer = admin, token::mint = token_mint, token::authority = presale)]
-    pub presale_wallet: Account<'info, TokenAccount>,
+        emit!(SetSolPriceEvent {
+            admin: ctx.accounts.admin.key(),
+            sol_price_in_usd,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
+        });
 
-    #[account(init, payer = admin, token::mint = token_mint, token::authority = presale)]
-    pub referral_wallet: Account<'info, TokenAccount>, // Storage for referral rewards
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetSolPrice {
+                old_price,
+                new_price: sol_price_in_usd,
+            },
+        )?;
 
-    #[account(mut)]
-    pub merchant_wallet: AccountInfo<'info>,
+        msg!("SOL price updated to ${}", sol_price_in_usd);
 
-    pub system_program: Program<'info, System>, // Required system program
-    pub token_program: Program<'info, Token>,   // Required token program
-    pub associated_token_program: Program<'info, AssociatedToken>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct SetStage<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // Only admin can change sale stage
+    pub fn set_payment_authority(ctx: Context<SetPaymentAuthority>, payment_authority: Pubkey) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
 
-    #[account(
-        mut,
-        has_one = admin, // Ensures the stored presale.admin matches the Signer
-        seeds = [PRESALE_SEED, admin.key().as_ref()],
-        bump
-    )]
-    pub presale: Account<'info, Presale>,
-}
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
 
-#[derive(Accounts)]
-pub struct UpdateSalePeriod<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // Only the admin can update the sale period
+        let old_authority = presale.payment_authority;
+        presale.payment_authority = payment_authority;
 
-    #[account(
-        mut,
-        has_one = admin, // Ensures only the admin can update the sale period
-        seeds = [PRESALE_SEED, admin.key().as_ref()],
-        bump
-    )]
-    pub presale: Account<'info, Presale>,
-}
+        emit!(SetPaymentAuthorityEvent {
+            admin: ctx.accounts.admin.key(),
+            payment_authority,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
+        });
 
-#[derive(Accounts)]
-pub struct BuyTokens<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>, // The user buying tokens
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetPaymentAuthority {
+                old_authority,
+                new_authority: payment_authority,
+            },
+        )?;
 
-    #[account(
-        mut,
-        seeds = [PRESALE_SEED, presale.admin.as_ref()], 
-        bump,
-    )]
-    pub presale: Account<'info, Presale>, // Presale storage PDA
+        msg!("Web2 payment authority updated to {}", payment_authority);
 
-    #[account(mut)]
-    pub presale_wallet: Account<'info, TokenAccount>, // Store presale tokens
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub referral_wallet: Account<'info, TokenAccount>, // Store Reward tokens
+    pub fn set_vesting_schedule(
+        ctx: Context<SetVestingSchedule>,
+        sale_stage: u8, // 1 = Private Sale, 2 = Public Sale
+        tge_percent: u8,
+        cliff_seconds: i64,
+        vesting_duration: i64,
+    ) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
 
-    #[account(mut, address = presale.merchant_wallet)] // ✅ Ensures correct merchant wallet
-    pub merchant_wallet: AccountInfo<'info>,
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
 
-    pub system_program: Program<'info, System>, // Required for SOL transfer
-}
+        // ✅ Schedules are locked once TGE has happened
+        require!(
+            !presale.pool_created,
+            PresaleError::LiquidityPoolAlreadyCreated
+        );
 
-#[derive(Accounts)]
-pub struct CheckPresaleTokenBalance<'info> {
-    #[account(
-        mut,
-        seeds = [PRESALE_SEED, presale.admin.as_ref()],
-        bump,
-    )]
-    pub presale: Account<'info, Presale>, // Presale storage PDA
+        require!(tge_percent <= 100, PresaleError::InvalidRate);
+        require!(
+            cliff_seconds >= 0 && vesting_duration >= 0,
+            PresaleError::InvalidVestingSchedule
+        );
 
-    #[account(mut)]
-    pub presale_wallet: Account<'info, TokenAccount>, // Store presale tokens
-}
+        let schedule = VestingSchedule {
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+        };
 
-#[derive(Accounts)]
-pub struct UpdateSalePrice<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // Only the admin can update the price
+        let old_schedule = match sale_stage {
+            1 => std::mem::replace(&mut presale.private_vesting, schedule),
+            2 => std::mem::replace(&mut presale.public_vesting, schedule),
+            _ => return Err(PresaleError::InvalidSaleStage.into()),
+        };
 
-    #[account(
-        mut,
-        has_one = admin, // Ensures only the admin can update
-        seeds = [PRESALE_SEED, admin.key().as_ref()],
-        bump
-    )]
-    pub presale: Account<'info, Presale>,
-}
+        emit!(SetVestingScheduleEvent {
+            admin: ctx.accounts.admin.key(),
+            sale_stage,
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+            presale: presale.key(),
+            current_stage: presale.sale_stage,
+            current_price: presale.current_price,
+            slot: Clock::get()?.slot,
+        });
 
-#[derive(Accounts)]
-pub struct BuyTokensByStableCoin<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>, // The user buying tokens
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetVestingSchedule {
+                sale_stage,
+                old_schedule,
+                new_schedule: schedule,
+            },
+        )?;
 
-    #[account(
-        mut,
-        seeds = [PRESALE_SEED, presale.admin.as_ref()],
-        bump,
-    )]
-    pub presale: Account<'info, Presale>, // Presale storage PDA
+        msg!(
+            "Vesting for stage {} updated: TGE = {}%, cliff = {}s, duration = {}s",
+            sale_stage,
+            tge_percent,
+            cliff_seconds,
+            vesting_duration
+        );
+
+        Ok(())
+    }
+
+    pub fn claim_tokens(ctx: Context<ClaimTokens>) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let buyer_ledger = &mut ctx.accounts.buyer_ledger;
+
+        // ✅ Tokens can only be claimed after the pool is created (TGE)
+        require!(presale.pool_created, PresaleError::LiquidityPoolNotCreated);
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // ✅ Vested amount per stage, computed against the buyer's ledger
+        let claimable = buyer_ledger.claimable(presale, now);
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.presale_wallet.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            claimable * 1_000_000_000, // Adjust decimals
+        )?;
+
+        buyer_ledger.claimed += claimable;
+        ctx.accounts.presale.reserved_tokens -= claimable * 1_000_000_000;
+
+        emit!(ClaimTokensEvent {
+            buyer: ctx.accounts.buyer.key(),
+            amount: claimable,
+            total_claimed: buyer_ledger.claimed,
+            presale: ctx.accounts.presale.key(),
+            reserved_tokens: ctx.accounts.presale.reserved_tokens,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "Buyer {} claimed {} tokens ({} claimed in total)",
+            ctx.accounts.buyer.key(),
+            claimable,
+            buyer_ledger.claimed
+        );
+
+        Ok(())
+    }
+
+    pub fn create_grant(
+        ctx: Context<CreateGrant>,
+        amount: u64, // Whole tokens locked in the grant
+        start: i64,  // Vesting start timestamp
+        tge_percent: u8,
+        cliff_seconds: i64,
+        vesting_duration: i64,
+        revocable: bool,
+    ) -> Result<()> {
+        // ✅ Ensure the caller is the admin
+        require!(
+            ctx.accounts.presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        require!(amount > 0, PresaleError::InvalidAmount);
+        require!(tge_percent <= 100, PresaleError::InvalidRate);
+        require!(
+            cliff_seconds >= 0 && vesting_duration >= 0,
+            PresaleError::InvalidVestingSchedule
+        );
+
+        // ✅ Lock the allocation in the grant vault
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.grant_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount * 1_000_000_000, // Adjust decimals
+        )?;
+
+        let grant = &mut ctx.accounts.grant;
+        grant.presale = ctx.accounts.presale.key();
+        grant.beneficiary = ctx.accounts.beneficiary.key();
+        grant.vault = ctx.accounts.grant_vault.key();
+        grant.total_amount = amount;
+        grant.claimed = 0;
+        grant.start = start;
+        grant.schedule = VestingSchedule {
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+        };
+        grant.revocable = revocable;
+        grant.revoked = false;
+        grant.bump = ctx.bumps.grant;
+
+        emit!(CreateGrantEvent {
+            admin: ctx.accounts.admin.key(),
+            beneficiary: grant.beneficiary,
+            amount,
+            start,
+            tge_percent,
+            cliff_seconds,
+            vesting_duration,
+            revocable,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::CreateGrant {
+                beneficiary: grant.beneficiary,
+                amount,
+                start,
+                schedule: grant.schedule,
+                revocable,
+            },
+        )?;
+
+        msg!(
+            "Vesting grant of {} tokens created for {}",
+            amount,
+            grant.beneficiary
+        );
+
+        Ok(())
+    }
+
+    pub fn claim_grant(ctx: Context<ClaimGrant>) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let grant = &mut ctx.accounts.grant;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vested = grant
+            .schedule
+            .vested_amount(grant.total_amount, grant.start, now);
+
+        let claimable = vested.saturating_sub(grant.claimed);
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.grant_vault.to_account_info(),
+                    to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            claimable * 1_000_000_000, // Adjust decimals
+        )?;
+
+        grant.claimed += claimable;
+
+        emit!(ClaimGrantEvent {
+            beneficiary: grant.beneficiary,
+            amount: claimable,
+            total_claimed: grant.claimed,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "Beneficiary {} claimed {} granted tokens",
+            grant.beneficiary,
+            claimable
+        );
+
+        Ok(())
+    }
+
+    pub fn revoke_grant(ctx: Context<RevokeGrant>) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let grant = &mut ctx.accounts.grant;
+
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        require!(
+            grant.revocable && !grant.revoked,
+            PresaleError::GrantNotRevocable
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let vested = grant
+            .schedule
+            .vested_amount(grant.total_amount, grant.start, now);
+        let unvested = grant.total_amount - vested;
+
+        // ✅ Return the unvested part to the admin
+        if unvested > 0 {
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.grant_vault.to_account_info(),
+                        to: ctx.accounts.admin_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                unvested * 1_000_000_000, // Adjust decimals
+            )?;
+        }
+
+        // ✅ Freeze the grant at what has vested, which stays claimable
+        let old_amount = grant.total_amount;
+        grant.total_amount = vested;
+        grant.schedule = VestingSchedule {
+            tge_percent: 100,
+            cliff_seconds: 0,
+            vesting_duration: 0,
+        };
+        grant.start = grant.start.min(now);
+        grant.revoked = true;
+
+        emit!(RevokeGrantEvent {
+            admin: ctx.accounts.admin.key(),
+            beneficiary: grant.beneficiary,
+            vested_amount: vested,
+            returned_amount: unvested,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::RevokeGrant {
+                beneficiary: grant.beneficiary,
+                old_amount,
+                new_amount: grant.total_amount,
+                returned_amount: unvested,
+            },
+        )?;
+
+        msg!(
+            "Vesting grant for {} revoked, {} tokens returned",
+            grant.beneficiary,
+            unvested
+        );
+
+        Ok(())
+    }
+
+    pub fn create_airdrop(
+        ctx: Context<CreateAirdrop>,
+        airdrop_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64, // Whole tokens covered by the Merkle tree
+        num_recipients: u32,
+        expiry: i64, // Claims close and the admin can claw back after this
+    ) -> Result<()> {
+        // ✅ Ensure the caller is the admin
+        require!(
+            ctx.accounts.presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        require!(total_amount > 0, PresaleError::InvalidAmount);
+        require!(
+            num_recipients > 0 && num_recipients <= MAX_AIRDROP_RECIPIENTS,
+            PresaleError::InvalidAmount
+        );
+        require!(
+            expiry > Clock::get()?.unix_timestamp,
+            PresaleError::AirdropExpired
+        );
+
+        // ✅ Fund the airdrop vault from the admin's allocation
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.airdrop_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            total_amount * 1_000_000_000, // Adjust decimals
+        )?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.presale = ctx.accounts.presale.key();
+        distributor.airdrop_id = airdrop_id;
+        distributor.merkle_root = merkle_root;
+        distributor.vault = ctx.accounts.airdrop_vault.key();
+        distributor.total_amount = total_amount;
+        distributor.total_claimed = 0;
+        distributor.num_recipients = num_recipients;
+        distributor.expiry = expiry;
+        distributor.clawed_back = false;
+        distributor.bump = ctx.bumps.distributor;
+        distributor.claim_bitmap = vec![0u8; (num_recipients as usize).div_ceil(8)];
+
+        emit!(CreateAirdropEvent {
+            admin: ctx.accounts.admin.key(),
+            airdrop_id,
+            merkle_root,
+            total_amount,
+            num_recipients,
+            expiry,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::CreateAirdrop {
+                airdrop_id,
+                merkle_root,
+                total_amount,
+                num_recipients,
+                expiry,
+            },
+        )?;
+
+        msg!(
+            "Airdrop {} created: {} tokens for {} recipients",
+            airdrop_id,
+            total_amount,
+            num_recipients
+        );
+
+        Ok(())
+    }
+
+    pub fn claim_airdrop(
+        ctx: Context<ClaimAirdrop>,
+        index: u64,
+        amount: u64, // Whole tokens allocated to the claimant
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let distributor = &mut ctx.accounts.distributor;
+        let claimant = ctx.accounts.claimant.key();
+
+        require!(
+            !distributor.clawed_back && Clock::get()?.unix_timestamp < distributor.expiry,
+            PresaleError::AirdropExpired
+        );
+        require!(
+            index < distributor.num_recipients as u64,
+            PresaleError::InvalidMerkleProof
+        );
+
+        // ✅ Each leaf can only be claimed once
+        let byte = (index / 8) as usize;
+        let bit = 1u8 << (index % 8);
+        require!(
+            distributor.claim_bitmap[byte] & bit == 0,
+            PresaleError::AirdropAlreadyClaimed
+        );
+
+        let leaf = keccak::hashv(&[
+            &[0u8],
+            &index.to_le_bytes(),
+            claimant.as_ref(),
+            &amount.to_le_bytes(),
+        ])
+        .0;
+        require!(
+            verify_merkle_proof(&proof, distributor.merkle_root, leaf),
+            PresaleError::InvalidMerkleProof
+        );
+
+        distributor.claim_bitmap[byte] |= bit;
+        distributor.total_claimed += amount;
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.airdrop_vault.to_account_info(),
+                    to: ctx.accounts.claimant_token_account.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount * 1_000_000_000, // Adjust decimals
+        )?;
+
+        emit!(ClaimAirdropEvent {
+            airdrop_id: distributor.airdrop_id,
+            claimant,
+            index,
+            amount,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "Claimant {} received {} airdrop tokens (index {})",
+            claimant,
+            amount,
+            index
+        );
+
+        Ok(())
+    }
+
+    pub fn clawback_airdrop(ctx: Context<ClawbackAirdrop>) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let distributor = &mut ctx.accounts.distributor;
+
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        // ✅ Unclaimed tokens can only be recovered after expiry
+        require!(
+            Clock::get()?.unix_timestamp >= distributor.expiry,
+            PresaleError::AirdropNotExpired
+        );
+        require!(!distributor.clawed_back, PresaleError::AirdropExpired);
+
+        let unclaimed = ctx.accounts.airdrop_vault.amount;
+
+        if unclaimed > 0 {
+            let seed_key = presale.seed_key();
+            let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TokenTransfer {
+                        from: ctx.accounts.airdrop_vault.to_account_info(),
+                        to: ctx.accounts.admin_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                unclaimed,
+            )?;
+        }
+
+        distributor.clawed_back = true;
+
+        emit!(ClawbackAirdropEvent {
+            admin: ctx.accounts.admin.key(),
+            airdrop_id: distributor.airdrop_id,
+            unclaimed_amount: unclaimed,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::ClawbackAirdrop {
+                airdrop_id: distributor.airdrop_id,
+                unclaimed_amount: unclaimed,
+            },
+        )?;
+
+        msg!(
+            "Airdrop {} closed, {} unclaimed base units returned to admin",
+            distributor.airdrop_id,
+            unclaimed
+        );
+
+        Ok(())
+    }
+
+    pub fn init_stake_pool(
+        ctx: Context<InitStakePool>,
+        reward_rate: u64, // Reward base units emitted per second across all stakers
+        lockup_options: Vec<LockupOption>,
+    ) -> Result<()> {
+        // ✅ Ensure the caller is the admin
+        require!(
+            ctx.accounts.presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        validate_lockup_options(&lockup_options)?;
+
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        stake_pool.admin = ctx.accounts.admin.key();
+        stake_pool.presale = ctx.accounts.presale.key();
+        stake_pool.token_mint = ctx.accounts.token_mint.key();
+        stake_pool.stake_vault = ctx.accounts.stake_vault.key();
+        stake_pool.reward_vault = ctx.accounts.reward_vault.key();
+        stake_pool.reward_rate = reward_rate;
+        stake_pool.reward_per_token_stored = 0;
+        stake_pool.last_update_time = Clock::get()?.unix_timestamp;
+        stake_pool.total_staked = 0;
+        stake_pool.total_weighted = 0;
+        stake_pool.reward_unallocated = 0;
+        stake_pool.private_buyer_bonus_bps = 0;
+        stake_pool.lockup_options = lockup_options.clone();
+        stake_pool.bump = ctx.bumps.stake_pool;
+
+        emit_admin_action(
+            stake_pool.presale,
+            stake_pool.admin,
+            AdminAction::InitStakePool {
+                reward_rate,
+                lockup_options,
+            },
+        )?;
+
+        msg!(
+            "Stake pool initialized for mint {} at {} reward units/s",
+            stake_pool.token_mint,
+            reward_rate
+        );
+
+        Ok(())
+    }
+
+    pub fn set_emission_rate(ctx: Context<UpdateStakePool>, reward_rate: u64) -> Result<()> {
+        let stake_pool = &mut ctx.accounts.stake_pool;
+
+        // ✅ Accrue at the old rate before switching
+        stake_pool.update_rewards(Clock::get()?.unix_timestamp);
+        let old_rate = std::mem::replace(&mut stake_pool.reward_rate, reward_rate);
+
+        emit!(SetEmissionRateEvent {
+            admin: ctx.accounts.admin.key(),
+            reward_rate,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.stake_pool.presale,
+            ctx.accounts.admin.key(),
+            AdminAction::SetEmissionRate {
+                old_rate,
+                new_rate: reward_rate,
+            },
+        )?;
+
+        msg!("Staking emission rate updated to {} units/s", reward_rate);
+
+        Ok(())
+    }
+
+    pub fn set_lockup_options(
+        ctx: Context<UpdateStakePool>,
+        lockup_options: Vec<LockupOption>,
+    ) -> Result<()> {
+        validate_lockup_options(&lockup_options)?;
+
+        // Existing positions keep the multiplier they staked with
+        let old_options = std::mem::replace(
+            &mut ctx.accounts.stake_pool.lockup_options,
+            lockup_options.clone(),
+        );
+
+        emit!(SetLockupOptionsEvent {
+            admin: ctx.accounts.admin.key(),
+            lockup_options: lockup_options.clone(),
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.stake_pool.presale,
+            ctx.accounts.admin.key(),
+            AdminAction::SetLockupOptions {
+                old_options,
+                new_options: lockup_options,
+            },
+        )?;
+
+        msg!("Staking lockup options updated");
+
+        Ok(())
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, PresaleError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        stake_pool.update_rewards(Clock::get()?.unix_timestamp);
+        let old_reward_unallocated = stake_pool.reward_unallocated;
+        stake_pool.reward_unallocated += amount;
+
+        emit!(FundRewardsEvent {
+            admin: ctx.accounts.admin.key(),
+            amount,
+            reward_unallocated: stake_pool.reward_unallocated,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.stake_pool.presale,
+            ctx.accounts.admin.key(),
+            AdminAction::FundRewards {
+                amount,
+                old_reward_unallocated,
+                new_reward_unallocated: ctx.accounts.stake_pool.reward_unallocated,
+            },
+        )?;
+
+        msg!("Reward vault topped up with {} units", amount);
+
+        Ok(())
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64, lockup_option: u8) -> Result<()> {
+        require!(amount > 0, PresaleError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let position = &mut ctx.accounts.stake_position;
+        if position.owner == Pubkey::default() {
+            position.stake_pool = stake_pool.key();
+            position.owner = ctx.accounts.owner.key();
+            position.bump = ctx.bumps.stake_position;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        add_stake(stake_pool, position, amount, lockup_option, 0, now)?;
+
+        emit!(StakeEvent {
+            owner: position.owner,
+            amount,
+            total_staked: position.amount,
+            multiplier_bps: position.multiplier_bps + position.bonus_bps,
+            lock_until: position.lock_until,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "{} staked {} units, locked until {}",
+            position.owner,
+            amount,
+            position.lock_until
+        );
+
+        Ok(())
+    }
+
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let position = &mut ctx.accounts.stake_position;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(amount > 0, PresaleError::InvalidAmount);
+        require!(amount <= position.amount, PresaleError::InsufficientStake);
+        require!(now >= position.lock_until, PresaleError::StakeLocked);
+
+        stake_pool.update_rewards(now);
+        position.settle(stake_pool, now);
+
+        let weighted_before = position.weighted_amount;
+        position.amount -= amount;
+        position.weighted_amount = position.weight();
+        stake_pool.total_staked -= amount;
+        stake_pool.total_weighted = stake_pool.total_weighted - weighted_before + position.weighted_amount;
+
+        let presale_key = stake_pool.presale;
+        let seeds: &[&[u8]] = &[STAKE_POOL_SEED, presale_key.as_ref(), &[stake_pool.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: stake_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(UnstakeEvent {
+            owner: position.owner,
+            amount,
+            total_staked: position.amount,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("{} unstaked {} units", position.owner, amount);
+
+        Ok(())
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let position = &mut ctx.accounts.stake_position;
+
+        let now = Clock::get()?.unix_timestamp;
+        stake_pool.update_rewards(now);
+        position.settle(stake_pool, now);
+
+        let rewards = position.rewards_owed;
+        require!(rewards > 0, PresaleError::NothingToClaim);
+        position.rewards_owed = 0;
+
+        let presale_key = stake_pool.presale;
+        let seeds: &[&[u8]] = &[STAKE_POOL_SEED, presale_key.as_ref(), &[stake_pool.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: stake_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            rewards,
+        )?;
+
+        emit!(ClaimRewardsEvent {
+            owner: position.owner,
+            amount: rewards,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("{} claimed {} reward units", position.owner, rewards);
+
+        Ok(())
+    }
+
+    pub fn set_private_buyer_bonus(
+        ctx: Context<UpdateStakePool>,
+        private_buyer_bonus_bps: u16,
+    ) -> Result<()> {
+        require!(
+            private_buyer_bonus_bps <= MAX_PRIVATE_BUYER_BONUS_BPS,
+            PresaleError::InvalidRate
+        );
+
+        let old_bps = std::mem::replace(
+            &mut ctx.accounts.stake_pool.private_buyer_bonus_bps,
+            private_buyer_bonus_bps,
+        );
+
+        emit!(SetPrivateBuyerBonusEvent {
+            admin: ctx.accounts.admin.key(),
+            private_buyer_bonus_bps,
+            presale: ctx.accounts.stake_pool.presale,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.stake_pool.presale,
+            ctx.accounts.admin.key(),
+            AdminAction::SetPrivateBuyerBonus {
+                old_bps,
+                new_bps: private_buyer_bonus_bps,
+            },
+        )?;
+
+        msg!(
+            "Private buyer staking bonus updated to {} bps",
+            private_buyer_bonus_bps
+        );
+
+        Ok(())
+    }
+
+    pub fn stake_from_claim(ctx: Context<StakeFromClaim>, lockup_option: u8) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let buyer_ledger = &mut ctx.accounts.buyer_ledger;
+
+        // ✅ Tokens can only be claimed after the pool is created (TGE)
+        require!(presale.pool_created, PresaleError::LiquidityPoolNotCreated);
+
+        let now = Clock::get()?.unix_timestamp;
+        let claimable = buyer_ledger.claimable(presale, now);
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        // ✅ Move the claimable balance straight into the stake vault
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.presale_wallet.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            claimable * 1_000_000_000, // Adjust decimals
+        )?;
+
+        buyer_ledger.claimed += claimable;
+        ctx.accounts.presale.reserved_tokens -= claimable * 1_000_000_000;
+
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let position = &mut ctx.accounts.stake_position;
+        if position.owner == Pubkey::default() {
+            position.stake_pool = stake_pool.key();
+            position.owner = ctx.accounts.buyer.key();
+            position.bump = ctx.bumps.stake_position;
+        }
+
+        // ✅ Private-round buyers earn the bonus multiplier when they commit to a lock
+        let bonus_bps = if buyer_ledger.private_purchased > 0 {
+            stake_pool.private_buyer_bonus_bps
+        } else {
+            0
+        };
+
+        add_stake(
+            stake_pool,
+            position,
+            claimable * 1_000_000_000,
+            lockup_option,
+            bonus_bps,
+            now,
+        )?;
+
+        emit!(ClaimTokensEvent {
+            buyer: ctx.accounts.buyer.key(),
+            amount: claimable,
+            total_claimed: buyer_ledger.claimed,
+            presale: ctx.accounts.presale.key(),
+            reserved_tokens: ctx.accounts.presale.reserved_tokens,
+            slot: Clock::get()?.slot,
+        });
+
+        emit!(StakeEvent {
+            owner: position.owner,
+            amount: claimable * 1_000_000_000,
+            total_staked: position.amount,
+            multiplier_bps: position.multiplier_bps + position.bonus_bps,
+            lock_until: position.lock_until,
+            presale: ctx.accounts.presale.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "Buyer {} staked {} claimed tokens, locked until {}",
+            position.owner,
+            claimable,
+            position.lock_until
+        );
+
+        Ok(())
+    }
+
+    pub fn finalize_presale(ctx: Context<FinalizePresale>) -> Result<()> {
+        // ✅ 1. Extract the bump first before borrowing presale mutably
+        let presale_info = ctx.accounts.presale.to_account_info(); // ✅ Extract AccountInfo before mutable borrow
+        let admin_key = ctx.accounts.admin.key(); // ✅ Extract admin key before mutable borrow
+        let bump = ctx.bumps.presale;
+        let presale = &mut ctx.accounts.presale;
+
+        // ✅ Ensure the caller is the admin
+        require!(presale.admin == admin_key, PresaleError::Unauthorized);
+
+        // ✅ 1. Ensure presale has ended
+        require!(presale.sale_stage == 3, PresaleError::PresaleActive);
+
+        // ✅ 2. Check if liquidity pool has already been created
+        require!(
+            !presale.pool_created,
+            PresaleError::LiquidityPoolAlreadyCreated
+        );
+
+        // ✅ Sales configured to seed a pool must use `finalize_presale_with_pool`
+        require!(
+            !presale.pool_config.enabled,
+            PresaleError::PoolConfigMismatch
+        );
+
+        // ✅ 3. Calculate unsold presale tokens, sold tokens stay reserved for claims
+        let available_presale_tokens = ctx.accounts.presale_wallet.amount;
+        require!(
+            available_presale_tokens >= presale.reserved_tokens,
+            PresaleError::InsufficientTokens
+        );
+        let unsold_presale_tokens = available_presale_tokens - presale.reserved_tokens;
+
+        // ✅ 4. Calculate unsold reward tokens, buyer bonuses already left the referral wallet
+        let available_reward_tokens = ctx.accounts.referral_wallet.amount;
+        let unallocated_rewards = presale.total_reward_deposited - presale.referral_charged;
+        let unsold_reward_tokens = available_reward_tokens.min(unallocated_rewards * 1_000_000_000); // Adjust decimals
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[
+            presale.seed_prefix(),
+            &seed_key, // ✅ Sale id, or the admin for sales migrated from v1
+            &[bump],
+        ];
+
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        let disposition = presale.unsold_disposition;
+
+        // ✅ Burn / return / add to liquidity the unsold presale tokens per the sale's policy
+        let presale_split = dispose_unsold(
+            &disposition,
+            unsold_presale_tokens,
+            ctx.accounts.presale_wallet.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.treasury_wallet.to_account_info(),
+            ctx.accounts.liquidity_wallet.to_account_info(),
+            presale_info.clone(), // ✅ Use the extracted value here
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+
+        // ✅ Same policy for the unsold referral tokens
+        let reward_split = dispose_unsold(
+            &disposition,
+            unsold_reward_tokens,
+            ctx.accounts.referral_wallet.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.treasury_wallet.to_account_info(),
+            ctx.accounts.liquidity_wallet.to_account_info(),
+            presale_info.clone(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+
+        // ✅ 5. Mark liquidity pool as created, vesting starts from here
+        presale.pool_created = true;
+        presale.tge_timestamp = Clock::get()?.unix_timestamp;
+
+        // ✅ 6. Emit an event for tracking
+        emit!(FinalizePresaleEvent {
+            admin: ctx.accounts.admin.key(),
+            unsold_presale_tokens,
+            unsold_reward_tokens,
+            burned_tokens: presale_split.0 + reward_split.0,
+            treasury_tokens: presale_split.1 + reward_split.1,
+            liquidity_tokens: presale_split.2 + reward_split.2,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            admin_key,
+            AdminAction::Finalize {
+                with_pool: false,
+                tge_timestamp: presale.tge_timestamp,
+                unsold_presale_tokens,
+                unsold_reward_tokens,
+            },
+        )?;
+
+        msg!(
+            "Presale finalized! Unsold tokens: {} burned, {} to treasury, {} to liquidity wallet.",
+            presale_split.0 + reward_split.0,
+            presale_split.1 + reward_split.1,
+            presale_split.2 + reward_split.2
+        );
+
+        Ok(())
+    }
+
+    pub fn set_pool_config(
+        ctx: Context<SetPoolConfig>,
+        enabled: bool,
+        quote_mint: Pubkey, // WSOL or USDC
+        raise_percent: u8,  // % of raised funds paired into the pool
+        token_amount: u64,  // Whole sale tokens paired into the pool
+        lp_lock_duration: i64, // Seconds the LP tokens stay locked after finalize
+    ) -> Result<()> {
+        let presale = &mut ctx.accounts.presale;
+
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        require!(
+            !presale.pool_created,
+            PresaleError::LiquidityPoolAlreadyCreated
+        );
+        require!(raise_percent <= 100, PresaleError::InvalidRate);
+        require!(lp_lock_duration >= 0, PresaleError::InvalidLockupOption);
+        require!(
+            quote_mint == token::spl_token::native_mint::ID
+                || ctx.accounts.config.accepted_stable_mints.contains(&quote_mint),
+            PresaleError::InvalidStableToken
+        );
+
+        let new_config = PoolConfig {
+            enabled,
+            quote_mint,
+            raise_percent,
+            token_amount,
+            lp_lock_duration,
+        };
+        let old_config = std::mem::replace(&mut presale.pool_config, new_config);
+
+        emit!(SetPoolConfigEvent {
+            admin: ctx.accounts.admin.key(),
+            enabled,
+            quote_mint,
+            raise_percent,
+            token_amount,
+            lp_lock_duration,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            presale.admin,
+            AdminAction::SetPoolConfig {
+                old_config,
+                new_config,
+            },
+        )?;
+
+        msg!(
+            "Pool config updated: enabled = {}, {}% of raise paired with {} tokens",
+            enabled,
+            raise_percent,
+            token_amount
+        );
+
+        Ok(())
+    }
+
+    pub fn finalize_presale_with_pool(
+        ctx: Context<FinalizePresaleWithPool>,
+        open_time: u64, // Pool trading start, 0 = immediately
+    ) -> Result<()> {
+        let presale_info = ctx.accounts.presale.to_account_info();
+        let admin_key = ctx.accounts.admin.key();
+        let bump = ctx.bumps.presale;
+        let presale = &mut ctx.accounts.presale;
+
+        // ✅ Ensure the caller is the admin
+        require!(presale.admin == admin_key, PresaleError::Unauthorized);
+
+        // ✅ 1. Ensure presale has ended
+        require!(presale.sale_stage == 3, PresaleError::PresaleActive);
+
+        // ✅ 2. Check if liquidity pool has already been created
+        require!(
+            !presale.pool_created,
+            PresaleError::LiquidityPoolAlreadyCreated
+        );
+        require!(presale.pool_config.enabled, PresaleError::PoolConfigMismatch);
+
+        // ✅ 3. Work out both sides of the pool
+        let pool_config = presale.pool_config;
+        let is_sol_quote = pool_config.quote_mint == token::spl_token::native_mint::ID;
+        let raised = if is_sol_quote {
+            presale.total_raised_lamports
+        } else {
+            presale.stable_raised(pool_config.quote_mint) // ✅ Only what was paid in the quote mint
+        };
+        let quote_amount = (raised as u128 * pool_config.raise_percent as u128 / 100) as u64;
+        let pool_token_amount = pool_config
+            .token_amount
+            .checked_mul(1_000_000_000) // Adjust decimals
+            .ok_or(PresaleError::InvalidAmount)?;
+
+        // Sold tokens stay reserved for claims
+        let unsold_presale_tokens = ctx
+            .accounts
+            .presale_wallet
+            .amount
+            .saturating_sub(presale.reserved_tokens);
+        require!(
+            quote_amount > 0 && pool_token_amount > 0,
+            PresaleError::InvalidAmount
+        );
+        require!(
+            unsold_presale_tokens >= pool_token_amount,
+            PresaleError::InsufficientTokens
+        );
+        // ✅ The quote side is the configured share of what the merchant actually raised. Raised
+        // SOL reaches the merchant as lamports, so the merchant wraps the pool's share into
+        // `merchant_quote_account` (WSOL) before finalizing
+        if is_sol_quote && ctx.accounts.merchant_quote_account.amount < quote_amount {
+            msg!("Wrap {} lamports into the merchant's WSOL account first", quote_amount);
+        }
+        require!(
+            ctx.accounts.merchant_quote_account.amount >= quote_amount,
+            PresaleError::InsufficientFunds
+        );
+        let unsold_presale_tokens = unsold_presale_tokens - pool_token_amount;
+
+        let unsold_reward_tokens = ctx
+            .accounts
+            .referral_wallet
+            .amount
+            .min((presale.total_reward_deposited - presale.referral_charged) * 1_000_000_000);
+
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        // ✅ 4. Move the pool's token side to the admin, who creates the pool
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.presale_wallet.to_account_info(),
+                    to: ctx.accounts.admin_token_account.to_account_info(),
+                    authority: presale_info.clone(),
+                },
+                signer_seeds,
+            ),
+            pool_token_amount,
+        )?;
+
+        // ✅ ...and the quote side from the merchant's raised funds
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.merchant_quote_account.to_account_info(),
+                    to: ctx.accounts.admin_quote_account.to_account_info(),
+                    authority: ctx.accounts.merchant.to_account_info(),
+                },
+            ),
+            quote_amount,
+        )?;
+
+        // ✅ 5. Create the constant-product pool and deposit both sides (Raydium CPMM)
+        // CPMM requires token_0 < token_1
+        let (token_0_mint, token_1_mint, creator_token_0, creator_token_1, init_amount_0, init_amount_1) =
+            if ctx.accounts.token_mint.key() < ctx.accounts.quote_mint.key() {
+                (
+                    ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.quote_mint.to_account_info(),
+                    ctx.accounts.admin_token_account.to_account_info(),
+                    ctx.accounts.admin_quote_account.to_account_info(),
+                    pool_token_amount,
+                    quote_amount,
+                )
+            } else {
+                (
+                    ctx.accounts.quote_mint.to_account_info(),
+                    ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.admin_quote_account.to_account_info(),
+                    ctx.accounts.admin_token_account.to_account_info(),
+                    quote_amount,
+                    pool_token_amount,
+                )
+            };
+
+        cpmm::initialize(
+            CpiContext::new(
+                ctx.accounts.cp_swap_program.to_account_info(),
+                cpmm::Initialize {
+                    creator: ctx.accounts.admin.to_account_info(),
+                    amm_config: ctx.accounts.amm_config.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                    pool_state: ctx.accounts.pool_state.to_account_info(),
+                    token_0_mint,
+                    token_1_mint,
+                    lp_mint: ctx.accounts.lp_mint.to_account_info(),
+                    creator_token_0,
+                    creator_token_1,
+                    creator_lp_token: ctx.accounts.admin_lp_token.to_account_info(),
+                    token_0_vault: ctx.accounts.token_0_vault.to_account_info(),
+                    token_1_vault: ctx.accounts.token_1_vault.to_account_info(),
+                    create_pool_fee: ctx.accounts.create_pool_fee.to_account_info(),
+                    observation_state: ctx.accounts.observation_state.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    token_0_program: ctx.accounts.token_program.to_account_info(),
+                    token_1_program: ctx.accounts.token_program.to_account_info(),
+                    associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+            ),
+            init_amount_0,
+            init_amount_1,
+            open_time,
+        )?;
+
+        // ✅ Lock the LP tokens in an escrow owned by the presale PDA
+        let lp_amount = {
+            let data = ctx.accounts.admin_lp_token.try_borrow_data()?;
+            TokenAccount::try_deserialize(&mut &data[..])?.amount
+        };
+        require!(
+            ctx.accounts.lp_vault.key()
+                == get_associated_token_address(&presale_info.key(), &ctx.accounts.lp_mint.key()),
+            PresaleError::InvalidTokenAccount
+        );
+
+        associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: ctx.accounts.admin.to_account_info(),
+                associated_token: ctx.accounts.lp_vault.to_account_info(),
+                authority: presale_info.clone(),
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.admin_lp_token.to_account_info(),
+                    to: ctx.accounts.lp_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            lp_amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let lp_lock = &mut ctx.accounts.lp_lock;
+        lp_lock.presale = presale_info.key();
+        lp_lock.lp_mint = ctx.accounts.lp_mint.key();
+        lp_lock.vault = ctx.accounts.lp_vault.key();
+        lp_lock.locked_amount = lp_amount;
+        lp_lock.unlock_timestamp = now + pool_config.lp_lock_duration;
+        lp_lock.unlocked = false;
+        lp_lock.bump = ctx.bumps.lp_lock;
+
+        emit!(LpLockedEvent {
+            pool_state: ctx.accounts.pool_state.key(),
+            lp_mint: lp_lock.lp_mint,
+            amount: lp_amount,
+            unlock_timestamp: lp_lock.unlock_timestamp,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        // ✅ 6. Remaining unsold tokens follow the sale's disposition policy
+        let disposition = presale.unsold_disposition;
+        let presale_split = dispose_unsold(
+            &disposition,
+            unsold_presale_tokens,
+            ctx.accounts.presale_wallet.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.treasury_wallet.to_account_info(),
+            ctx.accounts.liquidity_wallet.to_account_info(),
+            presale_info.clone(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+        let reward_split = dispose_unsold(
+            &disposition,
+            unsold_reward_tokens,
+            ctx.accounts.referral_wallet.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.treasury_wallet.to_account_info(),
+            ctx.accounts.liquidity_wallet.to_account_info(),
+            presale_info.clone(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+
+        // ✅ 7. The pool now exists, vesting starts from here
+        presale.pool_created = true;
+        presale.tge_timestamp = now;
+
+        emit!(PoolSeededEvent {
+            admin: admin_key,
+            pool_state: ctx.accounts.pool_state.key(),
+            lp_mint: ctx.accounts.lp_mint.key(),
+            quote_mint: pool_config.quote_mint,
+            token_amount: pool_token_amount,
+            quote_amount,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit!(FinalizePresaleEvent {
+            admin: admin_key,
+            unsold_presale_tokens,
+            unsold_reward_tokens,
+            burned_tokens: presale_split.0 + reward_split.0,
+            treasury_tokens: presale_split.1 + reward_split.1,
+            liquidity_tokens: presale_split.2 + reward_split.2,
+            presale: presale.key(),
+            sale_stage: presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            presale.key(),
+            admin_key,
+            AdminAction::Finalize {
+                with_pool: true,
+                tge_timestamp: presale.tge_timestamp,
+                unsold_presale_tokens,
+                unsold_reward_tokens,
+            },
+        )?;
+
+        msg!(
+            "Presale finalized! Pool {} seeded with {} tokens and {} quote units.",
+            ctx.accounts.pool_state.key(),
+            pool_token_amount,
+            quote_amount
+        );
+
+        Ok(())
+    }
+
+    pub fn unlock_lp(ctx: Context<UnlockLp>) -> Result<()> {
+        let presale = &ctx.accounts.presale;
+        let lp_lock = &mut ctx.accounts.lp_lock;
+
+        // ✅ Ensure the caller is the admin
+        require!(
+            presale.admin == ctx.accounts.admin.key(),
+            PresaleError::Unauthorized
+        );
+
+        require!(!lp_lock.unlocked, PresaleError::LpAlreadyUnlocked);
+        require!(
+            Clock::get()?.unix_timestamp >= lp_lock.unlock_timestamp,
+            PresaleError::LpStillLocked
+        );
+
+        let amount = ctx.accounts.lp_vault.amount;
+        let seed_key = presale.seed_key();
+        let seeds: &[&[u8]] = &[presale.seed_prefix(), &seed_key, &[presale.bump]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenTransfer {
+                    from: ctx.accounts.lp_vault.to_account_info(),
+                    to: ctx.accounts.admin_lp_token.to_account_info(),
+                    authority: presale.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        lp_lock.unlocked = true;
+
+        emit!(LpUnlockedEvent {
+            admin: ctx.accounts.admin.key(),
+            lp_mint: lp_lock.lp_mint,
+            amount,
+            presale: ctx.accounts.presale.key(),
+            sale_stage: ctx.accounts.presale.sale_stage,
+            slot: Clock::get()?.slot,
+        });
+
+        emit_admin_action(
+            ctx.accounts.presale.key(),
+            ctx.accounts.admin.key(),
+            AdminAction::UnlockLp {
+                lp_mint: lp_lock.lp_mint,
+                amount,
+            },
+        )?;
+
+        msg!("{} LP tokens unlocked to the admin", amount);
+
+        Ok(())
+    }
+
+    pub fn get_lp_lock_status(ctx: Context<GetLpLockStatus>) -> Result<LpLockStatus> {
+        let lp_lock = &ctx.accounts.lp_lock;
+        let now = Clock::get()?.unix_timestamp;
+
+        let status = LpLockStatus {
+            lp_mint: lp_lock.lp_mint,
+            locked_amount: ctx.accounts.lp_vault.amount,
+            unlock_timestamp: lp_lock.unlock_timestamp,
+            seconds_remaining: (lp_lock.unlock_timestamp - now).max(0),
+            unlocked: lp_lock.unlocked,
+        };
+
+        msg!(
+            "LP lock: {} tokens locked, {} seconds remaining",
+            status.locked_amount,
+            status.seconds_remaining
+        );
+
+        Ok(status)
+    }
+}
+
+/// Emits the audit record every admin instruction writes next to its own event. Stake pool
+/// changes are filed under the pool's sale, platform and config changes under their own PDA.
+fn emit_admin_action(presale: Pubkey, admin: Pubkey, action: AdminAction) -> Result<()> {
+    let clock = Clock::get()?;
+
+    emit!(AdminActionEvent {
+        presale,
+        admin,
+        action,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Verifies a Merkle proof built by `presale-cli airdrop build-root`.
+/// Inner nodes hash the sorted pair with a `1` prefix, leaves use a `0` prefix.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&[1u8], &computed, node]).0
+        } else {
+            keccak::hashv(&[&[1u8], node, &computed]).0
+        };
+    }
+    computed == root
+}
+
+fn validate_lockup_options(lockup_options: &[LockupOption]) -> Result<()> {
+    require!(
+        !lockup_options.is_empty() && lockup_options.len() <= MAX_LOCKUP_OPTIONS,
+        PresaleError::InvalidLockupOption
+    );
+    for option in lockup_options {
+        require!(
+            option.duration >= 0 && option.multiplier_bps >= 10_000,
+            PresaleError::InvalidLockupOption
+        );
+    }
+    Ok(())
+}
+
+fn apply_accepted_mints(
+    config: &mut ProgramConfig,
+    config_key: Pubkey,
+    accepted_stable_mints: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        accepted_stable_mints.len() <= MAX_ACCEPTED_MINTS,
+        PresaleError::TooManyAllowedMints
+    );
+
+    let old_mints = std::mem::replace(&mut config.accepted_stable_mints, accepted_stable_mints);
+
+    emit!(SetAcceptedMintsEvent {
+        authority: config.authority,
+        accepted_stable_mints: config.accepted_stable_mints.clone(),
+    });
+
+    emit_admin_action(
+        config_key,
+        config.authority,
+        AdminAction::SetAcceptedMints {
+            old_mints,
+            new_mints: config.accepted_stable_mints.clone(),
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Web3 payments (`payment_type` 0) are settled by the buy instruction itself. Web2 payments
+/// are settled off-chain and must be co-signed by the sale's payment authority.
+fn check_payment_authority(
+    presale: &Presale,
+    payment_type: u8,
+    payment_authority: Option<&Signer>,
+) -> Result<()> {
+    require!(payment_type <= 1, PresaleError::InvalidPaymentType);
+    if payment_type == 1 {
+        require!(
+            co_signed_by_payment_authority(presale, payment_authority),
+            PresaleError::PaymentNotAuthorized
+        );
+    }
+    Ok(())
+}
+
+/// In currency mode the referrer is paid out of the buyer's payment, so the referrer and the
+/// influencer flag only count when the sale's payment authority co-signs the purchase.
+/// Otherwise a buyer could name their own second wallet and take back part of the payment.
+fn check_referrer_vouched(
+    presale: &Presale,
+    referrer: Pubkey,
+    payment_authority: Option<&Signer>,
+) -> Result<()> {
+    require!(
+        referrer == Pubkey::default()
+            || presale.referral_reward_mode == 0
+            || co_signed_by_payment_authority(presale, payment_authority),
+        PresaleError::ReferrerNotVouched
+    );
+    Ok(())
+}
+
+/// Whether the sale has a payment authority and it signed the transaction.
+fn co_signed_by_payment_authority(presale: &Presale, payment_authority: Option<&Signer>) -> bool {
+    presale.payment_authority != Pubkey::default()
+        && payment_authority.map(|signer| signer.key()) == Some(presale.payment_authority)
+}
+
+/// Whether the larger referral commission plus the platform fee stays within the payment.
+fn payment_split_fits(regular_referral_rate: u8, influencer_referral_rate: u8, raise_fee_bps: u16) -> bool {
+    regular_referral_rate.max(influencer_referral_rate) as u32 * 100 + raise_fee_bps as u32 <= 10_000
+}
+
+fn apply_platform_settings(
+    platform: &mut Platform,
+    platform_key: Pubkey,
+    fee_recipient: Pubkey,
+    raise_fee_bps: u16,
+    token_fee_bps: u16,
+    allowed_mints: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        raise_fee_bps <= MAX_PLATFORM_FEE_BPS && token_fee_bps <= MAX_PLATFORM_FEE_BPS,
+        PresaleError::InvalidRate
+    );
+    require!(
+        allowed_mints.len() <= MAX_ALLOWED_MINTS,
+        PresaleError::TooManyAllowedMints
+    );
+
+    let old_fee_recipient = std::mem::replace(&mut platform.fee_recipient, fee_recipient);
+    let old_raise_fee_bps = std::mem::replace(&mut platform.raise_fee_bps, raise_fee_bps);
+    let old_token_fee_bps = std::mem::replace(&mut platform.token_fee_bps, token_fee_bps);
+    let old_allowed_mints = std::mem::replace(&mut platform.allowed_mints, allowed_mints);
+
+    emit!(PlatformUpdatedEvent {
+        authority: platform.authority,
+        fee_recipient,
+        raise_fee_bps,
+        token_fee_bps,
+        allowed_mints: platform.allowed_mints.clone(),
+    });
+
+    // ✅ A new platform account reports zeroed old values
+    emit_admin_action(
+        platform_key,
+        platform.authority,
+        AdminAction::SetPlatform {
+            old_fee_recipient,
+            new_fee_recipient: fee_recipient,
+            old_raise_fee_bps,
+            new_raise_fee_bps: raise_fee_bps,
+            old_token_fee_bps,
+            new_token_fee_bps: token_fee_bps,
+            old_allowed_mints,
+            new_allowed_mints: platform.allowed_mints.clone(),
+        },
+    )?;
+
+    msg!(
+        "Platform fees: {} bps of raise, {} bps of tokens, paid to {}",
+        raise_fee_bps,
+        token_fee_bps,
+        fee_recipient
+    );
+
+    Ok(())
+}
+
+/// Splits `amount` unsold tokens held in `from` across burn, treasury and liquidity
+/// per the sale's policy. Returns `(burned, to_treasury, to_liquidity)`.
+#[allow(clippy::too_many_arguments)]
+fn dispose_unsold<'info>(
+    disposition: &UnsoldDisposition,
+    amount: u64,
+    from: AccountInfo<'info>,
+    token_mint: AccountInfo<'info>,
+    treasury_wallet: AccountInfo<'info>,
+    liquidity_wallet: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(u64, u64, u64)> {
+    let burned = (amount as u128 * disposition.burn_percent as u128 / 100) as u64;
+    let to_treasury = (amount as u128 * disposition.treasury_percent as u128 / 100) as u64;
+    // Rounding dust goes to liquidity (or treasury when liquidity gets nothing)
+    let (to_treasury, to_liquidity) = if disposition.liquidity_percent > 0 {
+        (to_treasury, amount - burned - to_treasury)
+    } else {
+        (amount - burned, 0)
+    };
+
+    if burned > 0 {
+        token::burn(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                token::Burn {
+                    mint: token_mint,
+                    from: from.clone(),
+                    authority: authority.clone(),
+                },
+                signer_seeds,
+            ),
+            burned,
+        )?;
+    }
+
+    for (to, value) in [(treasury_wallet, to_treasury), (liquidity_wallet, to_liquidity)] {
+        if value > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    TokenTransfer {
+                        from: from.clone(),
+                        to,
+                        authority: authority.clone(),
+                    },
+                    signer_seeds,
+                ),
+                value,
+            )?;
+        }
+    }
+
+    Ok((burned, to_treasury, to_liquidity))
+}
+
+/// Adds `amount` to a position under the chosen lockup option and reweights it.
+/// `bonus_bps` is added on top of the option multiplier for locked positions.
+fn add_stake(
+    stake_pool: &mut StakePool,
+    position: &mut StakePosition,
+    amount: u64,
+    lockup_option: u8,
+    bonus_bps: u16,
+    now: i64,
+) -> Result<()> {
+    let option = *stake_pool
+        .lockup_options
+        .get(lockup_option as usize)
+        .ok_or(PresaleError::InvalidLockupOption)?;
+
+    stake_pool.update_rewards(now);
+    position.settle(stake_pool, now);
+
+    // The whole position moves to the chosen option, the lock never shortens
+    let weighted_before = position.weighted_amount;
+    position.amount += amount;
+    position.multiplier_bps = option.multiplier_bps;
+    position.lock_until = position.lock_until.max(now + option.duration);
+    // A bonus only applies to positions that take a lock, and ends with the lock it came with.
+    // Extending the lock through a plain `stake` does not extend the bonus
+    if option.duration == 0 {
+        position.bonus_bps = 0;
+        position.bonus_until = 0;
+    } else if bonus_bps > 0 {
+        position.bonus_bps = position.bonus_bps.max(bonus_bps);
+        position.bonus_until = position.lock_until;
+    }
+    position.weighted_amount = position.weight();
+
+    stake_pool.total_staked += amount;
+    stake_pool.total_weighted = stake_pool.total_weighted - weighted_before + position.weighted_amount;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitPlatform<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>, // ✅ Launchpad operator
+
+    #[account(
+        has_one = authority,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [PLATFORM_SEED],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Authority
+               32 +  // Fee recipient
+               2 +   // Raise fee bps
+               2 +   // Token fee bps
+               4 + 32 * MAX_ALLOWED_MINTS + // Allowed mints
+               1     // Bump
+    )]
+    pub platform: Account<'info, Platform>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatform<'info> {
+    pub authority: Signer<'info>, // ✅ Launchpad operator
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [PLATFORM_SEED],
+        bump = platform.bump,
+    )]
+    pub platform: Account<'info, Platform>,
+}
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>, // Launchpad operator, must hold the upgrade authority
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [CONFIG_SEED],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Authority
+               8 +   // Next sale id
+               4 + 32 * MAX_ACCEPTED_MINTS + // Accepted stable mints
+               1     // Bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Presale>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ PresaleError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAcceptedMints<'info> {
+    pub authority: Signer<'info>, // ✅ Launchpad operator
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // Admin who deploys the contract
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>, // Hands out sale ids
+
+    #[account(seeds = [PLATFORM_SEED], bump = platform.bump)]
+    pub platform: Account<'info, Platform>, // Fee terms and allowed mints
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [PRESALE_SEED, config.next_sale_id.to_le_bytes().as_ref()],  // Derive Presale PDA from the next sale id
+        bump,
+        space = 8 + Presale::INIT_SPACE // Discriminator + Presale
+    )]
+    pub presale: Account<'info, Presale>, // Stores presale details
+
+    pub token_mint: Account<'info, Mint>, // DYAWN token mint
+
+    #[account(init, payer = admin, token::mint = token_mint, token::authority = presale)]
+    pub presale_wallet: Account<'info, TokenAccount>,
+
+    #[account(init, payer = admin, token::mint = token_mint, token::authority = presale)]
+    pub referral_wallet: Account<'info, TokenAccount>, // Storage for referral rewards
+
+    #[account(mut)]
+    pub merchant_wallet: AccountInfo<'info>,
+
+    #[account(token::mint = token_mint)]
+    pub treasury_wallet: Account<'info, TokenAccount>, // Receives the treasury share of unsold tokens
+
+    pub system_program: Program<'info, System>, // Required system program
+    pub token_program: Program<'info, Token>,   // Required token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePresale<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Sale admin pays for the extra space
+
+    /// CHECK: Still in the v1 layout, so it cannot be deserialized as `Presale`.
+    /// Owner, discriminator, size, admin and PDA are checked in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub presale: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>, // ✅ Hands out the sale id
+
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Checked against the v1 account in the handler
+
+    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Checked against the v1 account in the handler
+
+    #[account(token::mint = presale_wallet.mint)]
+    pub treasury_wallet: Account<'info, TokenAccount>, // ✅ v1 had no treasury, receives the treasury share
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeliverV1Allocation<'info> {
+    pub admin: Signer<'info>, // ✅ Only admin delivers, from the v1 purchase records
+
+    #[account(
+        mut,
+        has_one = admin,
+        has_one = presale_wallet,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(mut)]
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Presale tokens)
+
+    #[account(mut, token::mint = presale_wallet.mint)]
+    pub recipient_token_account: Account<'info, TokenAccount>, // ✅ v1 buyer's token account
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSaleTokens<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin stocks the sale
+
+    #[account(
+        mut,
+        has_one = admin,
+        has_one = presale_wallet,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(mut)]
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Sale token supply
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Presale allocation source
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositRewardTokens<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin stocks the referral rewards
+
+    #[account(
+        mut,
+        has_one = admin,
+        has_one = referral_wallet,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(mut)]
+    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Referral reward supply
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Referral allocation source
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetStage<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // Only admin can change sale stage
+
+    #[account(
+        mut,
+        has_one = admin, // Ensures the stored presale.admin matches the Signer
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSalePeriod<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // Only the admin can update the sale period
+
+    #[account(
+        mut,
+        has_one = admin, // Ensures only the admin can update the sale period
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTokens<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // The user buying tokens
+
+    #[account(
+        mut,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()], 
+        bump,
+    )]
+    pub presale: Account<'info, Presale>, // Presale storage PDA
+
+    #[account(mut, address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // Store presale tokens
+
+    #[account(mut, address = presale.referral_wallet)] // ✅ Buyer bonuses are paid from here
+    pub referral_wallet: Account<'info, TokenAccount>, // Store Reward tokens
+
+    #[account(mut, address = presale.merchant_wallet)] // ✅ Ensures correct merchant wallet
+    pub merchant_wallet: AccountInfo<'info>,
+
+    #[account(mut, address = presale.platform_fee_recipient)] // ✅ Receives the platform's SOL fee
+    pub platform_fee_wallet: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = presale.platform_fee_recipient,
+    )]
+    pub platform_fee_token_account: Account<'info, TokenAccount>, // ✅ Receives the platform's token fee
+
+    #[account(mut)]
+    pub referrer_wallet: Option<AccountInfo<'info>>, // Receives SOL commission when rewards are paid in currency
+
+    pub payment_authority: Option<Signer<'info>>, // ✅ Co-signs Web2 purchases
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [BUYER_LEDGER_SEED, presale.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Presale
+               32 +  // Buyer
+               8 +   // Private sale purchases
+               8 +   // Public sale purchases
+               8 +   // Claimed
+               1     // Bump
+    )]
+    pub buyer_ledger: Account<'info, BuyerLedger>, // Per-buyer purchase ledger
+
+    pub token_program: Program<'info, Token>, // Required for the platform token fee
+    pub system_program: Program<'info, System>, // Required for SOL transfer
+}
+
+#[derive(Accounts)]
+pub struct CheckPresaleTokenBalance<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump,
+    )]
+    pub presale: Account<'info, Presale>, // Presale storage PDA
+
+    pub presale_wallet: Account<'info, TokenAccount>, // Store presale tokens
+}
+
+#[derive(Accounts)]
+pub struct AuditReserves<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // Holds sale tokens owed to buyers
+}
+
+#[derive(Accounts)]
+pub struct GetSaleStatus<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct QuotePurchase<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>, // Accepted stable coins
+}
+
+#[derive(Accounts)]
+pub struct UpdateSalePrice<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // Only the admin can update the price
+
+    #[account(
+        mut,
+        has_one = admin, // Ensures only the admin can update
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTokensByStableCoin<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // The user buying tokens
+
+    #[account(
+        mut,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump,
+    )]
+    pub presale: Account<'info, Presale>, // Presale storage PDA
+
+    #[account(mut, address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // Presale token storage
+
+    #[account(mut, address = presale.referral_wallet)] // ✅ Buyer bonuses are paid from here
+    pub referral_wallet: Account<'info, TokenAccount>, // Store Reward tokens
+
+    #[account(mut, token::mint = stable_coin_mint)] // ✅ Pays in the mint being credited
+    pub buyer_stable_coin_account: Account<'info, TokenAccount>, // Buyer’s USDC account
+
+    #[account(
+        mut,
+        token::mint = stable_coin_mint,
+        token::authority = presale.merchant_wallet, // ✅ Only the sale's merchant is paid
+    )]
+    pub merchant_stable_coin_account: Account<'info, TokenAccount>, // Merchant’s stable coin account
+
+    #[account(mut)]
+    pub referrer_stable_coin_account: Option<Account<'info, TokenAccount>>, // Referrer’s USDC account for currency commission
+
+    pub payment_authority: Option<Signer<'info>>, // ✅ Co-signs Web2 purchases
+
+    #[account(
+        mut,
+        token::mint = stable_coin_mint,
+        token::authority = presale.platform_fee_recipient,
+    )]
+    pub platform_fee_stable_account: Account<'info, TokenAccount>, // ✅ Receives the platform's USDC fee
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = presale.platform_fee_recipient,
+    )]
+    pub platform_fee_token_account: Account<'info, TokenAccount>, // ✅ Receives the platform's token fee
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [BUYER_LEDGER_SEED, presale.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Presale
+               32 +  // Buyer
+               8 +   // Private sale purchases
+               8 +   // Public sale purchases
+               8 +   // Claimed
+               1     // Bump
+    )]
+    pub buyer_ledger: Account<'info, BuyerLedger>, // Per-buyer purchase ledger
+
+    #[account()]
+    pub stable_coin_mint: Account<'info, Mint>, // USDC mint
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>, // Accepted stable coins
+
+    pub token_program: Program<'info, Token>, // Solana Token Program
+    pub system_program: Program<'info, System>, // Required to create the buyer ledger
+}
+
+#[derive(Accounts)]
+pub struct SetReferralRate<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can update referral rates
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralRewardMode<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can switch the reward mode
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct SetBuyerBonusRate<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can update the buyer bonus
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct SetSolPrice<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can update the SOL price
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaymentAuthority<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can pick the Web2 co-signer
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct SetVestingSchedule<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can update vesting
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokens<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // ✅ Buyer claiming vested tokens
+
+    #[account(
+        mut, // ✅ Claims release reserved tokens
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = buyer, // ✅ Ledger belongs to the signer
+        has_one = presale,
+        seeds = [BUYER_LEDGER_SEED, presale.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_ledger.bump,
+    )]
+    pub buyer_ledger: Account<'info, BuyerLedger>,
+
+    #[account(mut, address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Presale tokens)
+
+    #[account(
+        mut,
+        token::mint = presale_wallet.mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>, // ✅ Buyer's DYAWN token account
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateGrant<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can create grants
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    pub beneficiary: SystemAccount<'info>, // ✅ Team member or advisor receiving the grant
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [GRANT_SEED, presale.key().as_ref(), beneficiary.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Presale
+               32 +  // Beneficiary
+               32 +  // Vault
+               8 +   // Total amount
+               8 +   // Claimed
+               8 +   // Start
+               17 +  // Vesting schedule
+               1 +   // Revocable flag
+               1 +   // Revoked flag
+               1     // Bump
+    )]
+    pub grant: Account<'info, VestingGrant>,
+
+    #[account(address = presale.token_mint)]
+    pub token_mint: Account<'info, Mint>, // DYAWN token mint
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [GRANT_VAULT_SEED, grant.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = presale,
+    )]
+    pub grant_vault: Account<'info, TokenAccount>, // ✅ Locked grant tokens
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Team / marketing allocation source
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimGrant<'info> {
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = presale,
+        has_one = beneficiary, // ✅ Only the beneficiary can claim
+        seeds = [GRANT_SEED, presale.key().as_ref(), beneficiary.key().as_ref()],
+        bump = grant.bump,
+    )]
+    pub grant: Account<'info, VestingGrant>,
+
+    #[account(mut, address = grant.vault)]
+    pub grant_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = beneficiary,
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeGrant<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can revoke grants
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = presale,
+        seeds = [GRANT_SEED, presale.key().as_ref(), grant.beneficiary.as_ref()],
+        bump = grant.bump,
+    )]
+    pub grant: Account<'info, VestingGrant>,
+
+    #[account(mut, address = grant.vault)]
+    pub grant_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Receives unvested tokens
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(airdrop_id: u64, merkle_root: [u8; 32], total_amount: u64, num_recipients: u32)]
+pub struct CreateAirdrop<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can create airdrops
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [AIRDROP_SEED, presale.key().as_ref(), airdrop_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Presale
+               8 +   // Airdrop id
+               32 +  // Merkle root
+               32 +  // Vault
+               8 +   // Total amount
+               8 +   // Total claimed
+               4 +   // Number of recipients
+               8 +   // Expiry
+               1 +   // Clawed back flag
+               1 +   // Bump
+               4 + (num_recipients as usize).div_ceil(8) // Claim bitmap
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    #[account(address = presale.token_mint)]
+    pub token_mint: Account<'info, Mint>, // DYAWN token mint
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [AIRDROP_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = presale,
+    )]
+    pub airdrop_vault: Account<'info, TokenAccount>, // ✅ Tokens to be claimed
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Airdrop allocation source
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    pub claimant: Signer<'info>,
+
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = presale,
+        seeds = [AIRDROP_SEED, presale.key().as_ref(), distributor.airdrop_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    #[account(mut, address = distributor.vault)]
+    pub airdrop_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = claimant,
+    )]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClawbackAirdrop<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can claw back
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = presale,
+        seeds = [AIRDROP_SEED, presale.key().as_ref(), distributor.airdrop_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    #[account(mut, address = distributor.vault)]
+    pub airdrop_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale.token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Receives unclaimed tokens
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitStakePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can create the pool
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(address = presale.token_mint)]
+    pub token_mint: Account<'info, Mint>, // DYAWN token mint
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [STAKE_POOL_SEED, presale.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Admin
+               32 +  // Presale
+               32 +  // Token mint
+               32 +  // Stake vault
+               32 +  // Reward vault
+               8 +   // Reward rate
+               16 +  // Reward per token stored
+               8 +   // Last update time
+               8 +   // Total staked
+               8 +   // Total weighted
+               8 +   // Reward unallocated
+               2 +   // Private buyer bonus bps
+               4 + MAX_LOCKUP_OPTIONS * (8 + 2) + // Lockup options
+               1     // Bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [STAKE_VAULT_SEED, stake_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = stake_pool,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>, // ✅ Staked tokens
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [REWARD_VAULT_SEED, stake_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = stake_pool,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>, // ✅ Staking rewards topped up by the admin
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStakePool<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Only the pool admin can change emissions
+        seeds = [STAKE_POOL_SEED, stake_pool.presale.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Only the pool admin tops up rewards
+        has_one = reward_vault,
+        seeds = [STAKE_POOL_SEED, stake_pool.presale.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stake_pool.token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>, // ✅ Staking allocation source
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = stake_vault,
+        seeds = [STAKE_POOL_SEED, stake_pool.presale.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [STAKE_POSITION_SEED, stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Stake pool
+               32 +  // Owner
+               8 +   // Amount
+               8 +   // Weighted amount
+               2 +   // Multiplier bps
+               2 +   // Bonus bps
+               8 +   // Lock until
+               16 +  // Reward per token paid
+               8 +   // Rewards owed
+               1 +   // Bump
+               8 +   // Bonus until
+               8     // Settled at
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stake_pool.token_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = stake_vault,
+        seeds = [STAKE_POOL_SEED, stake_pool.presale.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = stake_pool,
+        seeds = [STAKE_POSITION_SEED, stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump = stake_position.bump,
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stake_pool.token_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = reward_vault,
+        seeds = [STAKE_POOL_SEED, stake_pool.presale.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = stake_pool,
+        seeds = [STAKE_POSITION_SEED, stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump = stake_position.bump,
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stake_pool.token_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakeFromClaim<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // ✅ Buyer staking their claimable allocation
+
+    #[account(
+        mut, // ✅ Claims release reserved tokens
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = buyer,
+        has_one = presale,
+        seeds = [BUYER_LEDGER_SEED, presale.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_ledger.bump,
+    )]
+    pub buyer_ledger: Account<'info, BuyerLedger>,
+
+    #[account(mut, address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Presale tokens)
+
+    #[account(
+        mut,
+        has_one = stake_vault,
+        constraint = stake_pool.presale == presale.key(), // ✅ Only the sale's own pool
+        seeds = [STAKE_POOL_SEED, presale.key().as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [STAKE_POSITION_SEED, stake_pool.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Stake pool
+               32 +  // Owner
+               8 +   // Amount
+               8 +   // Weighted amount
+               2 +   // Multiplier bps
+               2 +   // Bonus bps
+               8 +   // Lock until
+               16 +  // Reward per token paid
+               8 +   // Rewards owed
+               1 +   // Bump
+               8 +   // Bonus until
+               8     // Settled at
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CheckRewardTokenBalance<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()], 
+        bump,
+    )]
+    pub presale: Account<'info, Presale>, // ✅ Presale contract state
+
+    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Referral wallet holding reward tokens
+}
+
+#[derive(Accounts)]
+pub struct FinalizePresale<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can finalize the presale
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the presale admin can call this
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>, // ✅ Presale account
+
+    #[account(mut, address = presale.presale_wallet)]
+    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Presale tokens)
+
+    #[account(mut, address = presale.referral_wallet)]
+    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Referral tokens)
+
+    #[account(mut)]
+    pub liquidity_wallet: Account<'info, TokenAccount>, // ✅ Destination wallet (Liquidity)
+
+    #[account(mut, address = presale.treasury_wallet)]
+    pub treasury_wallet: Account<'info, TokenAccount>, // ✅ Destination wallet (Treasury)
+
+    #[account(mut, address = presale.token_mint)]
+    pub token_mint: Account<'info, Mint>, // ✅ Burned tokens leave the supply
+
+    pub token_program: Program<'info, Token>, // ✅ Solana Token Program
+}
+
+#[derive(Accounts)]
+pub struct SetPoolConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can call this function
+
+    #[account(
+        mut,
+        has_one = admin, // ✅ Ensures only the admin can configure pool seeding
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>, // Accepted stable coins
+}
+
+#[derive(Accounts)]
+pub struct FinalizePresaleWithPool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can finalize, also the pool creator
+
+    #[account(
+        mut,
+        has_one = admin,
+        has_one = presale_wallet,
+        has_one = referral_wallet,
+        has_one = token_mint,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump
+    )]
+    pub presale: Box<Account<'info, Presale>>,
+
+    #[account(mut)]
+    pub presale_wallet: Box<Account<'info, TokenAccount>>, // ✅ Source wallet (Presale tokens)
+
+    #[account(mut)]
+    pub referral_wallet: Box<Account<'info, TokenAccount>>, // ✅ Source wallet (Referral tokens)
+
+    #[account(mut)]
+    pub liquidity_wallet: Box<Account<'info, TokenAccount>>, // ✅ Destination wallet (Liquidity)
+
+    #[account(mut, address = presale.treasury_wallet)]
+    pub treasury_wallet: Box<Account<'info, TokenAccount>>, // ✅ Destination wallet (Treasury)
+
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>, // DYAWN token mint
+
+    #[account(address = presale.pool_config.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>, // WSOL or USDC
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Box<Account<'info, TokenAccount>>, // ✅ Token side, passes through to the pool
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = admin,
+    )]
+    pub admin_quote_account: Box<Account<'info, TokenAccount>>, // ✅ Quote side, passes through to the pool
+
+    #[account(address = presale.merchant_wallet)]
+    pub merchant: Signer<'info>, // ✅ Received the raise, releases the pool's share of it
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = merchant,
+    )]
+    pub merchant_quote_account: Box<Account<'info, TokenAccount>>, // ✅ Raised funds (wrapped SOL or USDC)
+
+    pub cp_swap_program: Program<'info, RaydiumCpmm>,
+
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: Pool vault and LP mint authority, validated by Raydium CPMM
+    pub pool_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Pool state, initialized by Raydium CPMM
+    #[account(mut)]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// CHECK: LP mint, initialized by Raydium CPMM
+    #[account(mut)]
+    pub lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Admin's LP token ATA, initialized by Raydium CPMM
+    #[account(mut)]
+    pub admin_lp_token: UncheckedAccount<'info>,
+
+    /// CHECK: Token 0 vault, initialized by Raydium CPMM
+    #[account(mut)]
+    pub token_0_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token 1 vault, initialized by Raydium CPMM
+    #[account(mut)]
+    pub token_1_vault: UncheckedAccount<'info>,
+
+    #[account(mut, address = create_pool_fee_receiver::id())]
+    pub create_pool_fee: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Oracle observations, initialized by Raydium CPMM
+    #[account(mut)]
+    pub observation_state: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [LP_LOCK_SEED, presale.key().as_ref()],
+        bump,
+        space = 8 +  // Discriminator
+               32 +  // Presale
+               32 +  // LP mint
+               32 +  // Vault
+               8 +   // Locked amount
+               8 +   // Unlock timestamp
+               1 +   // Unlocked flag
+               1     // Bump
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+
+    /// CHECK: Presale PDA's LP token ATA, created after the pool exists and checked in the handler
+    #[account(mut)]
+    pub lp_vault: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockLp<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // ✅ Only admin can unlock LP
+
+    #[account(
+        has_one = admin,
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        has_one = presale,
+        seeds = [LP_LOCK_SEED, presale.key().as_ref()],
+        bump = lp_lock.bump,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(mut, address = lp_lock.vault)]
+    pub lp_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = lp_lock.lp_mint,
+        token::authority = admin,
+    )]
+    pub admin_lp_token: Account<'info, TokenAccount>, // ✅ Receives unlocked LP tokens
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct GetLpLockStatus<'info> {
+    #[account(
+        seeds = [presale.seed_prefix(), presale.seed_key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        has_one = presale,
+        seeds = [LP_LOCK_SEED, presale.key().as_ref()],
+        bump = lp_lock.bump,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(address = lp_lock.vault)]
+    pub lp_vault: Account<'info, TokenAccount>,
+}
+
+#[account]
+pub struct Platform {
+    pub authority: Pubkey,          // Launchpad operator
+    pub fee_recipient: Pubkey,      // Wallet that owns the platform fee accounts
+    pub raise_fee_bps: u16,         // Cut of every on-chain payment
+    pub token_fee_bps: u16,         // Cut of every token sale, paid from the sale supply
+    pub allowed_mints: Vec<Pubkey>, // Sale token mints that may list, empty = any
+    pub bump: u8,
+}
+
+#[account]
+pub struct ProgramConfig {
+    pub authority: Pubkey, // Launchpad operator
+    pub next_sale_id: u64, // Id given to the next presale
+    pub accepted_stable_mints: Vec<Pubkey>, // Stable coins buyers may pay with (6 decimals)
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Presale {
+    pub version: u8,               // Account layout version, see `PRESALE_VERSION`
+    pub sale_id: u64,              // Sale PDA seed, assigned from ProgramConfig
+    pub admin: Pubkey,              // Admin wallet address
+    pub presale_start: i64,         // Presale start timestamp (Unix time)
+    pub private_price: u64,         // Token price in private sale
+    pub public_price: u64,          // Token price in public sale
+    pub current_price: u64,         // Current token price (updates dynamically)
+    pub private_sale_duration: i64, // Private sale duration
+    pub public_sale_duration: i64,  // Public sale duration
+    pub sale_stage: u8,             // Sale stage (0: Not started, 1: Private, 2: Public, 3: Ended)
+    pub total_sold: u64,            // Total tokens sold
+    pub referral_charged: u64,      // Total tokens sold
+    pub pool_created: bool,         // Liquidity pool created flag
+    pub presale_wallet: Pubkey,     // Token account for presale
+    pub referral_wallet: Pubkey,    // Token account for referral rewards
+    pub merchant_wallet: Pubkey,
+    pub regular_referral_rate: u8, // Referral reward % for regular users
+    pub influencer_referral_rate: u8, // Referral reward % for influencers
+    pub referral_reward_mode: u8,  // 0 = Rewards in sale tokens, 1 = Commission in payment currency
+    pub buyer_bonus_rate: u8,      // Extra tokens % for buyers who use a referrer
+    pub private_vesting: VestingSchedule, // Vesting for private sale purchases
+    pub public_vesting: VestingSchedule,  // Vesting for public sale purchases
+    pub tge_timestamp: i64,        // Vesting start, set when the pool is created
+    pub token_mint: Pubkey,        // Sale token mint
+    pub total_raised_lamports: u64, // SOL received by the merchant (Web3 only)
+    pub total_raised_stable: u64,  // USDC base units received by the merchant (Web3 only)
+    pub raised_by_stable_mint: [StableRaised; MAX_ACCEPTED_MINTS], // `total_raised_stable` split by mint
+    pub pool_config: PoolConfig,   // Optional AMM pool seeding at finalize
+    pub unsold_disposition: UnsoldDisposition, // What finalize does with unsold tokens
+    pub treasury_wallet: Pubkey,   // Receives the treasury share of unsold tokens
+    pub reserved_tokens: u64,      // Sold tokens not yet claimed, in base units
+    pub sale_allocation: u64,      // Sale tokens required before the sale can start
+    pub total_deposited: u64,      // Sale tokens deposited through the program
+    pub total_reward_deposited: u64, // Reward tokens deposited through the program
+    pub platform_fee_recipient: Pubkey, // Platform fee terms, copied from `Platform` at initialize
+    pub platform_raise_fee_bps: u16,
+    pub platform_token_fee_bps: u16,
+    pub platform_tokens_charged: u64, // Sale tokens paid to the platform
+    pub bump: u8,                  // Store bump here
+    pub purchase_count: u64,       // Purchases recorded so far, numbers the buy events
+    pub sol_price_in_usd: u64,     // SOL price used to price SOL purchases, set by the admin
+    pub payment_authority: Pubkey, // Backend key that co-signs Web2 purchases, default = Web2 disabled
+    pub v1_seeds: bool,            // Migrated from v1, the PDA is still [PRESALE_V1_SEED, admin]
+    pub v1_undelivered: u64,       // v1 purchases not delivered yet, whole tokens (no buyer ledgers)
+    pub reserved: [u8; 7],         // Room for new fields without another resize
+}
+
+/// `Presale` as deployed before versioning, at `[PRESALE_V1_SEED, admin]`. Kept to migrate those accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct PresaleV1 {
+    pub admin: Pubkey,
+    pub presale_start: i64,
+    pub private_price: u64,
+    pub public_price: u64,
+    pub current_price: u64,
+    pub private_sale_duration: i64,
+    pub public_sale_duration: i64,
+    pub sale_stage: u8,
+    pub total_sold: u64,
+    pub referral_charged: u64,
+    pub pool_created: bool,
+    pub presale_wallet: Pubkey,
+    pub referral_wallet: Pubkey,
+    pub merchant_wallet: Pubkey,
+    pub regular_referral_rate: u8,
+    pub influencer_referral_rate: u8,
+    pub bump: u8,
+}
+
+impl Presale {
+    /// Current layout for a v1 sale. v1 sales were funded by direct transfers and kept no buyer
+    /// ledgers, so the wallet balances become the deposits and sold tokens stay reserved until
+    /// the admin delivers them with `deliver_v1_allocation`.
+    pub fn from_v1(
+        v1: PresaleV1,
+        sale_id: u64,
+        presale_wallet: &TokenAccount,
+        referral_wallet: &TokenAccount,
+        treasury_wallet: Pubkey,
+    ) -> Result<Self> {
+        let reserved_tokens = v1
+            .total_sold
+            .checked_mul(1_000_000_000) // Adjust decimals
+            .ok_or(PresaleError::MathOverflow)?;
+
+        Ok(Presale {
+            version: PRESALE_VERSION,
+            sale_id,
+            admin: v1.admin,
+            presale_start: v1.presale_start,
+            private_price: v1.private_price,
+            public_price: v1.public_price,
+            current_price: v1.current_price,
+            private_sale_duration: v1.private_sale_duration,
+            public_sale_duration: v1.public_sale_duration,
+            sale_stage: v1.sale_stage,
+            total_sold: v1.total_sold,
+            referral_charged: v1.referral_charged,
+            pool_created: v1.pool_created,
+            presale_wallet: v1.presale_wallet,
+            referral_wallet: v1.referral_wallet,
+            merchant_wallet: v1.merchant_wallet,
+            regular_referral_rate: v1.regular_referral_rate,
+            influencer_referral_rate: v1.influencer_referral_rate,
+            referral_reward_mode: 0,
+            buyer_bonus_rate: 0,
+            private_vesting: VestingSchedule::default(),
+            public_vesting: VestingSchedule::default(),
+            tge_timestamp: 0,
+            token_mint: presale_wallet.mint,
+            total_raised_lamports: 0,
+            total_raised_stable: 0,
+            raised_by_stable_mint: Default::default(),
+            pool_config: PoolConfig::default(),
+            // v1 finalize sent everything unsold to the liquidity wallet
+            unsold_disposition: UnsoldDisposition {
+                burn_percent: 0,
+                treasury_percent: 0,
+                liquidity_percent: 100,
+            },
+            treasury_wallet,
+            reserved_tokens,
+            sale_allocation: 0,
+            total_deposited: presale_wallet.amount / 1_000_000_000,
+            total_reward_deposited: referral_wallet.amount / 1_000_000_000,
+            // Sales from before the launchpad pay no platform fees
+            platform_fee_recipient: v1.admin,
+            platform_raise_fee_bps: 0,
+            platform_token_fee_bps: 0,
+            platform_tokens_charged: 0,
+            bump: v1.bump,
+            purchase_count: 0,
+            sol_price_in_usd: 0,
+            payment_authority: Pubkey::default(),
+            v1_seeds: true,
+            v1_undelivered: v1.total_sold,
+            reserved: [0; 7],
+        })
+    }
+}
+
+#[account]
+pub struct BuyerLedger {
+    pub presale: Pubkey,        // Presale this ledger belongs to
+    pub buyer: Pubkey,          // Buyer wallet address
+    pub private_purchased: u64, // Tokens bought during the private sale
+    pub public_purchased: u64,  // Tokens bought during the public sale
+    pub claimed: u64,           // Tokens already claimed
+    pub bump: u8,
+}
+
+#[account]
+pub struct VestingGrant {
+    pub presale: Pubkey,     // Presale whose PDA signs for the vault
+    pub beneficiary: Pubkey, // Team member or advisor
+    pub vault: Pubkey,       // Token account holding the locked tokens
+    pub total_amount: u64,   // Whole tokens granted (frozen at vested amount on revoke)
+    pub claimed: u64,        // Whole tokens already claimed
+    pub start: i64,          // Vesting start timestamp
+    pub schedule: VestingSchedule,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct AirdropDistributor {
+    pub presale: Pubkey,         // Presale whose PDA signs for the vault
+    pub airdrop_id: u64,         // Admin-chosen id, part of the PDA seeds
+    pub merkle_root: [u8; 32],   // Root over (index, claimant, amount) leaves
+    pub vault: Pubkey,           // Token account holding unclaimed tokens
+    pub total_amount: u64,       // Whole tokens funded
+    pub total_claimed: u64,      // Whole tokens claimed so far
+    pub num_recipients: u32,     // Number of leaves in the tree
+    pub expiry: i64,             // Claims close at this timestamp
+    pub clawed_back: bool,       // Unclaimed tokens returned to admin
+    pub bump: u8,
+    pub claim_bitmap: Vec<u8>,   // One bit per leaf index
+}
+
+#[account]
+pub struct StakePool {
+    pub admin: Pubkey,                 // Presale admin who manages emissions
+    pub presale: Pubkey,               // Sale the pool belongs to, part of the PDA seeds
+    pub token_mint: Pubkey,            // Sale token mint, staked and paid out
+    pub stake_vault: Pubkey,           // Token account holding staked tokens
+    pub reward_vault: Pubkey,          // Token account holding rewards
+    pub reward_rate: u64,              // Reward base units emitted per second
+    pub reward_per_token_stored: u128, // Accumulator scaled by REWARD_PRECISION
+    pub last_update_time: i64,         // Last accumulator update
+    pub total_staked: u64,             // Base units staked
+    pub total_weighted: u64,           // Staked amount after lockup multipliers
+    pub reward_unallocated: u64,       // Funded rewards not yet emitted
+    pub private_buyer_bonus_bps: u16,  // Extra multiplier for locked stake-from-claim by private buyers
+    pub lockup_options: Vec<LockupOption>,
+    pub bump: u8,
+}
+
+impl StakePool {
+    /// Emits rewards up to `now`, capped by what the admin has funded.
+    pub fn update_rewards(&mut self, now: i64) {
+        if now > self.last_update_time && self.total_weighted > 0 {
+            let elapsed = (now - self.last_update_time) as u128;
+            let emitted = (elapsed * self.reward_rate as u128).min(self.reward_unallocated as u128);
+
+            self.reward_per_token_stored += emitted * REWARD_PRECISION / self.total_weighted as u128;
+            self.reward_unallocated -= emitted as u64;
+        }
+        self.last_update_time = self.last_update_time.max(now);
+    }
+}
+
+#[account]
+pub struct StakePosition {
+    pub stake_pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,                 // Base units staked
+    pub weighted_amount: u64,        // Amount after the lockup multiplier
+    pub multiplier_bps: u16,         // 10_000 = 1x
+    pub bonus_bps: u16,              // Private buyer bonus on top of the multiplier
+    pub lock_until: i64,             // Unstake allowed from this timestamp
+    pub reward_per_token_paid: u128, // Accumulator value at last settlement
+    pub rewards_owed: u64,           // Settled, unclaimed rewards
+    pub bump: u8,
+    pub bonus_until: i64,            // End of the lock the bonus was granted for
+    pub settled_at: i64,             // Timestamp of the last settlement
+}
+
+impl StakePosition {
+    pub fn weight(&self) -> u64 {
+        let multiplier = self.multiplier_bps as u128 + self.bonus_bps as u128;
+        (self.amount as u128 * multiplier / 10_000) as u64
+    }
+
+    /// Moves rewards earned since the last settlement into `rewards_owed`.
+    /// The lockup multiplier earns until `lock_until` and the bonus until `bonus_until`, after
+    /// that the position earns at 1x. The accumulator at an expiry that passed between two
+    /// settlements is interpolated over time, the pool's total weight only drops at settlement.
+    pub fn settle(&mut self, stake_pool: &mut StakePool, now: i64) {
+        let mut expiries = [self.lock_until, self.bonus_until];
+        expiries.sort_unstable();
+
+        for expiry in expiries {
+            if expiry > now {
+                break;
+            }
+            if expiry > self.settled_at {
+                let reward_per_token = self.reward_per_token_paid
+                    + (stake_pool.reward_per_token_stored - self.reward_per_token_paid)
+                        * (expiry - self.settled_at) as u128
+                        / (now - self.settled_at) as u128;
+                self.accrue(reward_per_token);
+                self.settled_at = expiry;
+            }
+            self.expire(stake_pool, expiry);
+        }
+
+        self.accrue(stake_pool.reward_per_token_stored);
+        self.settled_at = self.settled_at.max(now);
+    }
+
+    fn accrue(&mut self, reward_per_token: u128) {
+        let earned = self.weighted_amount as u128 * (reward_per_token - self.reward_per_token_paid)
+            / REWARD_PRECISION;
+        self.rewards_owed += earned as u64;
+        self.reward_per_token_paid = reward_per_token;
+    }
+
+    /// Drops the multiplier and bonus whose lock has ended by `at`.
+    fn expire(&mut self, stake_pool: &mut StakePool, at: i64) {
+        if at >= self.lock_until {
+            self.multiplier_bps = 10_000;
+        }
+        if at >= self.bonus_until {
+            self.bonus_bps = 0;
+        }
+
+        let weighted_before = self.weighted_amount;
+        self.weighted_amount = self.weight();
+        stake_pool.total_weighted = stake_pool.total_weighted - weighted_before + self.weighted_amount;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LockupOption {
+    pub duration: i64,       // Lock length in seconds
+    pub multiplier_bps: u16, // Reward weight, 10_000 = 1x
+}
+
+impl Presale {
+    /// First PDA seed. Sales migrated from v1 keep their admin-keyed address.
+    pub fn seed_prefix(&self) -> &'static [u8] {
+        if self.v1_seeds {
+            PRESALE_V1_SEED
+        } else {
+            PRESALE_SEED
+        }
+    }
+
+    /// Second PDA seed, the sale id (u64 LE) or the admin of a v1 sale.
+    pub fn seed_key(&self) -> Vec<u8> {
+        if self.v1_seeds {
+            self.admin.to_bytes().to_vec()
+        } else {
+            self.sale_id.to_le_bytes().to_vec()
+        }
+    }
+
+    /// Deposited sale tokens not yet sold or paid out as platform fees, zero once they are all gone.
+    pub fn remaining_tokens(&self) -> u64 {
+        self.total_deposited
+            .saturating_sub(self.total_sold)
+            .saturating_sub(self.platform_tokens_charged)
+    }
+
+    /// Reward deposits not charged to referrers or buyer bonuses yet, in whole tokens.
+    pub fn remaining_rewards(&self) -> u64 {
+        self.total_reward_deposited.saturating_sub(self.referral_charged)
+    }
+
+    /// What a payment of `amount` lamports (`is_sol`) or whole stable coins buys at the current
+    /// stage price, and how it is split. The buy instructions and `quote_purchase` both use it,
+    /// so a quote cannot drift from the purchase it describes.
+    fn purchase_amounts(
+        &self,
+        is_sol: bool,
+        amount: u64,
+        has_referrer: bool,
+        is_influencer: bool,
+        referrer_vouched: bool,
+    ) -> Result<PurchaseAmounts> {
+        let amount_in_usd = if is_sol {
+            mul_div(amount, self.sol_price_in_usd, 1_000_000_000)?
+        } else {
+            amount
+        };
+        let payment = if is_sol {
+            amount
+        } else {
+            amount.checked_mul(USDC_DECIMALS).ok_or(PresaleError::MathOverflow)?
+        };
+        let tokens = if self.current_price > 0 {
+            mul_div(amount_in_usd, 1_000_000, self.current_price)?
+        } else {
+            0
+        };
+
+        let referral_rate = self.referral_rate(is_influencer) as u64;
+        let referral_commission = if has_referrer && self.referral_reward_mode == 1 {
+            mul_div(payment, referral_rate, 100)?
+        } else {
+            0
+        };
+        let referral_reward = if has_referrer && self.referral_reward_mode == 0 {
+            mul_div(tokens, referral_rate, 100)?
+        } else {
+            0
+        };
+        let bonus_tokens = if has_referrer && referrer_vouched {
+            mul_div(tokens, self.buyer_bonus_rate as u64, 100)?
+        } else {
+            0
+        };
+
+        let platform_fee = self.platform_raise_fee(payment);
+        let merchant_amount = payment
+            .checked_sub(referral_commission)
+            .and_then(|rest| rest.checked_sub(platform_fee))
+            .ok_or(PresaleError::MathOverflow)?;
+
+        Ok(PurchaseAmounts {
+            amount_in_usd,
+            tokens,
+            platform_token_fee: self.platform_token_fee(tokens),
+            platform_fee,
+            referral_commission,
+            referral_reward,
+            bonus_tokens,
+            merchant_amount,
+        })
+    }
+
+    /// Referral percentage paid to a regular referrer or an influencer.
+    pub fn referral_rate(&self, is_influencer: bool) -> u8 {
+        if is_influencer {
+            self.influencer_referral_rate
+        } else {
+            self.regular_referral_rate
+        }
+    }
+
+    /// Platform cut of a payment, in the payment's base units.
+    pub fn platform_raise_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.platform_raise_fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Platform cut of a token sale, in whole tokens.
+    pub fn platform_token_fee(&self, tokens: u64) -> u64 {
+        (tokens as u128 * self.platform_token_fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Adds a stable payment the merchant received to the total and to its mint's share.
+    pub fn record_stable_raised(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        // Slots fill in order, so the mint's own slot comes before the first free one
+        let raised = self
+            .raised_by_stable_mint
+            .iter_mut()
+            .find(|raised| raised.mint == mint || raised.mint == Pubkey::default())
+            .ok_or(PresaleError::TooManyAllowedMints)?;
+        raised.mint = mint;
+        raised.amount = checked_sum(raised.amount, amount)?;
+        self.total_raised_stable = checked_sum(self.total_raised_stable, amount)?;
+        Ok(())
+    }
+
+    /// Stable base units the merchant received in `mint`.
+    pub fn stable_raised(&self, mint: Pubkey) -> u64 {
+        self.raised_by_stable_mint
+            .iter()
+            .find(|raised| raised.mint == mint)
+            .map_or(0, |raised| raised.amount)
+    }
+}
+
+/// Result of `Presale::purchase_amounts`.
+struct PurchaseAmounts {
+    amount_in_usd: u64,       // Whole USD the payment is worth
+    tokens: u64,              // Whole tokens bought
+    platform_token_fee: u64,  // Whole tokens paid to the platform out of the sale supply
+    platform_fee: u64,        // Payment base units paid to the platform
+    referral_commission: u64, // Payment base units paid to the referrer (currency mode)
+    referral_reward: u64,     // Whole reward tokens charged for the referrer (token mode)
+    bonus_tokens: u64,        // Whole reward tokens given to the buyer (vouched referrals)
+    merchant_amount: u64,     // Payment base units left for the merchant
+}
+
+/// `a * b / c` in 128 bits, failing with `MathOverflow` when the result does not fit a u64.
+fn mul_div(a: u64, b: u64, c: u64) -> Result<u64> {
+    let result = a as u128 * b as u128 / c as u128;
+    Ok(u64::try_from(result).map_err(|_| PresaleError::MathOverflow)?)
+}
+
+/// `a + b`, failing with `MathOverflow` past u64.
+fn checked_sum(a: u64, b: u64) -> Result<u64> {
+    Ok(a.checked_add(b).ok_or(PresaleError::MathOverflow)?)
+}
+
+/// Whole tokens in base units, failing with `MathOverflow` past u64.
+fn base_units(tokens: u64) -> Result<u64> {
+    Ok(tokens.checked_mul(1_000_000_000).ok_or(PresaleError::MathOverflow)?)
+}
 
-    #[account(mut)]
-    pub presale_wallet: Account<'info, TokenAccount>, // Presale token storage
+impl BuyerLedger {
+    /// Vested tokens not yet claimed, per the presale's stage schedules.
+    pub fn claimable(&self, presale: &Presale, now: i64) -> u64 {
+        let vested = presale
+            .private_vesting
+            .vested_amount(self.private_purchased, presale.tge_timestamp, now)
+            + presale
+                .public_vesting
+                .vested_amount(self.public_purchased, presale.tge_timestamp, now);
+
+        vested.saturating_sub(self.claimed)
+    }
+}
 
-    #[account(mut)]
-    pub referral_wallet: Account<'info, TokenAccount>, // Store Reward tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReserveAudit {
+    pub wallet_balance: u64,     // Presale wallet balance (base units)
+    pub outstanding_claims: u64, // Sold tokens buyers have not claimed yet
+    pub surplus: u64,            // Balance above outstanding claims
+    pub solvent: bool,           // wallet_balance >= outstanding_claims
+}
 
-    #[account(mut)]
-    pub buyer_stable_coin_account: Account<'info, TokenAccount>, // Buyer’s USDC account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SaleStatus {
+    pub sale_id: u64,
+    pub sale_stage: u8,           // Stage recorded on the account
+    pub effective_stage: u8,      // Stage the schedule puts the sale in at the current clock
+    pub private_price: u64,
+    pub public_price: u64,
+    pub current_price: u64,
+    pub presale_start: i64,
+    pub stage_end: i64,           // Scheduled end of the effective stage, 0 if none
+    pub seconds_remaining: i64,   // Until stage_end
+    pub total_sold: u64,          // Whole tokens
+    pub remaining_tokens: u64,    // Whole tokens still for sale
+    pub reserved_tokens: u64,     // Sold tokens not claimed yet (base units)
+    pub reward_liability: u64,    // Referral rewards and buyer bonuses charged (whole tokens)
+    pub remaining_rewards: u64,   // Reward deposits not charged yet (whole tokens)
+    pub total_raised_lamports: u64,
+    pub total_raised_stable: u64,
+    pub purchase_count: u64,
+    pub purchases_open: bool,     // Buys are accepted in the recorded stage
+    pub pool_created: bool,       // Finalized, claims are open
+}
 
-    #[account(mut)]
-    pub merchant_stable_coin_account: Account<'info, TokenAccount>, // Merchant’s stable coin account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PurchaseQuote {
+    pub tokens_out: u64,          // Whole tokens credited to the buyer
+    pub bonus_tokens: u64,        // Buyer referral bonus on top of tokens_out
+    pub referral_reward: u64,     // Whole tokens to the referrer (reward mode 0)
+    pub referral_commission: u64, // Payment base units to the referrer (reward mode 1)
+    pub platform_fee: u64,        // Payment base units
+    pub platform_token_fee: u64,  // Whole tokens, out of the sale supply
+    pub sale_stage: u8,
+    pub price: u64,               // Current stage price, micro-USD per token
+    pub effective_price: u64,     // Micro-USD per token including rounding
+    pub remaining_tokens: u64,    // Whole tokens for sale before this purchase
+    pub remaining_rewards: u64,   // Whole reward tokens left before this purchase
+    pub limit: Option<u32>,       // Error code the purchase would fail with
+}
 
-    #[account()]
-    pub stable_coin_mint: Account<'info, Mint>, // USDC mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct InitializeArgs {
+    pub private_price: u64,         // Micro-USD per token in the private stage
+    pub public_price: u64,          // Micro-USD per token in the public stage
+    pub private_sale_duration: i64, // Days
+    pub public_sale_duration: i64,  // Days
+    pub regular_referral_rate: u8,  // % of purchased tokens
+    pub influencer_referral_rate: u8,
+    pub unsold_disposition: UnsoldDisposition, // What finalize does with unsold tokens
+    pub sale_allocation: u64, // Whole sale tokens that must be deposited before the sale starts
+}
 
-    pub token_program: Program<'info, Token>, // Solana Token Program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct UnsoldDisposition {
+    pub burn_percent: u8,      // % of unsold tokens burned
+    pub treasury_percent: u8,  // % returned to the treasury wallet
+    pub liquidity_percent: u8, // % sent to the liquidity wallet
 }
 
-#[derive(Accounts)]
-pub struct SetReferralRate<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // ✅ Only admin can call this function
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct StableRaised {
+    pub mint: Pubkey, // Default = unused slot
+    pub amount: u64,  // Base units received by the merchant
+}
 
-    #[account(
-        mut,
-        has_one = admin, // ✅ Ensures only the admin can update referral rates
-        seeds = [PRESALE_SEED, admin.key().as_ref()],
-        bump
-    )]
-    pub presale: Account<'info, Presale>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PoolConfig {
+    pub enabled: bool,      // Finalize must seed a Raydium CPMM pool
+    pub quote_mint: Pubkey, // WSOL or USDC side of the pool
+    pub raise_percent: u8,  // % of raised funds paired into the pool
+    pub token_amount: u64,  // Whole sale tokens paired into the pool
+    pub lp_lock_duration: i64, // Seconds LP tokens stay locked after finalize
 }
 
-#[derive(Accounts)]
-pub struct CheckRewardTokenBalance<'info> {
-    #[account(
-        mut,
-        seeds = [PRESALE_SEED, presale.admin.as_ref()], 
-        bump,
-    )]
-    pub presale: Account<'info, Presale>, // ✅ Presale contract state
+#[account]
+pub struct LpLock {
+    pub presale: Pubkey,       // Presale whose PDA owns the LP vault
+    pub lp_mint: Pubkey,       // Raydium CPMM LP mint
+    pub vault: Pubkey,         // Presale PDA's LP token ATA
+    pub locked_amount: u64,    // LP tokens locked at finalize
+    pub unlock_timestamp: i64, // Admin can unlock from this timestamp
+    pub unlocked: bool,
+    pub bump: u8,
+}
 
-    #[account(mut)]
-    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Referral wallet holding reward tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LpLockStatus {
+    pub lp_mint: Pubkey,
+    pub locked_amount: u64, // LP tokens currently in the vault
+    pub unlock_timestamp: i64,
+    pub seconds_remaining: i64,
+    pub unlocked: bool,
 }
 
-#[derive(Accounts)]
-pub struct FinalizePresale<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>, // ✅ Only admin can finalize the presale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VestingSchedule {
+    pub tge_percent: u8,       // % unlocked at TGE
+    pub cliff_seconds: i64,    // Lock after TGE before linear release starts
+    pub vesting_duration: i64, // Linear release length after the cliff
+}
 
-    #[account(
-        mut,
-        has_one = admin, // ✅ Ensures only the presale admin can call this
-        seeds = [PRESALE_SEED, admin.key().as_ref()],
-        bump
-    )]
-    pub presale: Account<'info, Presale>, // ✅ Presale account
+impl VestingSchedule {
+    /// Tokens out of `total` unlocked at `now` for a schedule starting at `start`.
+    pub fn vested_amount(&self, total: u64, start: i64, now: i64) -> u64 {
+        if total == 0 || now < start {
+            return 0;
+        }
 
-    #[account(mut)]
-    pub presale_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Presale tokens)
+        let tge_amount = (total as u128 * self.tge_percent as u128 / 100) as u64;
+        let elapsed = now - start;
 
-    #[account(mut)]
-    pub referral_wallet: Account<'info, TokenAccount>, // ✅ Source wallet (Referral tokens)
+        if elapsed < self.cliff_seconds {
+            return tge_amount;
+        }
 
-    #[account(mut)]
-    pub liquidity_wallet: Account<'info, TokenAccount>, // ✅ Destination wallet (Liquidity)
+        let vesting_elapsed = elapsed - self.cliff_seconds;
+        if self.vesting_duration == 0 || vesting_elapsed >= self.vesting_duration {
+            return total;
+        }
 
-    pub token_program: Program<'info, Token>, // ✅ Solana Token Program
-}
+        let linear = (total - tge_amount) as u128 * vesting_elapsed as u128
+            / self.vesting_duration as u128;
 
-#[account]
-pub struct Presale {
-    pub admin: Pubkey,              // Admin wallet address
-    pub presale_start: i64,         // Presale start timestamp (Unix time)
-    pub private_price: u64,         // Token price in private sale
-    pub public_price: u64,          // Token price in public sale
-    pub current_price: u64,         // Current token price (updates dynamically)
-    pub private_sale_duration: i64, // Private sale duration
-    pub public_sale_duration: i64,  // Public sale duration
-    pub sale_stage: u8,             // Sale stage (0: Not started, 1: Private, 2: Public, 3: Ended)
-    pub total_sold: u64,            // Total tokens sold
-    pub referral_charged: u64,      // Total tokens sold
-    pub pool_created: bool,         // Liquidity pool created flag
-    pub presale_wallet: Pubkey,     // Token account for presale
-    pub referral_wallet: Pubkey,    // Token account for referral rewards
-    pub merchant_wallet: Pubkey,
-    pub regular_referral_rate: u8, // Referral reward % for regular users
-    pub influencer_referral_rate: u8, // Referral reward % for influencers
-    pub bump: u8,                  // Store bump here
+        tge_amount + linear as u64
+    }
 }
 
 #[event]
 pub struct BuyTokensEvent {
     pub buyer: Pubkey,
     pub tokens_purchased: u64,
+    pub bonus_tokens: u64, // Referral bonus tokens charged against the referral wallet
     pub sol_spent: u64,
     pub sol_price_in_usd: u64,
     pub payment_type: u8,
+    pub presale: Pubkey,
+    pub purchase_seq: u64,            // ✅ `Presale::purchase_count` after this purchase
+    pub sale_stage: u8,
+    pub price: u64,                   // ✅ Micro-USD per token charged
+    pub referrer: Pubkey,             // ✅ Default pubkey when none
+    pub total_sold: u64,              // ✅ Totals below are the `Presale` counters after this purchase
+    pub referral_charged: u64,
+    pub platform_tokens_charged: u64,
+    pub total_raised_lamports: u64,
+    pub slot: u64,
 }
 
 #[event]
@@ -819,14 +5399,27 @@ pub struct UpdateSalePriceEvent {
     pub admin: Pubkey,
     pub new_price: u64,
     pub sale_stage: u8,
+    pub presale: Pubkey,
+    pub slot: u64,
 }
 
 #[event]
 pub struct BuyTokensByStableCoinEvent {
     pub buyer: Pubkey,
     pub tokens_purchased: u64,
+    pub bonus_tokens: u64, // Referral bonus tokens charged against the referral wallet
     pub stable_coin_amount: u64,
     pub payment_type: u8, // 0 = Web3, 1 = Web2 (Stored for withdrawal)
+    pub presale: Pubkey,
+    pub purchase_seq: u64,            // ✅ `Presale::purchase_count` after this purchase
+    pub sale_stage: u8,
+    pub price: u64,                   // ✅ Micro-USD per token charged
+    pub referrer: Pubkey,             // ✅ Default pubkey when none
+    pub total_sold: u64,              // ✅ Totals below are the `Presale` counters after this purchase
+    pub referral_charged: u64,
+    pub platform_tokens_charged: u64,
+    pub total_raised_stable: u64,
+    pub slot: u64,
 }
 
 #[event]
@@ -835,6 +5428,57 @@ pub struct ReferralRewardEvent {
     pub referred_buyer: Pubkey, // ✅ The buyer who used the referral
     pub reward_amount: u64,     // ✅ Amount of tokens rewarded
     pub is_influencer: bool,    // ✅ Whether the referrer is an influencer
+    pub presale: Pubkey,
+    pub purchase_seq: u64,      // ✅ Purchase that earned the reward
+}
+
+#[event]
+pub struct ReferralCommissionEvent {
+    pub referrer: Pubkey,       // ✅ The referrer who gets paid
+    pub referred_buyer: Pubkey, // ✅ The buyer who used the referral
+    pub commission_amount: u64, // ✅ Lamports (SOL) or USDC base units
+    pub payment_currency: u8,   // ✅ 0 = SOL, 1 = USDC
+    pub is_influencer: bool,    // ✅ Whether the referrer is an influencer
+    pub settled: bool,          // ✅ False for Web2 payments, where the backend pays the commission
+    pub presale: Pubkey,
+    pub purchase_seq: u64,      // ✅ Purchase that earned the commission
+}
+
+#[event]
+pub struct PlatformFeeEvent {
+    pub presale: Pubkey,
+    pub buyer: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub payment_fee: u64,     // ✅ Lamports (SOL) or USDC base units
+    pub payment_currency: u8, // ✅ 0 = SOL, 1 = USDC
+    pub token_fee: u64,       // ✅ Whole sale tokens
+    pub settled: bool,        // ✅ False for Web2 payments, where the backend pays the payment fee
+    pub purchase_seq: u64,    // ✅ Purchase the fee was taken from
+}
+
+#[event]
+pub struct SetAcceptedMintsEvent {
+    pub authority: Pubkey,
+    pub accepted_stable_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct PlatformUpdatedEvent {
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub raise_fee_bps: u16,
+    pub token_fee_bps: u16,
+    pub allowed_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct SetReferralRewardModeEvent {
+    pub admin: Pubkey,
+    pub referral_reward_mode: u8,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
 }
 
 #[event]
@@ -842,13 +5486,446 @@ pub struct SetReferralRateEvent {
     pub admin: Pubkey,
     pub regular_referral_rate: u8,
     pub influencer_referral_rate: u8,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetBuyerBonusRateEvent {
+    pub admin: Pubkey,
+    pub buyer_bonus_rate: u8,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetSolPriceEvent {
+    pub admin: Pubkey,
+    pub sol_price_in_usd: u64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetPaymentAuthorityEvent {
+    pub admin: Pubkey,
+    pub payment_authority: Pubkey,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MigratePresaleEvent {
+    pub admin: Pubkey,
+    pub presale: Pubkey,
+    pub sale_id: u64,
+    pub version: u8,
+}
+
+#[event]
+pub struct DeliverV1AllocationEvent {
+    pub admin: Pubkey,
+    pub presale: Pubkey,
+    pub recipient: Pubkey,    // ✅ Owner of the receiving token account
+    pub amount: u64,          // ✅ Whole tokens delivered
+    pub v1_undelivered: u64,  // ✅ v1 purchases left to deliver, whole tokens
+    pub reserved_tokens: u64, // ✅ `Presale::reserved_tokens` after the delivery, base units
+    pub slot: u64,
+}
+
+#[event]
+pub struct DepositTokensEvent {
+    pub admin: Pubkey,
+    pub wallet: Pubkey,          // Presale or referral wallet
+    pub amount: u64,             // Whole tokens deposited
+    pub total_deposited: u64,    // Running total for that wallet
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetVestingScheduleEvent {
+    pub admin: Pubkey,
+    pub sale_stage: u8, // Stage the schedule applies to
+    pub tge_percent: u8,
+    pub cliff_seconds: i64,
+    pub vesting_duration: i64,
+    pub presale: Pubkey,
+    pub current_stage: u8,
+    pub current_price: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimTokensEvent {
+    pub buyer: Pubkey,
+    pub amount: u64,        // ✅ Tokens claimed in this call
+    pub total_claimed: u64, // ✅ Tokens claimed by this buyer so far
+    pub presale: Pubkey,
+    pub reserved_tokens: u64, // ✅ `Presale::reserved_tokens` after the claim, base units
+    pub slot: u64,
+}
+
+#[event]
+pub struct CreateGrantEvent {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub start: i64,
+    pub tge_percent: u8,
+    pub cliff_seconds: i64,
+    pub vesting_duration: i64,
+    pub revocable: bool,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimGrantEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RevokeGrantEvent {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,   // ✅ Stays claimable by the beneficiary
+    pub returned_amount: u64, // ✅ Unvested tokens returned to the admin
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct CreateAirdropEvent {
+    pub admin: Pubkey,
+    pub airdrop_id: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub num_recipients: u32,
+    pub expiry: i64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimAirdropEvent {
+    pub airdrop_id: u64,
+    pub claimant: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClawbackAirdropEvent {
+    pub admin: Pubkey,
+    pub airdrop_id: u64,
+    pub unclaimed_amount: u64, // ✅ Base units returned to the admin
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetEmissionRateEvent {
+    pub admin: Pubkey,
+    pub reward_rate: u64,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetLockupOptionsEvent {
+    pub admin: Pubkey,
+    pub lockup_options: Vec<LockupOption>,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetPrivateBuyerBonusEvent {
+    pub admin: Pubkey,
+    pub private_buyer_bonus_bps: u16,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct FundRewardsEvent {
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub reward_unallocated: u64,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub owner: Pubkey,
+    pub amount: u64,       // ✅ Base units added
+    pub total_staked: u64, // ✅ Position size after staking
+    pub multiplier_bps: u16,
+    pub lock_until: i64,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct UnstakeEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimRewardsEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub presale: Pubkey, // ✅ Sale the stake pool belongs to
+    pub slot: u64,
+}
+
+#[event]
+pub struct SetPoolConfigEvent {
+    pub admin: Pubkey,
+    pub enabled: bool,
+    pub quote_mint: Pubkey,
+    pub raise_percent: u8,
+    pub token_amount: u64,
+    pub lp_lock_duration: i64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolSeededEvent {
+    pub admin: Pubkey,
+    pub pool_state: Pubkey, // ✅ Raydium CPMM pool
+    pub lp_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub token_amount: u64,  // ✅ Sale token base units deposited
+    pub quote_amount: u64,  // ✅ Lamports or USDC base units deposited
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct LpLockedEvent {
+    pub pool_state: Pubkey,
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct LpUnlockedEvent {
+    pub admin: Pubkey,
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
 }
 
 #[event]
 pub struct FinalizePresaleEvent {
     pub admin: Pubkey,              // ✅ Admin who finalized presale
-    pub unsold_presale_tokens: u64, // ✅ Number of unsold presale tokens moved to liquidity wallet
-    pub unsold_reward_tokens: u64, // ✅ Number of unsold referral tokens moved to liquidity wallet
+    pub unsold_presale_tokens: u64, // ✅ Number of unsold presale tokens disposed of
+    pub unsold_reward_tokens: u64, // ✅ Number of unsold referral tokens disposed of
+    pub burned_tokens: u64,        // ✅ Unsold tokens burned
+    pub treasury_tokens: u64,      // ✅ Unsold tokens returned to the treasury wallet
+    pub liquidity_tokens: u64,     // ✅ Unsold tokens moved to liquidity wallet
+    pub presale: Pubkey,
+    pub sale_stage: u8,
+    pub slot: u64,
+}
+
+/// One admin change, with the values before and after where the instruction
+/// overwrites existing state. Durations are in seconds, as stored on the account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum AdminAction {
+    Initialize {
+        sale_id: u64,
+        private_price: u64,
+        public_price: u64,
+        private_sale_duration: i64,
+        public_sale_duration: i64,
+        regular_referral_rate: u8,
+        influencer_referral_rate: u8,
+        sale_allocation: u64,
+    },
+    Migrate {
+        old_version: u8,
+        new_version: u8,
+    },
+    SetStage {
+        old_stage: u8,
+        new_stage: u8,
+        old_price: u64,
+        new_price: u64,
+    },
+    UpdateSalePeriod {
+        old_private_sale_duration: i64,
+        new_private_sale_duration: i64,
+        old_public_sale_duration: i64,
+        new_public_sale_duration: i64,
+    },
+    UpdateSalePrice {
+        sale_stage: u8,
+        old_price: u64,
+        new_price: u64,
+    },
+    SetReferralRate {
+        old_regular_rate: u8,
+        new_regular_rate: u8,
+        old_influencer_rate: u8,
+        new_influencer_rate: u8,
+    },
+    SetReferralRewardMode {
+        old_mode: u8,
+        new_mode: u8,
+    },
+    SetBuyerBonusRate {
+        old_rate: u8,
+        new_rate: u8,
+    },
+    SetVestingSchedule {
+        sale_stage: u8,
+        old_schedule: VestingSchedule,
+        new_schedule: VestingSchedule,
+    },
+    SetPoolConfig {
+        old_config: PoolConfig,
+        new_config: PoolConfig,
+    },
+    Finalize {
+        with_pool: bool,
+        tge_timestamp: i64,
+        unsold_presale_tokens: u64, // Base units
+        unsold_reward_tokens: u64,  // Base units
+    },
+    SetSolPrice {
+        old_price: u64,
+        new_price: u64,
+    },
+    SetPaymentAuthority {
+        old_authority: Pubkey,
+        new_authority: Pubkey,
+    },
+    SetAcceptedMints {
+        old_mints: Vec<Pubkey>,
+        new_mints: Vec<Pubkey>,
+    },
+    SetPlatform {
+        old_fee_recipient: Pubkey,
+        new_fee_recipient: Pubkey,
+        old_raise_fee_bps: u16,
+        new_raise_fee_bps: u16,
+        old_token_fee_bps: u16,
+        new_token_fee_bps: u16,
+        old_allowed_mints: Vec<Pubkey>,
+        new_allowed_mints: Vec<Pubkey>,
+    },
+    DepositSaleTokens {
+        amount: u64, // Whole tokens
+        old_total_deposited: u64,
+        new_total_deposited: u64,
+    },
+    DepositRewardTokens {
+        amount: u64, // Whole tokens
+        old_total_deposited: u64,
+        new_total_deposited: u64,
+    },
+    CreateGrant {
+        beneficiary: Pubkey,
+        amount: u64, // Whole tokens
+        start: i64,
+        schedule: VestingSchedule,
+        revocable: bool,
+    },
+    RevokeGrant {
+        beneficiary: Pubkey,
+        old_amount: u64,      // Whole tokens
+        new_amount: u64,      // Vested part that stays claimable
+        returned_amount: u64, // Unvested part sent back to the admin
+    },
+    CreateAirdrop {
+        airdrop_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64, // Whole tokens
+        num_recipients: u32,
+        expiry: i64,
+    },
+    ClawbackAirdrop {
+        airdrop_id: u64,
+        unclaimed_amount: u64, // Base units
+    },
+    UnlockLp {
+        lp_mint: Pubkey,
+        amount: u64, // LP base units
+    },
+    SetEmissionRate {
+        old_rate: u64,
+        new_rate: u64,
+    },
+    SetLockupOptions {
+        old_options: Vec<LockupOption>,
+        new_options: Vec<LockupOption>,
+    },
+    SetPrivateBuyerBonus {
+        old_bps: u16,
+        new_bps: u16,
+    },
+    InitStakePool {
+        reward_rate: u64,
+        lockup_options: Vec<LockupOption>,
+    },
+    FundRewards {
+        amount: u64, // Reward base units
+        old_reward_unallocated: u64,
+        new_reward_unallocated: u64,
+    },
+    DeliverV1Allocation {
+        recipient: Pubkey,
+        amount: u64, // Whole tokens
+        old_undelivered: u64,
+        new_undelivered: u64,
+    },
+}
+
+#[event]
+pub struct AdminActionEvent {
+    pub presale: Pubkey,
+    pub admin: Pubkey,
+    pub action: AdminAction,
+    pub slot: u64,
+    pub unix_timestamp: i64,
 }
 
 #[error_code]
@@ -883,7 +5960,7 @@ pub enum PresaleError {
     #[msg("Insufficient SOL sent for purchase.")]
     InsufficientFunds,
 
-    #[msg("Invalid stable token. Only USDC is accepted.")]
+    #[msg("Invalid stable token. Only stable coins accepted by the program config can be used.")]
     InvalidStableToken,
 
     #[msg("Not enough USDC available for purchase.")]
@@ -913,4 +5990,79 @@ pub enum PresaleError {
 
     #[msg("The liquidity pool has already been created.")]
     LiquidityPoolAlreadyCreated,
+
+    #[msg("Invalid referrer: referrer account does not match or buyer referred themselves.")]
+    InvalidReferrer,
+
+    #[msg("Invalid referral reward mode. Please choose 0 or 1")]
+    InvalidReferralRewardMode,
+
+    #[msg("Currency commissions need the referrer co-signed by the sale's payment authority.")]
+    ReferrerNotVouched,
+
+    #[msg("Invalid sale stage. Please choose 1 or 2")]
+    InvalidSaleStage,
+
+    #[msg("Invalid vesting schedule: cliff and duration must not be negative.")]
+    InvalidVestingSchedule,
+
+    #[msg("No vested tokens available to claim.")]
+    NothingToClaim,
+
+    #[msg("Web2 purchases must be co-signed by the sale's payment authority.")]
+    PaymentNotAuthorized,
+
+    #[msg("Invalid amount: must be greater than zero.")]
+    InvalidAmount,
+
+    #[msg("The vesting grant is not revocable or was already revoked.")]
+    GrantNotRevocable,
+
+    #[msg("Invalid Merkle proof for this airdrop claim.")]
+    InvalidMerkleProof,
+
+    #[msg("This airdrop allocation has already been claimed.")]
+    AirdropAlreadyClaimed,
+
+    #[msg("The airdrop has expired or was clawed back.")]
+    AirdropExpired,
+
+    #[msg("The airdrop has not expired yet.")]
+    AirdropNotExpired,
+
+    #[msg("Invalid lockup option.")]
+    InvalidLockupOption,
+
+    #[msg("Staked tokens are still locked.")]
+    StakeLocked,
+
+    #[msg("Not enough staked tokens.")]
+    InsufficientStake,
+
+    #[msg("Finalize with the instruction matching the pool configuration.")]
+    PoolConfigMismatch,
+
+    #[msg("LP tokens are still locked.")]
+    LpStillLocked,
+
+    #[msg("LP tokens have already been unlocked.")]
+    LpAlreadyUnlocked,
+
+    #[msg("Unsold token disposition percentages must add up to 100.")]
+    InvalidUnsoldDisposition,
+
+    #[msg("The sale allocation has not been deposited yet.")]
+    AllocationNotDeposited,
+
+    #[msg("This token mint is not allowed on the platform.")]
+    MintNotAllowed,
+
+    #[msg("Too many allowed or accepted mints.")]
+    TooManyAllowedMints,
+
+    #[msg("The presale account is already in the current layout.")]
+    AlreadyMigrated,
+
+    #[msg("Arithmetic overflow.")]
+    MathOverflow,
 }
@@ -0,0 +1,199 @@
+//! Admin instructions leave an `AdminActionEvent` with the values they replaced.
+
+mod common;
+
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use common::*;
+use presale::constant::*;
+use presale::{AdminAction, AdminActionEvent, LockupOption};
+
+#[test]
+fn stage_and_period_changes_record_old_and_new_values() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+
+    let started = set_stage(&mut svm, &sale).unwrap();
+    let actions = events::<AdminActionEvent>(&started.logs);
+    assert_eq!(actions.len(), 1);
+    assert_eq!((actions[0].presale, actions[0].admin), (sale.presale, sale.admin.pubkey()));
+    let AdminAction::SetStage { old_stage, new_stage, old_price, new_price } = actions[0].action else {
+        panic!("expected SetStage");
+    };
+    assert_eq!((old_stage, new_stage), (0, 1));
+    assert_eq!((old_price, new_price), (3500, 3500));
+
+    let public = set_stage(&mut svm, &sale).unwrap();
+    let actions = events::<AdminActionEvent>(&public.logs);
+    let AdminAction::SetStage { old_stage, new_stage, new_price, .. } = actions[0].action else {
+        panic!("expected SetStage");
+    };
+    assert_eq!((old_stage, new_stage), (1, 2));
+    assert_eq!(new_price, fetch::<presale::Presale>(&svm, &sale.presale).public_price);
+
+    let updated = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::UpdateSalePeriod {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::UpdateSalePeriod {
+                new_private_sale_duration: 10,
+                new_public_sale_duration: 30,
+            },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let actions = events::<AdminActionEvent>(&updated.logs);
+    let AdminAction::UpdateSalePeriod {
+        new_private_sale_duration,
+        new_public_sale_duration,
+        ..
+    } = actions[0].action
+    else {
+        panic!("expected UpdateSalePeriod");
+    };
+    assert_eq!((new_private_sale_duration, new_public_sale_duration), (10 * 86400, 30 * 86400));
+}
+
+#[test]
+fn deposits_and_program_settings_record_old_and_new_values() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+
+    let admin_token_account = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    mint_to(&mut svm, &sale.mint, &sale.mint_authority, &admin_token_account, 10 * TOKEN);
+    let deposited = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::DepositSaleTokens {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::DepositSaleTokens { amount: 10 },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let actions = events::<AdminActionEvent>(&deposited.logs);
+    assert_eq!(actions.len(), 1);
+    let AdminAction::DepositSaleTokens {
+        amount,
+        old_total_deposited,
+        new_total_deposited,
+    } = actions[0].action
+    else {
+        panic!("expected DepositSaleTokens");
+    };
+    assert_eq!((amount, old_total_deposited, new_total_deposited), (10, 1_000_000, 1_000_010));
+
+    // Program-wide settings are filed under the config PDA
+    let authority = upgrade_authority();
+    let old_mints = fetch::<presale::ProgramConfig>(&svm, &config_pda()).accepted_stable_mints;
+    let new_mints = vec![sale.mint];
+    let updated = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetAcceptedMints {
+                authority: authority.pubkey(),
+                config: config_pda(),
+            },
+            presale::instruction::SetAcceptedMints {
+                accepted_stable_mints: new_mints.clone(),
+            },
+        )],
+        &authority,
+        &[],
+    )
+    .unwrap();
+    let actions = events::<AdminActionEvent>(&updated.logs);
+    assert_eq!((actions[0].presale, actions[0].admin), (config_pda(), authority.pubkey()));
+    let AdminAction::SetAcceptedMints { old_mints: old, new_mints: new } = actions[0].action.clone() else {
+        panic!("expected SetAcceptedMints");
+    };
+    assert_eq!((old, new), (old_mints, new_mints));
+}
+
+#[test]
+fn stake_pool_setup_and_funding_are_recorded() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let stake_pool = Pubkey::find_program_address(&[STAKE_POOL_SEED, sale.presale.as_ref()], &presale::ID).0;
+    let stake_vault = Pubkey::find_program_address(&[STAKE_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+    let reward_vault = Pubkey::find_program_address(&[REWARD_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+    let lockup_options = vec![LockupOption {
+        duration: 30 * DAY,
+        multiplier_bps: 15_000,
+    }];
+
+    let initialized = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::InitStakePool {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                token_mint: sale.mint,
+                stake_pool,
+                stake_vault,
+                reward_vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::InitStakePool {
+                reward_rate: 1_000,
+                lockup_options,
+            },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let actions = events::<AdminActionEvent>(&initialized.logs);
+    assert_eq!((actions[0].presale, actions[0].admin), (sale.presale, sale.admin.pubkey()));
+    let AdminAction::InitStakePool { reward_rate, lockup_options } = actions[0].action.clone() else {
+        panic!("expected InitStakePool");
+    };
+    assert_eq!(reward_rate, 1_000);
+    assert_eq!(lockup_options.len(), 1);
+    assert_eq!((lockup_options[0].duration, lockup_options[0].multiplier_bps), (30 * DAY, 15_000));
+
+    let admin_token_account = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    mint_to(&mut svm, &sale.mint, &sale.mint_authority, &admin_token_account, 10 * TOKEN);
+    let funded = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::FundRewards {
+                admin: sale.admin.pubkey(),
+                stake_pool,
+                reward_vault,
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::FundRewards { amount: 10 * TOKEN },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let actions = events::<AdminActionEvent>(&funded.logs);
+    assert_eq!(actions[0].presale, sale.presale);
+    let AdminAction::FundRewards {
+        amount,
+        old_reward_unallocated,
+        new_reward_unallocated,
+    } = actions[0].action
+    else {
+        panic!("expected FundRewards");
+    };
+    assert_eq!(amount, 10 * TOKEN);
+    assert_eq!((old_reward_unallocated, new_reward_unallocated), (0, 10 * TOKEN));
+}
@@ -0,0 +1,853 @@
+//! Shared harness for the presale program integration tests.
+//!
+//! The program runs natively inside `solana-program-test`, so `cargo test -p presale` needs no
+//! SBF toolchain. SPL Token and the associated token program are the real BPF builds that ship
+//! with `solana-program-test`. `Svm` wraps the async test context behind a blocking API.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    solana_program::program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_program, AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator, InstructionData,
+    ToAccountMetas,
+    __private::base64::{engine::general_purpose::STANDARD, Engine},
+};
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar::{Sysvar, SysvarId},
+    transaction::{Transaction, TransactionError},
+};
+use solana_system_interface::instruction as system_instruction;
+use std::sync::Once;
+use tokio::runtime::Runtime;
+
+use presale::{constant::*, ProgramConfig};
+
+pub const TOKEN: u64 = 1_000_000_000; // Sale token has 9 decimals
+pub const DAY: i64 = 86_400;
+
+pub const USDC: u64 = 1_000_000; // Stable coins have 6 decimals
+
+pub struct TestSale {
+    pub admin: Keypair,
+    pub mint: Pubkey,
+    pub mint_authority: Keypair,
+    pub presale: Pubkey,
+    pub presale_wallet: Pubkey,
+    pub referral_wallet: Pubkey,
+    pub merchant: Keypair,
+    pub merchant_wallet: Pubkey,
+    pub treasury_wallet: Pubkey,
+    pub platform_fee_recipient: Pubkey,
+    pub platform_fee_token_account: Pubkey,
+}
+
+/// An accepted stable coin and the sale's merchant and platform fee accounts for it.
+pub struct TestStable {
+    pub mint: Pubkey,
+    pub mint_authority: Keypair,
+    pub merchant_account: Pubkey,
+    pub platform_fee_account: Pubkey,
+}
+
+#[derive(Debug)]
+pub struct TransactionMetadata {
+    pub logs: Vec<String>,
+    pub return_data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct FailedTransactionMetadata {
+    pub err: TransactionError,
+    pub meta: TransactionMetadata,
+}
+
+pub type TransactionResult = Result<TransactionMetadata, FailedTransactionMetadata>;
+
+/// Blocking front for a `ProgramTestContext`.
+pub struct Svm {
+    runtime: Runtime,
+    context: ProgramTestContext,
+    rent: Rent,
+}
+
+impl Svm {
+    pub fn send_transaction(&mut self, tx: Transaction) -> TransactionResult {
+        let banks_client = self.context.banks_client.clone();
+        let processed = self
+            .runtime
+            .block_on(banks_client.process_transaction_with_metadata(tx))
+            .expect("banks server is running");
+        let meta = match processed.metadata {
+            Some(metadata) => TransactionMetadata {
+                logs: metadata.log_messages.into_iter().map(restore_program_data).collect(),
+                return_data: metadata.return_data.map(|data| data.data).unwrap_or_default(),
+            },
+            None => TransactionMetadata {
+                logs: vec![],
+                return_data: vec![],
+            },
+        };
+        match processed.result {
+            Ok(()) => Ok(meta),
+            Err(err) => Err(FailedTransactionMetadata { err, meta }),
+        }
+    }
+
+    pub fn latest_blockhash(&self) -> solana_sdk::hash::Hash {
+        self.context.last_blockhash
+    }
+
+    /// Moves to the next slot, which records a new blockhash, so an identical
+    /// transaction sent again gets a new signature.
+    pub fn expire_blockhash(&mut self) {
+        let slot = self.get_sysvar::<Clock>().slot;
+        self.context.warp_to_slot(slot + 1).unwrap();
+    }
+
+    pub fn get_account(&self, address: &Pubkey) -> Option<Account> {
+        let banks_client = self.context.banks_client.clone();
+        self.runtime.block_on(banks_client.get_account(*address)).unwrap()
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.context.set_account(&address, &AccountSharedData::from(account));
+    }
+
+    pub fn get_balance(&self, address: &Pubkey) -> Option<u64> {
+        self.get_account(address).map(|account| account.lamports)
+    }
+
+    pub fn airdrop(&mut self, address: &Pubkey, lamports: u64) {
+        let mut account = self.get_account(address).unwrap_or(Account {
+            owner: system_program::ID,
+            ..Account::default()
+        });
+        account.lamports += lamports;
+        self.set_account(*address, account);
+    }
+
+    pub fn get_sysvar<T: Sysvar>(&self) -> T {
+        let banks_client = self.context.banks_client.clone();
+        self.runtime.block_on(banks_client.get_sysvar::<T>()).unwrap()
+    }
+
+    pub fn set_sysvar<T: SysvarId + Sysvar>(&mut self, sysvar: &T) {
+        self.context.set_sysvar(sysvar);
+    }
+
+    pub fn minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
+        self.rent.minimum_balance(data_len)
+    }
+}
+
+/// Prefix `sol_log_data` lines carry through `sol_log`, restored to `Program data: ` after the transaction.
+const PROGRAM_DATA_MARKER: &str = "\u{1}program-data\u{1} ";
+
+/// `solana-program-test`'s syscall stubs drop `sol_log_data` for native programs, so events
+/// would never reach the transaction logs. Routes them through `sol_log` and forwards the rest.
+struct EventLogStubs(Box<dyn SyscallStubs>);
+
+impl SyscallStubs for EventLogStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_sysvar(&self, sysvar_id_addr: *const u8, var_addr: *mut u8, offset: u64, length: u64) -> u64 {
+        self.0.sol_get_sysvar(sysvar_id_addr, var_addr, offset, length)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_epoch_stake(&self, vote_address: *const u8) -> u64 {
+        self.0.sol_get_epoch_stake(vote_address)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.0.sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.0.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter().map(|field| STANDARD.encode(field)).collect();
+        self.0.sol_log(&format!("{PROGRAM_DATA_MARKER}{}", fields.join(" ")))
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+fn restore_program_data(line: String) -> String {
+    match line.strip_prefix("Program log: ").and_then(|line| line.strip_prefix(PROGRAM_DATA_MARKER)) {
+        Some(data) => format!("Program data: {data}"),
+        None => line,
+    }
+}
+
+struct NoStubs;
+impl SyscallStubs for NoStubs {}
+
+/// Authority recorded in the program config the harness installs.
+pub fn upgrade_authority() -> Keypair {
+    Keypair::new_from_array([7; 32])
+}
+
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Anchor's entry wants `&'info [AccountInfo<'info>]`, the test processor hands out shorter borrows
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    presale::entry(program_id, accounts, data)
+}
+
+/// The presale program as a native builtin. Add other programs before passing it to `start`.
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("presale", presale::ID, processor!(process_instruction));
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+pub fn start(program_test: ProgramTest) -> Svm {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+    // `solana-program-test` installs its stubs when the first bank starts. Wrap them before any
+    // test gets to run a transaction, the other threads wait on the `Once` meanwhile.
+    static EVENT_LOGS: Once = Once::new();
+    EVENT_LOGS.call_once(|| {
+        runtime.block_on(ProgramTest::default().start_with_context());
+        let stubs = set_syscall_stubs(Box::new(NoStubs));
+        set_syscall_stubs(Box::new(EventLogStubs(stubs)));
+    });
+
+    let context = runtime.block_on(program_test.start_with_context());
+    let mut svm = Svm {
+        runtime,
+        context,
+        rent: Rent::default(),
+    };
+    svm.rent = svm.get_sysvar::<Rent>();
+    svm.airdrop(&upgrade_authority().pubkey(), 100 * 1_000_000_000);
+    svm
+}
+
+pub fn setup() -> Svm {
+    start(program_test())
+}
+
+pub fn send(
+    svm: &mut Svm,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> TransactionResult {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        svm.latest_blockhash(),
+    );
+    let result = svm.send_transaction(tx);
+    svm.expire_blockhash();
+    result
+}
+
+/// Asserts the transaction failed with `expected`, a `PresaleError` or an Anchor `ErrorCode`.
+pub fn assert_error<E: Into<u32> + std::fmt::Debug + Copy>(result: TransactionResult, expected: E) {
+    let failed = match result {
+        Ok(_) => panic!("expected {expected:?}, transaction succeeded"),
+        Err(failed) => failed,
+    };
+    match failed.err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, expected.into(), "expected {expected:?}, logs: {:#?}", failed.meta.logs)
+        }
+        err => panic!("expected {expected:?}, got {err:?}, logs: {:#?}", failed.meta.logs),
+    }
+}
+
+pub fn ix<A: ToAccountMetas, D: InstructionData>(accounts: A, data: D) -> Instruction {
+    Instruction {
+        program_id: presale::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn funded_keypair(svm: &mut Svm) -> Keypair {
+    let keypair = Keypair::new();
+    svm.airdrop(&keypair.pubkey(), 100 * 1_000_000_000);
+    keypair
+}
+
+pub fn create_mint(svm: &mut Svm, authority: &Keypair, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN);
+    send(
+        svm,
+        &[
+            system_instruction::create_account(
+                &authority.pubkey(),
+                &mint.pubkey(),
+                rent,
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &authority.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ],
+        authority,
+        &[&mint],
+    )
+    .unwrap();
+    mint.pubkey()
+}
+
+pub fn create_token_account(svm: &mut Svm, payer: &Keypair, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    let account = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(spl_token::state::Account::LEN);
+    send(
+        svm,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent,
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), mint, owner)
+                .unwrap(),
+        ],
+        payer,
+        &[&account],
+    )
+    .unwrap();
+    account.pubkey()
+}
+
+pub fn mint_to(svm: &mut Svm, mint: &Pubkey, authority: &Keypair, destination: &Pubkey, amount: u64) {
+    send(
+        svm,
+        &[spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            destination,
+            &authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        authority,
+        &[],
+    )
+    .unwrap();
+}
+
+pub fn token_balance(svm: &Svm, account: &Pubkey) -> u64 {
+    let data = svm.get_account(account).expect("token account exists").data;
+    spl_token::state::Account::unpack(&data).unwrap().amount
+}
+
+pub fn fetch<T: AccountDeserialize>(svm: &Svm, address: &Pubkey) -> T {
+    let data = svm.get_account(address).expect("account exists").data;
+    T::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+/// Decodes every `E` emitted in a transaction's logs, in order.
+pub fn events<E: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter(|data| data.starts_with(E::DISCRIMINATOR))
+        .map(|data| E::try_from_slice(&data[8..]).unwrap())
+        .collect()
+}
+
+/// Decodes a view instruction's return data. Trailing zero bytes may be trimmed by the runtime.
+pub fn return_data<T: AnchorDeserialize>(meta: &TransactionMetadata) -> T {
+    let mut data = meta.return_data.clone();
+    data.resize(data.len() + 1024, 0);
+    T::deserialize(&mut data.as_slice()).unwrap()
+}
+
+pub fn warp(svm: &mut Svm, seconds: i64) {
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += seconds;
+    svm.set_sysvar::<Clock>(&clock);
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], &presale::ID).0
+}
+
+pub fn platform_pda() -> Pubkey {
+    Pubkey::find_program_address(&[PLATFORM_SEED], &presale::ID).0
+}
+
+pub fn presale_pda(sale_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[PRESALE_SEED, &sale_id.to_le_bytes()], &presale::ID).0
+}
+
+/// Writes the program config `init_config` would create. A native builtin has no program data
+/// account, so the upgrade authority check of `init_config` cannot run here.
+pub fn install_config(svm: &mut Svm) {
+    let (config, bump) = Pubkey::find_program_address(&[CONFIG_SEED], &presale::ID);
+    let mut data = Vec::new();
+    ProgramConfig {
+        authority: upgrade_authority().pubkey(),
+        next_sale_id: 0,
+        accepted_stable_mints: vec![DEFAULT_USDC_MINT],
+        bump,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    data.resize(8 + 32 + 8 + 4 + 32 * MAX_ACCEPTED_MINTS + 1, 0);
+
+    svm.set_account(
+        config,
+        Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: presale::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// Creates the program config and a fee-free platform on first use,
+/// and returns the id the next sale will get.
+pub fn next_sale_id(svm: &mut Svm) -> u64 {
+    let config = config_pda();
+    if svm.get_account(&config).is_none() {
+        install_config(svm);
+        let authority = upgrade_authority();
+        send(
+            svm,
+            &[ix(
+                presale::accounts::InitPlatform {
+                    authority: authority.pubkey(),
+                    config,
+                    platform: platform_pda(),
+                    system_program: system_program::ID,
+                },
+                presale::instruction::InitPlatform {
+                    fee_recipient: authority.pubkey(),
+                    raise_fee_bps: 0,
+                    token_fee_bps: 0,
+                    allowed_mints: vec![],
+                },
+            )],
+            &authority,
+            &[],
+        )
+        .unwrap();
+    }
+    fetch::<ProgramConfig>(svm, &config).next_sale_id
+}
+
+pub fn buyer_ledger_pda(presale: &Pubkey, buyer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[BUYER_LEDGER_SEED, presale.as_ref(), buyer.as_ref()],
+        &presale::ID,
+    )
+    .0
+}
+
+/// Creates the sale mint and initializes a presale with the README defaults.
+/// `presale_supply` and `reward_supply` whole tokens are minted into the sale wallets.
+pub fn init_sale(svm: &mut Svm, presale_supply: u64, reward_supply: u64) -> TestSale {
+    let admin = funded_keypair(svm);
+    let mint_authority = funded_keypair(svm);
+    let mint = create_mint(svm, &mint_authority, 9);
+    let merchant = Keypair::new();
+    let merchant_wallet = merchant.pubkey();
+    let presale = presale_pda(next_sale_id(svm));
+    let treasury_wallet = create_token_account(svm, &admin, &mint, &admin.pubkey());
+
+    let presale_wallet = Keypair::new();
+    let referral_wallet = Keypair::new();
+
+    send(
+        svm,
+        &[ix(
+            presale::accounts::Initialize {
+                admin: admin.pubkey(),
+                config: config_pda(),
+                platform: platform_pda(),
+                presale,
+                token_mint: mint,
+                presale_wallet: presale_wallet.pubkey(),
+                referral_wallet: referral_wallet.pubkey(),
+                merchant_wallet,
+                treasury_wallet,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            presale::instruction::Initialize {
+                args: presale::InitializeArgs {
+                    private_price: 3500,
+                    public_price: 7000,
+                    private_sale_duration: 15,
+                    public_sale_duration: 60,
+                    regular_referral_rate: 5,
+                    influencer_referral_rate: 10,
+                    unsold_disposition: presale::UnsoldDisposition {
+                        burn_percent: 0,
+                        treasury_percent: 0,
+                        liquidity_percent: 100,
+                    },
+                    sale_allocation: presale_supply,
+                },
+            },
+        )],
+        &admin,
+        &[&presale_wallet, &referral_wallet],
+    )
+    .unwrap();
+
+    let platform_fee_recipient = fetch::<presale::Platform>(svm, &platform_pda()).fee_recipient;
+    let platform_fee_token_account = create_token_account(svm, &admin, &mint, &platform_fee_recipient);
+
+    // Stock both wallets through the program so the deposits are recorded
+    let admin_token_account = create_token_account(svm, &admin, &mint, &admin.pubkey());
+    mint_to(svm, &mint, &mint_authority, &admin_token_account, (presale_supply + reward_supply) * TOKEN);
+    let mut deposits = Vec::new();
+    if presale_supply > 0 {
+        deposits.push(ix(
+            presale::accounts::DepositSaleTokens {
+                admin: admin.pubkey(),
+                presale,
+                presale_wallet: presale_wallet.pubkey(),
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::DepositSaleTokens { amount: presale_supply },
+        ));
+    }
+    if reward_supply > 0 {
+        deposits.push(ix(
+            presale::accounts::DepositRewardTokens {
+                admin: admin.pubkey(),
+                presale,
+                referral_wallet: referral_wallet.pubkey(),
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::DepositRewardTokens { amount: reward_supply },
+        ));
+    }
+    if !deposits.is_empty() {
+        send(svm, &deposits, &admin, &[]).unwrap();
+    }
+
+    // SOL purchases are priced at $200
+    send(
+        svm,
+        &[ix(
+            presale::accounts::SetSolPrice {
+                admin: admin.pubkey(),
+                presale,
+            },
+            presale::instruction::SetSolPrice { sol_price_in_usd: 200 },
+        )],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    TestSale {
+        admin,
+        mint,
+        mint_authority,
+        presale,
+        presale_wallet: presale_wallet.pubkey(),
+        referral_wallet: referral_wallet.pubkey(),
+        merchant,
+        merchant_wallet,
+        treasury_wallet,
+        platform_fee_recipient,
+        platform_fee_token_account,
+    }
+}
+
+pub fn set_stage(svm: &mut Svm, sale: &TestSale) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::SetStage {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetStage {},
+        )],
+        &sale.admin,
+        &[],
+    )
+}
+
+pub fn buy_with_sol(
+    svm: &mut Svm,
+    sale: &TestSale,
+    buyer: &Keypair,
+    lamports_sent: u64,
+    referrer: Pubkey,
+    payment_authority: Option<&Keypair>,
+) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::BuyTokens {
+                buyer: buyer.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                merchant_wallet: sale.merchant_wallet,
+                platform_fee_wallet: sale.platform_fee_recipient,
+                platform_fee_token_account: sale.platform_fee_token_account,
+                referrer_wallet: None,
+                payment_authority: payment_authority.map(|signer| signer.pubkey()),
+                buyer_ledger: buyer_ledger_pda(&sale.presale, &buyer.pubkey()),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            presale::instruction::BuyTokens {
+                payment_type: 0,
+                lamports_sent,
+                referrer,
+                is_influencer: false,
+            },
+        )],
+        buyer,
+        payment_authority.as_slice(),
+    )
+}
+
+/// Runs `quote_purchase` for a SOL payment at the sale's $200 SOL price.
+pub fn quote_sol(svm: &mut Svm, sale: &TestSale, lamports: u64, referrer: Pubkey) -> presale::PurchaseQuote {
+    let result = send(
+        svm,
+        &[ix(
+            presale::accounts::QuotePurchase {
+                presale: sale.presale,
+                config: config_pda(),
+            },
+            presale::instruction::QuotePurchase {
+                payment_mint: spl_token::native_mint::ID,
+                amount: lamports,
+                referrer,
+                is_influencer: false,
+                referrer_vouched: false,
+            },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    return_data(&result)
+}
+
+pub fn sale_status(svm: &mut Svm, sale: &TestSale) -> presale::SaleStatus {
+    let result = send(
+        svm,
+        &[ix(
+            presale::accounts::GetSaleStatus { presale: sale.presale },
+            presale::instruction::GetSaleStatus {},
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    return_data(&result)
+}
+
+/// Creates a 6 decimal stable coin, makes it the config's only accepted mint and
+/// opens the sale's merchant and platform fee accounts for it.
+pub fn accept_stable(svm: &mut Svm, sale: &TestSale) -> TestStable {
+    let mint_authority = funded_keypair(svm);
+    let mint = create_mint(svm, &mint_authority, 6);
+    let authority = upgrade_authority();
+    send(
+        svm,
+        &[ix(
+            presale::accounts::SetAcceptedMints {
+                authority: authority.pubkey(),
+                config: config_pda(),
+            },
+            presale::instruction::SetAcceptedMints {
+                accepted_stable_mints: vec![mint],
+            },
+        )],
+        &authority,
+        &[],
+    )
+    .unwrap();
+
+    let merchant_account = create_token_account(svm, &mint_authority, &mint, &sale.merchant_wallet);
+    let platform_fee_account = create_token_account(svm, &mint_authority, &mint, &sale.platform_fee_recipient);
+
+    TestStable {
+        mint,
+        mint_authority,
+        merchant_account,
+        platform_fee_account,
+    }
+}
+
+/// Opens a stable coin account for `owner` holding `amount` whole coins.
+pub fn stable_account(svm: &mut Svm, stable: &TestStable, owner: &Pubkey, amount: u64) -> Pubkey {
+    let account = create_token_account(svm, &stable.mint_authority, &stable.mint, owner);
+    if amount > 0 {
+        mint_to(svm, &stable.mint, &stable.mint_authority, &account, amount * USDC);
+    }
+    account
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn buy_with_stable(
+    svm: &mut Svm,
+    sale: &TestSale,
+    stable: &TestStable,
+    buyer: &Keypair,
+    buyer_account: Pubkey,
+    stable_coin_amount: u64,
+    referrer: Pubkey,
+    referrer_account: Option<Pubkey>,
+    payment_authority: Option<&Keypair>,
+) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::BuyTokensByStableCoin {
+                buyer: buyer.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                buyer_stable_coin_account: buyer_account,
+                merchant_stable_coin_account: stable.merchant_account,
+                referrer_stable_coin_account: referrer_account,
+                payment_authority: payment_authority.map(|signer| signer.pubkey()),
+                platform_fee_stable_account: stable.platform_fee_account,
+                platform_fee_token_account: sale.platform_fee_token_account,
+                buyer_ledger: buyer_ledger_pda(&sale.presale, &buyer.pubkey()),
+                stable_coin_mint: stable.mint,
+                config: config_pda(),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            presale::instruction::BuyTokensByStableCoin {
+                payment_type: 0,
+                stable_coin_amount,
+                referrer,
+                is_influencer: false,
+            },
+        )],
+        buyer,
+        payment_authority.as_slice(),
+    )
+}
+
+pub fn finalize(svm: &mut Svm, sale: &TestSale, liquidity_wallet: Pubkey) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::FinalizePresale {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                liquidity_wallet,
+                treasury_wallet: sale.treasury_wallet,
+                token_mint: sale.mint,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::FinalizePresale {},
+        )],
+        &sale.admin,
+        &[],
+    )
+}
+
+pub fn claim_tokens(
+    svm: &mut Svm,
+    sale: &TestSale,
+    buyer: &Keypair,
+    buyer_token_account: Pubkey,
+) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::ClaimTokens {
+                buyer: buyer.pubkey(),
+                presale: sale.presale,
+                buyer_ledger: buyer_ledger_pda(&sale.presale, &buyer.pubkey()),
+                presale_wallet: sale.presale_wallet,
+                buyer_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::ClaimTokens {},
+        )],
+        buyer,
+        &[],
+    )
+}
@@ -0,0 +1,921 @@
+//! Every `PresaleError` the tests can reach, triggered on purpose, and which variants they cannot.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, solana_program::keccak, system_program};
+use anchor_spl::token::spl_token;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use common::*;
+use presale::constant::*;
+use presale::{LockupOption, PresaleError, StakePool, StakePosition, UnsoldDisposition};
+
+/// How a variant is covered. A new variant does not compile until it is listed in `coverage()`.
+enum Coverage {
+    /// `file::test` raises it and checks the code with `assert_error`.
+    Test(&'static str),
+    /// As `Test`, but the test is `#[ignore]`d until `fixtures/fetch.sh` has fetched the CPMM program.
+    IgnoredTest(&'static str),
+    /// The program raises it, but no test reaches that path, and why.
+    Untested(&'static str),
+    /// The program never raises it.
+    Unreached,
+}
+
+fn coverage(error: PresaleError) -> Coverage {
+    use Coverage::*;
+    use PresaleError::*;
+    match error {
+        InvalidRate | InvalidReferralRewardMode | InvalidSaleStage | InvalidVestingSchedule => {
+            Test("errors::admin_settings_are_validated")
+        }
+        InvalidUnsoldDisposition | MintNotAllowed | AllocationNotDeposited => {
+            Test("errors::initialize_is_validated")
+        }
+        TooManyAllowedMints => Test("errors::config_is_validated"),
+        Unauthorized | InvalidTokenAccount => Test("migration::migrate_presale_rejects_other_signers"),
+        AlreadyMigrated => Test("migration::migrate_presale_upgrades_v1_accounts_in_place"),
+        MathOverflow => Test("migration::migrate_presale_rejects_sold_counts_past_u64_base_units"),
+        SaleAlreadyEnded | InvalidAmount | PresaleActive | LiquidityPoolAlreadyCreated => {
+            Test("errors::stage_and_finalize_are_validated")
+        }
+        PresaleNotActive | InvalidReferrer | ReferrerNotVouched | InvalidPrice | InvalidStableToken
+        | InsufficientTokens | InsufficientRewardTokens => Test("errors::purchases_are_validated"),
+        LiquidityPoolNotCreated | NothingToClaim => Test("lifecycle::full_sale_lifecycle"),
+        GrantNotRevocable => Test("errors::grants_are_validated"),
+        InvalidMerkleProof | AirdropAlreadyClaimed | AirdropExpired | AirdropNotExpired => {
+            Test("errors::airdrops_are_validated")
+        }
+        InvalidLockupOption | StakeLocked | InsufficientStake => Test("errors::staking_is_validated"),
+        PoolConfigMismatch => Test("finalize_pool::plain_finalize_rejected_when_pool_mode_enabled"),
+        LpStillLocked | LpAlreadyUnlocked => {
+            IgnoredTest("finalize_pool::finalize_seeds_cpmm_pool_with_raised_sol")
+        }
+        InsufficientFunds => Untested(
+            "the buy_tokens check always holds, and the short quote balance of finalize_presale_with_pool \
+             needs the CPMM fixture",
+        ),
+        InvalidPaymentType | PaymentNotAuthorized => {
+            Test("errors::web2_purchases_need_the_payment_authority")
+        }
+        PrivateSaleNotOver | PublicSaleNotOver | InsufficientUSDC | InvalidSignature | ExpiredSignature
+        | NoUnsoldTokens => Unreached,
+    }
+}
+
+const PROGRAM_SOURCE: &str = include_str!("../src/presale.rs");
+
+const TEST_SOURCES: &[(&str, &str)] = &[
+    ("admin_actions", include_str!("admin_actions.rs")),
+    ("errors", include_str!("errors.rs")),
+    ("finalize_pool", include_str!("finalize_pool.rs")),
+    ("lifecycle", include_str!("lifecycle.rs")),
+    ("migration", include_str!("migration.rs")),
+    ("purchase_events", include_str!("purchase_events.rs")),
+    ("quote", include_str!("quote.rs")),
+    ("sale_status", include_str!("sale_status.rs")),
+    ("staking", include_str!("staking.rs")),
+];
+
+/// Whether the program source raises `error` outside of comments.
+fn raised_by_program(error: PresaleError) -> bool {
+    let name = format!("PresaleError::{error:?}");
+    PROGRAM_SOURCE
+        .lines()
+        .any(|line| !line.trim_start().starts_with("//") && line.contains(&name))
+}
+
+/// Checks that the test `file::test` exists, is ignored exactly when `ignored`, and passes `error` to
+/// `assert_error`.
+fn check_test_asserts(error: PresaleError, test: &str, ignored: bool) {
+    let (file, name) = test.split_once("::").unwrap();
+    let (_, source) = TEST_SOURCES
+        .iter()
+        .find(|(listed, _)| *listed == file)
+        .unwrap_or_else(|| panic!("{error:?}: no test file {file}.rs"));
+    let header = source
+        .find(&format!("\nfn {name}() {{"))
+        .unwrap_or_else(|| panic!("{error:?}: no test {test}"));
+    let attributes = &source[source[..header].rfind("#[test]").unwrap()..header];
+    assert_eq!(attributes.contains("#[ignore"), ignored, "{error:?}: {test} is ignored: {attributes}");
+
+    let body = &source[header..];
+    let body = &body[..body.find("\n}\n").unwrap()];
+    let name = format!("PresaleError::{error:?}");
+    let asserted = body.match_indices(&name).any(|(at, _)| {
+        // The variant is an argument of `assert_error` when no statement ends in between
+        let before = &body[..at];
+        before.rfind("assert_error(") > before.rfind(';')
+    });
+    assert!(asserted, "{error:?}: {test} does not pass it to assert_error");
+}
+
+#[test]
+fn every_error_is_accounted_for() {
+    use PresaleError::*;
+    let all = [
+        InvalidRate,
+        InvalidTokenAccount,
+        PrivateSaleNotOver,
+        PublicSaleNotOver,
+        SaleAlreadyEnded,
+        PresaleNotActive,
+        PresaleActive,
+        InsufficientTokens,
+        InsufficientRewardTokens,
+        InsufficientFunds,
+        InvalidStableToken,
+        InsufficientUSDC,
+        InvalidPaymentType,
+        InvalidPrice,
+        Unauthorized,
+        LiquidityPoolNotCreated,
+        InvalidSignature,
+        ExpiredSignature,
+        NoUnsoldTokens,
+        LiquidityPoolAlreadyCreated,
+        InvalidReferrer,
+        InvalidReferralRewardMode,
+        ReferrerNotVouched,
+        InvalidSaleStage,
+        InvalidVestingSchedule,
+        NothingToClaim,
+        PaymentNotAuthorized,
+        InvalidAmount,
+        GrantNotRevocable,
+        InvalidMerkleProof,
+        AirdropAlreadyClaimed,
+        AirdropExpired,
+        AirdropNotExpired,
+        InvalidLockupOption,
+        StakeLocked,
+        InsufficientStake,
+        PoolConfigMismatch,
+        LpStillLocked,
+        LpAlreadyUnlocked,
+        InvalidUnsoldDisposition,
+        AllocationNotDeposited,
+        MintNotAllowed,
+        TooManyAllowedMints,
+        AlreadyMigrated,
+        MathOverflow,
+    ];
+
+    // Codes are assigned in declaration order, so no gaps means no variant was left out of the list
+    for (index, error) in all.into_iter().enumerate() {
+        assert_eq!(u32::from(error), 6000 + index as u32, "{error:?} is out of order");
+        match coverage(error) {
+            Coverage::Test(test) => check_test_asserts(error, test, false),
+            Coverage::IgnoredTest(test) => check_test_asserts(error, test, true),
+            Coverage::Untested(why) => {
+                assert!(raised_by_program(error), "{error:?} is never raised, not {why}")
+            }
+            Coverage::Unreached => assert!(!raised_by_program(error), "{error:?} is raised"),
+        }
+    }
+}
+
+fn admin_ix<A: anchor_lang::ToAccountMetas, D: anchor_lang::InstructionData>(
+    svm: &mut Svm,
+    sale: &TestSale,
+    accounts: A,
+    data: D,
+) -> TransactionResult {
+    send(svm, &[ix(accounts, data)], &sale.admin, &[])
+}
+
+/// Sends `initialize` for a fresh mint with the given policy, without depositing anything.
+fn initialize(svm: &mut Svm, admin: &Keypair, disposition: UnsoldDisposition) -> (TransactionResult, Pubkey) {
+    let mint_authority = funded_keypair(svm);
+    let mint = create_mint(svm, &mint_authority, 9);
+    let treasury_wallet = create_token_account(svm, admin, &mint, &admin.pubkey());
+    let presale = presale_pda(next_sale_id(svm));
+    let presale_wallet = Keypair::new();
+    let referral_wallet = Keypair::new();
+
+    let result = send(
+        svm,
+        &[ix(
+            presale::accounts::Initialize {
+                admin: admin.pubkey(),
+                config: config_pda(),
+                platform: platform_pda(),
+                presale,
+                token_mint: mint,
+                presale_wallet: presale_wallet.pubkey(),
+                referral_wallet: referral_wallet.pubkey(),
+                merchant_wallet: Pubkey::new_unique(),
+                treasury_wallet,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            presale::instruction::Initialize {
+                args: presale::InitializeArgs {
+                    private_price: 3500,
+                    public_price: 7000,
+                    private_sale_duration: 15,
+                    public_sale_duration: 60,
+                    regular_referral_rate: 5,
+                    influencer_referral_rate: 10,
+                    unsold_disposition: disposition,
+                    sale_allocation: 1_000,
+                },
+            },
+        )],
+        admin,
+        &[&presale_wallet, &referral_wallet],
+    );
+    (result, presale)
+}
+
+/// Opens a sale token account for `owner` holding `amount` whole tokens.
+fn token_account(svm: &mut Svm, sale: &TestSale, owner: &Keypair, amount: u64) -> Pubkey {
+    let account = create_token_account(svm, owner, &sale.mint, &owner.pubkey());
+    if amount > 0 {
+        mint_to(svm, &sale.mint, &sale.mint_authority, &account, amount * TOKEN);
+    }
+    account
+}
+
+#[test]
+fn admin_settings_are_validated() {
+    let mut svm = setup();
+    next_sale_id(&mut svm);
+    let authority = upgrade_authority();
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::UpdatePlatform {
+                authority: authority.pubkey(),
+                platform: platform_pda(),
+            },
+            presale::instruction::UpdatePlatform {
+                fee_recipient: authority.pubkey(),
+                raise_fee_bps: MAX_PLATFORM_FEE_BPS,
+                token_fee_bps: 0,
+                allowed_mints: vec![],
+            },
+        )],
+        &authority,
+        &[],
+    )
+    .unwrap();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+
+    let referral_rate = |regular_referral_rate, influencer_referral_rate| {
+        presale::instruction::SetReferralRate {
+            regular_referral_rate,
+            influencer_referral_rate,
+        }
+    };
+    let accounts = || presale::accounts::SetReferralRate {
+        admin: sale.admin.pubkey(),
+        presale: sale.presale,
+    };
+    assert_error(admin_ix(&mut svm, &sale, accounts(), referral_rate(101, 10)), PresaleError::InvalidRate);
+    // The commission and the 10% platform fee must both fit in a payment
+    assert_error(admin_ix(&mut svm, &sale, accounts(), referral_rate(10, 91)), PresaleError::InvalidRate);
+    admin_ix(&mut svm, &sale, accounts(), referral_rate(10, 90)).unwrap();
+
+    let result = admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::SetReferralRewardMode {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+        },
+        presale::instruction::SetReferralRewardMode { referral_reward_mode: 2 },
+    );
+    assert_error(result, PresaleError::InvalidReferralRewardMode);
+
+    let vesting = |sale_stage, cliff_seconds| presale::instruction::SetVestingSchedule {
+        sale_stage,
+        tge_percent: 20,
+        cliff_seconds,
+        vesting_duration: 30 * DAY,
+    };
+    let accounts = || presale::accounts::SetVestingSchedule {
+        admin: sale.admin.pubkey(),
+        presale: sale.presale,
+    };
+    assert_error(admin_ix(&mut svm, &sale, accounts(), vesting(3, 0)), PresaleError::InvalidSaleStage);
+    assert_error(admin_ix(&mut svm, &sale, accounts(), vesting(1, -1)), PresaleError::InvalidVestingSchedule);
+    admin_ix(&mut svm, &sale, accounts(), vesting(1, 0)).unwrap();
+}
+
+#[test]
+fn initialize_is_validated() {
+    let mut svm = setup();
+    let admin = funded_keypair(&mut svm);
+
+    let (result, _) = initialize(
+        &mut svm,
+        &admin,
+        UnsoldDisposition {
+            burn_percent: 50,
+            treasury_percent: 0,
+            liquidity_percent: 40,
+        },
+    );
+    assert_error(result, PresaleError::InvalidUnsoldDisposition);
+
+    let disposition = UnsoldDisposition {
+        burn_percent: 100,
+        treasury_percent: 0,
+        liquidity_percent: 0,
+    };
+
+    // The sale cannot open before its allocation is deposited
+    let (result, presale) = initialize(&mut svm, &admin, disposition);
+    result.unwrap();
+    let result = send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetStage {
+                admin: admin.pubkey(),
+                presale,
+            },
+            presale::instruction::SetStage {},
+        )],
+        &admin,
+        &[],
+    );
+    assert_error(result, PresaleError::AllocationNotDeposited);
+
+    // Once the platform restricts mints, others cannot be listed
+    let authority = upgrade_authority();
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::UpdatePlatform {
+                authority: authority.pubkey(),
+                platform: platform_pda(),
+            },
+            presale::instruction::UpdatePlatform {
+                fee_recipient: authority.pubkey(),
+                raise_fee_bps: 0,
+                token_fee_bps: 0,
+                allowed_mints: vec![Pubkey::new_unique()],
+            },
+        )],
+        &authority,
+        &[],
+    )
+    .unwrap();
+    let (result, _) = initialize(&mut svm, &admin, disposition);
+    assert_error(result, PresaleError::MintNotAllowed);
+}
+
+#[test]
+fn config_is_validated() {
+    let mut svm = setup();
+    next_sale_id(&mut svm);
+
+    let authority = upgrade_authority();
+    let set_accepted_mints = |accepted_stable_mints: Vec<Pubkey>| {
+        ix(
+            presale::accounts::SetAcceptedMints {
+                authority: authority.pubkey(),
+                config: config_pda(),
+            },
+            presale::instruction::SetAcceptedMints { accepted_stable_mints },
+        )
+    };
+    let too_many = (0..=MAX_ACCEPTED_MINTS).map(|_| Pubkey::new_unique()).collect();
+    let result = send(&mut svm, &[set_accepted_mints(too_many)], &authority, &[]);
+    assert_error(result, PresaleError::TooManyAllowedMints);
+    let most = (0..MAX_ACCEPTED_MINTS).map(|_| Pubkey::new_unique()).collect();
+    send(&mut svm, &[set_accepted_mints(most)], &authority, &[]).unwrap();
+}
+
+#[test]
+fn stage_and_finalize_are_validated() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let admin_token_account = token_account(&mut svm, &sale, &sale.admin, 10);
+    let deposit = |amount| presale::instruction::DepositSaleTokens { amount };
+    let accounts = || presale::accounts::DepositSaleTokens {
+        admin: sale.admin.pubkey(),
+        presale: sale.presale,
+        presale_wallet: sale.presale_wallet,
+        admin_token_account,
+        token_program: spl_token::ID,
+    };
+    assert_error(admin_ix(&mut svm, &sale, accounts(), deposit(0)), PresaleError::InvalidAmount);
+
+    let liquidity_wallet = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    set_stage(&mut svm, &sale).unwrap();
+    assert_error(finalize(&mut svm, &sale, liquidity_wallet), PresaleError::PresaleActive);
+
+    set_stage(&mut svm, &sale).unwrap();
+    set_stage(&mut svm, &sale).unwrap();
+    assert_error(set_stage(&mut svm, &sale), PresaleError::SaleAlreadyEnded);
+    assert_error(admin_ix(&mut svm, &sale, accounts(), deposit(10)), PresaleError::SaleAlreadyEnded);
+
+    // The sale's own wallets are the only sources a finalize will sweep
+    let finalize_from = |presale_wallet, referral_wallet| presale::accounts::FinalizePresale {
+        admin: sale.admin.pubkey(),
+        presale: sale.presale,
+        presale_wallet,
+        referral_wallet,
+        liquidity_wallet,
+        treasury_wallet: sale.treasury_wallet,
+        token_mint: sale.mint,
+        token_program: spl_token::ID,
+    };
+    let foreign = finalize_from(admin_token_account, sale.referral_wallet);
+    let result = admin_ix(&mut svm, &sale, foreign, presale::instruction::FinalizePresale {});
+    assert_error(result, ErrorCode::ConstraintAddress);
+    let foreign = finalize_from(sale.presale_wallet, admin_token_account);
+    let result = admin_ix(&mut svm, &sale, foreign, presale::instruction::FinalizePresale {});
+    assert_error(result, ErrorCode::ConstraintAddress);
+
+    finalize(&mut svm, &sale, liquidity_wallet).unwrap();
+    assert_error(finalize(&mut svm, &sale, liquidity_wallet), PresaleError::LiquidityPoolAlreadyCreated);
+}
+
+#[test]
+fn purchases_are_validated() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100);
+    let unlisted = accept_stable(&mut svm, &sale);
+    let stable = accept_stable(&mut svm, &sale);
+    let buyer = funded_keypair(&mut svm);
+    let buyer_usdc = stable_account(&mut svm, &stable, &buyer.pubkey(), 1_000);
+
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, Pubkey::default(), None),
+        PresaleError::PresaleNotActive,
+    );
+    set_stage(&mut svm, &sale).unwrap();
+
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, buyer.pubkey(), None),
+        PresaleError::InvalidReferrer,
+    );
+    // $0.20 is below the $1 minimum
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &buyer, 1_000_000, Pubkey::default(), None),
+        PresaleError::InvalidPrice,
+    );
+    // 1 SOL buys 57_142 tokens, 5% of them is more than the 100 reward tokens deposited
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, Pubkey::new_unique(), None),
+        PresaleError::InsufficientRewardTokens,
+    );
+    // $4_000 buys more than the sale holds
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &buyer, 20_000_000_000, Pubkey::default(), None),
+        PresaleError::InsufficientTokens,
+    );
+
+    // The config lists one stable coin, so the first is no longer accepted
+    let unlisted_account = stable_account(&mut svm, &unlisted, &buyer.pubkey(), 10);
+    let no_referrer = Pubkey::default();
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &unlisted, &buyer, unlisted_account, 10, no_referrer, None, None),
+        PresaleError::InvalidStableToken,
+    );
+
+    // The payment must be in the credited mint and go to the sale's merchant
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, unlisted_account, 10, no_referrer, None, None),
+        ErrorCode::ConstraintTokenMint,
+    );
+    let paid_to_self = TestStable {
+        mint: stable.mint,
+        mint_authority: Keypair::new(),
+        merchant_account: stable_account(&mut svm, &stable, &buyer.pubkey(), 0),
+        platform_fee_account: stable.platform_fee_account,
+    };
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &paid_to_self, &buyer, buyer_usdc, 10, no_referrer, None, None),
+        ErrorCode::ConstraintTokenOwner,
+    );
+
+    // Commission mode needs the referrer's account to pay into
+    admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::SetReferralRewardMode {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+        },
+        presale::instruction::SetReferralRewardMode { referral_reward_mode: 1 },
+    )
+    .unwrap();
+    // The buyer's own word is not enough for a referrer paid out of the payment
+    let referrer = Pubkey::new_unique();
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, buyer_usdc, 10, referrer, None, None),
+        PresaleError::ReferrerNotVouched,
+    );
+    let backend = funded_keypair(&mut svm);
+    admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::SetPaymentAuthority {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+        },
+        presale::instruction::SetPaymentAuthority { payment_authority: backend.pubkey() },
+    )
+    .unwrap();
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, buyer_usdc, 10, referrer, None, Some(&buyer)),
+        PresaleError::ReferrerNotVouched,
+    );
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, buyer_usdc, 10, referrer, None, Some(&backend)),
+        PresaleError::InvalidReferrer,
+    );
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, buyer_usdc, 0, Pubkey::default(), None, None),
+        PresaleError::InvalidPrice,
+    );
+    // Whole stable coins past u64 in base units
+    assert_error(
+        buy_with_stable(&mut svm, &sale, &stable, &buyer, buyer_usdc, u64::MAX, no_referrer, None, None),
+        PresaleError::MathOverflow,
+    );
+}
+
+/// Sends `buy_tokens` for 1 SOL with `payment_type`, co-signed by `payment_authority` if given.
+fn buy_with_payment_type(
+    svm: &mut Svm,
+    sale: &TestSale,
+    buyer: &Keypair,
+    payment_type: u8,
+    payment_authority: Option<&Keypair>,
+) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::BuyTokens {
+                buyer: buyer.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                merchant_wallet: sale.merchant_wallet,
+                platform_fee_wallet: sale.platform_fee_recipient,
+                platform_fee_token_account: sale.platform_fee_token_account,
+                referrer_wallet: None,
+                payment_authority: payment_authority.map(|signer| signer.pubkey()),
+                buyer_ledger: buyer_ledger_pda(&sale.presale, &buyer.pubkey()),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            presale::instruction::BuyTokens {
+                payment_type,
+                lamports_sent: 1_000_000_000,
+                referrer: Pubkey::default(),
+                is_influencer: false,
+            },
+        )],
+        buyer,
+        payment_authority.as_slice(),
+    )
+}
+
+#[test]
+fn web2_purchases_need_the_payment_authority() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let buyer = funded_keypair(&mut svm);
+    let backend = funded_keypair(&mut svm);
+    set_stage(&mut svm, &sale).unwrap();
+
+    assert_error(buy_with_payment_type(&mut svm, &sale, &buyer, 2, None), PresaleError::InvalidPaymentType);
+    // No payment authority is set yet, so Web2 purchases are disabled
+    assert_error(
+        buy_with_payment_type(&mut svm, &sale, &buyer, 1, Some(&backend)),
+        PresaleError::PaymentNotAuthorized,
+    );
+
+    admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::SetPaymentAuthority {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+        },
+        presale::instruction::SetPaymentAuthority { payment_authority: backend.pubkey() },
+    )
+    .unwrap();
+    assert_error(buy_with_payment_type(&mut svm, &sale, &buyer, 1, None), PresaleError::PaymentNotAuthorized);
+    assert_error(
+        buy_with_payment_type(&mut svm, &sale, &buyer, 1, Some(&buyer)),
+        PresaleError::PaymentNotAuthorized,
+    );
+
+    // The backend vouches for the off-chain payment, no SOL moves
+    buy_with_payment_type(&mut svm, &sale, &buyer, 1, Some(&backend)).unwrap();
+    let ledger: presale::BuyerLedger = fetch(&svm, &buyer_ledger_pda(&sale.presale, &buyer.pubkey()));
+    assert_eq!(ledger.private_purchased, 57_142);
+    assert_eq!(svm.get_balance(&sale.merchant_wallet), None);
+
+    // The SOL price is the admin's, and cannot be cleared
+    let result = admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::SetSolPrice {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+        },
+        presale::instruction::SetSolPrice { sol_price_in_usd: 0 },
+    );
+    assert_error(result, PresaleError::InvalidPrice);
+}
+
+#[test]
+fn grants_are_validated() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let admin_token_account = token_account(&mut svm, &sale, &sale.admin, 1_000);
+    let beneficiary = Pubkey::new_unique();
+    let grant = Pubkey::find_program_address(
+        &[GRANT_SEED, sale.presale.as_ref(), beneficiary.as_ref()],
+        &presale::ID,
+    )
+    .0;
+    let grant_vault = Pubkey::find_program_address(&[GRANT_VAULT_SEED, grant.as_ref()], &presale::ID).0;
+
+    admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::CreateGrant {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+            beneficiary,
+            grant,
+            token_mint: sale.mint,
+            grant_vault,
+            admin_token_account,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        },
+        presale::instruction::CreateGrant {
+            amount: 1_000,
+            start: 0,
+            tge_percent: 0,
+            cliff_seconds: 0,
+            vesting_duration: 365 * DAY,
+            revocable: false,
+        },
+    )
+    .unwrap();
+
+    let result = admin_ix(
+        &mut svm,
+        &sale,
+        presale::accounts::RevokeGrant {
+            admin: sale.admin.pubkey(),
+            presale: sale.presale,
+            grant,
+            grant_vault,
+            admin_token_account,
+            token_program: spl_token::ID,
+        },
+        presale::instruction::RevokeGrant {},
+    );
+    assert_error(result, PresaleError::GrantNotRevocable);
+}
+
+#[test]
+fn airdrops_are_validated() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let admin_token_account = token_account(&mut svm, &sale, &sale.admin, 1_000);
+    let claimant = funded_keypair(&mut svm);
+    let claimant_token_account = token_account(&mut svm, &sale, &claimant, 0);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+    // A single leaf tree, the root is the leaf itself
+    let root = keccak::hashv(&[
+        &[0u8],
+        &0u64.to_le_bytes(),
+        claimant.pubkey().as_ref(),
+        &500u64.to_le_bytes(),
+    ])
+    .0;
+
+    let create = |svm: &mut Svm, airdrop_id: u64, expiry: i64| {
+        let distributor = Pubkey::find_program_address(
+            &[AIRDROP_SEED, sale.presale.as_ref(), &airdrop_id.to_le_bytes()],
+            &presale::ID,
+        )
+        .0;
+        let airdrop_vault =
+            Pubkey::find_program_address(&[AIRDROP_VAULT_SEED, distributor.as_ref()], &presale::ID).0;
+        let result = admin_ix(
+            svm,
+            &sale,
+            presale::accounts::CreateAirdrop {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                distributor,
+                token_mint: sale.mint,
+                airdrop_vault,
+                admin_token_account,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::CreateAirdrop {
+                airdrop_id,
+                merkle_root: root,
+                total_amount: 500,
+                num_recipients: 1,
+                expiry,
+            },
+        );
+        (result, distributor, airdrop_vault)
+    };
+    let (result, _, _) = create(&mut svm, 0, now);
+    assert_error(result, PresaleError::AirdropExpired);
+
+    let (result, distributor, airdrop_vault) = create(&mut svm, 1, now + 30 * DAY);
+    result.unwrap();
+    let claim = |svm: &mut Svm, index: u64, amount: u64| {
+        send(
+            svm,
+            &[ix(
+                presale::accounts::ClaimAirdrop {
+                    claimant: claimant.pubkey(),
+                    presale: sale.presale,
+                    distributor,
+                    airdrop_vault,
+                    claimant_token_account,
+                    token_program: spl_token::ID,
+                },
+                presale::instruction::ClaimAirdrop {
+                    index,
+                    amount,
+                    proof: vec![],
+                },
+            )],
+            &claimant,
+            &[],
+        )
+    };
+    assert_error(claim(&mut svm, 0, 5_000), PresaleError::InvalidMerkleProof);
+    assert_error(claim(&mut svm, 1, 500), PresaleError::InvalidMerkleProof);
+    claim(&mut svm, 0, 500).unwrap();
+    assert_error(claim(&mut svm, 0, 500), PresaleError::AirdropAlreadyClaimed);
+
+    let clawback = |svm: &mut Svm| {
+        admin_ix(
+            svm,
+            &sale,
+            presale::accounts::ClawbackAirdrop {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                distributor,
+                airdrop_vault,
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::ClawbackAirdrop {},
+        )
+    };
+    assert_error(clawback(&mut svm), PresaleError::AirdropNotExpired);
+    warp(&mut svm, 30 * DAY);
+    assert_error(claim(&mut svm, 0, 500), PresaleError::AirdropExpired);
+}
+
+#[test]
+fn staking_is_validated() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let stake_pool = Pubkey::find_program_address(&[STAKE_POOL_SEED, sale.presale.as_ref()], &presale::ID).0;
+    let stake_vault = Pubkey::find_program_address(&[STAKE_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+    let reward_vault = Pubkey::find_program_address(&[REWARD_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+
+    let init = |svm: &mut Svm, lockup_options: Vec<LockupOption>| {
+        admin_ix(
+            svm,
+            &sale,
+            presale::accounts::InitStakePool {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                token_mint: sale.mint,
+                stake_pool,
+                stake_vault,
+                reward_vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::InitStakePool {
+                reward_rate: 0,
+                lockup_options,
+            },
+        )
+    };
+    assert_error(init(&mut svm, vec![]), PresaleError::InvalidLockupOption);
+    init(
+        &mut svm,
+        vec![
+            LockupOption {
+                duration: 0,
+                multiplier_bps: 10_000,
+            },
+            LockupOption {
+                duration: 30 * DAY,
+                multiplier_bps: 15_000,
+            },
+        ],
+    )
+    .unwrap();
+
+    let owner = funded_keypair(&mut svm);
+    let owner_token_account = token_account(&mut svm, &sale, &owner, 100);
+    let stake_position = Pubkey::find_program_address(
+        &[STAKE_POSITION_SEED, stake_pool.as_ref(), owner.pubkey().as_ref()],
+        &presale::ID,
+    )
+    .0;
+    let stake = |svm: &mut Svm, lockup_option: u8| {
+        send(
+            svm,
+            &[ix(
+                presale::accounts::Stake {
+                    owner: owner.pubkey(),
+                    stake_pool,
+                    stake_position,
+                    stake_vault,
+                    owner_token_account,
+                    system_program: system_program::ID,
+                    token_program: spl_token::ID,
+                },
+                presale::instruction::Stake {
+                    amount: 10 * TOKEN,
+                    lockup_option,
+                },
+            )],
+            &owner,
+            &[],
+        )
+    };
+    assert_error(stake(&mut svm, 2), PresaleError::InvalidLockupOption);
+    stake(&mut svm, 1).unwrap();
+
+    let unstake = |svm: &mut Svm, amount: u64| {
+        send(
+            svm,
+            &[ix(
+                presale::accounts::Unstake {
+                    owner: owner.pubkey(),
+                    stake_pool,
+                    stake_position,
+                    stake_vault,
+                    owner_token_account,
+                    token_program: spl_token::ID,
+                },
+                presale::instruction::Unstake { amount },
+            )],
+            &owner,
+            &[],
+        )
+    };
+    assert_error(unstake(&mut svm, 20 * TOKEN), PresaleError::InsufficientStake);
+    assert_error(unstake(&mut svm, 10 * TOKEN), PresaleError::StakeLocked);
+    let pool: StakePool = fetch(&svm, &stake_pool);
+    assert_eq!(pool.total_weighted, 15 * TOKEN);
+
+    // Once the lock has expired the position is weighted at 1x again
+    warp(&mut svm, 30 * DAY);
+    unstake(&mut svm, 4 * TOKEN).unwrap();
+    let position: StakePosition = fetch(&svm, &stake_position);
+    assert_eq!((position.multiplier_bps, position.weighted_amount), (10_000, 6 * TOKEN));
+    assert_eq!(fetch::<StakePool>(&svm, &stake_pool).total_weighted, 6 * TOKEN);
+    unstake(&mut svm, 6 * TOKEN).unwrap();
+
+    let private_buyer_bonus = |private_buyer_bonus_bps| presale::instruction::SetPrivateBuyerBonus {
+        private_buyer_bonus_bps,
+    };
+    let accounts = || presale::accounts::UpdateStakePool {
+        admin: sale.admin.pubkey(),
+        stake_pool,
+    };
+    assert_error(
+        admin_ix(&mut svm, &sale, accounts(), private_buyer_bonus(MAX_PRIVATE_BUYER_BONUS_BPS + 1)),
+        PresaleError::InvalidRate,
+    );
+    let result = admin_ix(&mut svm, &sale, accounts(), private_buyer_bonus(MAX_PRIVATE_BUYER_BONUS_BPS));
+    let logs = result.unwrap().logs;
+    let updated = events::<presale::SetPrivateBuyerBonusEvent>(&logs);
+    assert_eq!(updated[0].private_buyer_bonus_bps, MAX_PRIVATE_BUYER_BONUS_BPS);
+    // Stake pools are per sale, their events name the sale
+    assert_eq!(updated[0].presale, sale.presale);
+    assert_eq!(pool.presale, sale.presale);
+    let actions = events::<presale::AdminActionEvent>(&logs);
+    assert_eq!(actions[0].presale, sale.presale);
+    let presale::AdminAction::SetPrivateBuyerBonus { old_bps, new_bps } = actions[0].action else {
+        panic!("expected SetPrivateBuyerBonus");
+    };
+    assert_eq!((old_bps, new_bps), (0, MAX_PRIVATE_BUYER_BONUS_BPS));
+}
@@ -0,0 +1,341 @@
+//! `finalize_presale_with_pool` and the LP lock against the real Raydium CPMM program.
+//!
+//! The AMM binary is not checked in. Fetch it from mainnet before running:
+//!
+//! ```sh
+//! program/tests/fixtures/fetch.sh
+//! cargo test -p presale --test finalize_pool -- --ignored
+//! ```
+
+mod common;
+
+use anchor_lang::{system_program, AccountSerialize, Discriminator};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, spl_associated_token_account},
+    token::spl_token,
+};
+use common::*;
+use presale::cpmm::{
+    self, create_pool_fee_receiver, AmmConfig, AMM_CONFIG_SEED, AUTH_SEED, OBSERVATION_SEED, POOL_LP_MINT_SEED,
+    POOL_SEED, POOL_VAULT_SEED,
+};
+use presale::PresaleError;
+use solana_sdk::{
+    account::Account, program_option::COption, program_pack::Pack, pubkey::Pubkey, signature::Signer,
+    sysvar,
+};
+use solana_system_interface::instruction as system_instruction;
+
+/// Starts the test validator with the CPMM program loaded from `tests/fixtures/raydium_cp_swap.so`.
+fn setup_with_cpmm() -> Svm {
+    let mut program_test = program_test();
+    program_test.add_program("raydium_cp_swap", cpmm::ID, None);
+    start(program_test)
+}
+
+/// Installs a fee-free AMM config, the native mint and the pool fee receiver.
+fn install_cpmm(svm: &mut Svm) -> Pubkey {
+    let amm_config = Pubkey::find_program_address(&[AMM_CONFIG_SEED, &0u16.to_be_bytes()], &cpmm::ID).0;
+    let mut data = Vec::new();
+    AmmConfig {
+        bump: 255,
+        disable_create_pool: false,
+        index: 0,
+        trade_fee_rate: 2500,
+        protocol_fee_rate: 120_000,
+        fund_fee_rate: 40_000,
+        create_pool_fee: 0,
+        protocol_owner: Pubkey::new_unique(),
+        fund_owner: Pubkey::new_unique(),
+        padding: [0; 16],
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    assert_eq!(&data[..8], AmmConfig::DISCRIMINATOR);
+    svm.set_account(
+        amm_config,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: cpmm::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut mint_data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint_data);
+    svm.set_account(
+        spl_token::native_mint::ID,
+        Account {
+            lamports: 1_000_000_000,
+            data: mint_data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut fee_data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: spl_token::native_mint::ID,
+        owner: Pubkey::new_unique(),
+        amount: 0,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(2_039_280),
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut fee_data);
+    svm.set_account(
+        create_pool_fee_receiver::id(),
+        Account {
+            lamports: 2_039_280,
+            data: fee_data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    amm_config
+}
+
+#[test]
+#[ignore = "needs tests/fixtures/raydium_cp_swap.so, see module docs"]
+fn finalize_seeds_cpmm_pool_with_raised_sol() {
+    let mut svm = setup_with_cpmm();
+    let amm_config = install_cpmm(&mut svm);
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let admin = &sale.admin;
+
+    // 50% of the SOL raise is paired with 10,000 tokens
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetPoolConfig {
+                admin: admin.pubkey(),
+                presale: sale.presale,
+                config: config_pda(),
+            },
+            presale::instruction::SetPoolConfig {
+                enabled: true,
+                quote_mint: spl_token::native_mint::ID,
+                raise_percent: 50,
+                token_amount: 10_000,
+                lp_lock_duration: 180 * DAY,
+            },
+        )],
+        admin,
+        &[],
+    )
+    .unwrap();
+
+    set_stage(&mut svm, &sale).unwrap();
+    let buyer = funded_keypair(&mut svm);
+    buy_with_sol(&mut svm, &sale, &buyer, 2_000_000_000, Pubkey::default(), None).unwrap();
+    set_stage(&mut svm, &sale).unwrap();
+    set_stage(&mut svm, &sale).unwrap();
+
+    let presale_state: presale::Presale = fetch(&svm, &sale.presale);
+    assert_eq!(presale_state.total_raised_lamports, 2_000_000_000);
+    let quote_amount = 1_000_000_000;
+    let token_amount = 10_000 * TOKEN;
+
+    // The merchant wraps the raised SOL that is paired into the pool
+    let merchant = &sale.merchant;
+    let merchant_wsol =
+        get_associated_token_address(&merchant.pubkey(), &spl_token::native_mint::ID);
+    let admin_wsol = get_associated_token_address(&admin.pubkey(), &spl_token::native_mint::ID);
+    send(
+        &mut svm,
+        &[
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &admin.pubkey(),
+                &merchant.pubkey(),
+                &spl_token::native_mint::ID,
+                &spl_token::ID,
+            ),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &admin.pubkey(),
+                &admin.pubkey(),
+                &spl_token::native_mint::ID,
+                &spl_token::ID,
+            ),
+            system_instruction::transfer(&merchant.pubkey(), &merchant_wsol, quote_amount),
+            spl_token::instruction::sync_native(&spl_token::ID, &merchant_wsol).unwrap(),
+        ],
+        admin,
+        &[merchant],
+    )
+    .unwrap();
+    let admin_token_account = create_token_account(&mut svm, admin, &sale.mint, &admin.pubkey());
+    let liquidity_wallet = create_token_account(&mut svm, admin, &sale.mint, &admin.pubkey());
+
+    let (token_0_mint, token_1_mint) = if sale.mint < spl_token::native_mint::ID {
+        (sale.mint, spl_token::native_mint::ID)
+    } else {
+        (spl_token::native_mint::ID, sale.mint)
+    };
+    let cpmm_program = cpmm::ID;
+    let pool_authority = Pubkey::find_program_address(&[AUTH_SEED], &cpmm_program).0;
+    let pool_state = Pubkey::find_program_address(
+        &[
+            POOL_SEED,
+            amm_config.as_ref(),
+            token_0_mint.as_ref(),
+            token_1_mint.as_ref(),
+        ],
+        &cpmm_program,
+    )
+    .0;
+    let lp_mint =
+        Pubkey::find_program_address(&[POOL_LP_MINT_SEED, pool_state.as_ref()], &cpmm_program).0;
+    let vault = |mint: &Pubkey| {
+        Pubkey::find_program_address(
+            &[POOL_VAULT_SEED, pool_state.as_ref(), mint.as_ref()],
+            &cpmm_program,
+        )
+        .0
+    };
+    let observation_state =
+        Pubkey::find_program_address(&[OBSERVATION_SEED, pool_state.as_ref()], &cpmm_program).0;
+
+    let lp_lock = Pubkey::find_program_address(
+        &[presale::constant::LP_LOCK_SEED, sale.presale.as_ref()],
+        &presale::ID,
+    )
+    .0;
+    let lp_vault = get_associated_token_address(&sale.presale, &lp_mint);
+    let admin_lp_token = get_associated_token_address(&admin.pubkey(), &lp_mint);
+
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::FinalizePresaleWithPool {
+                admin: admin.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                liquidity_wallet,
+                treasury_wallet: sale.treasury_wallet,
+                token_mint: sale.mint,
+                quote_mint: spl_token::native_mint::ID,
+                admin_token_account,
+                admin_quote_account: admin_wsol,
+                merchant: merchant.pubkey(),
+                merchant_quote_account: merchant_wsol,
+                cp_swap_program: cpmm_program,
+                amm_config,
+                pool_authority,
+                pool_state,
+                lp_mint,
+                admin_lp_token,
+                token_0_vault: vault(&token_0_mint),
+                token_1_vault: vault(&token_1_mint),
+                create_pool_fee: create_pool_fee_receiver::id(),
+                observation_state,
+                lp_lock,
+                lp_vault,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            presale::instruction::FinalizePresaleWithPool { open_time: 0 },
+        )],
+        admin,
+        &[merchant],
+    )
+    .unwrap();
+
+    assert_eq!(token_balance(&svm, &vault(&sale.mint)), token_amount);
+    assert_eq!(
+        token_balance(&svm, &vault(&spl_token::native_mint::ID)),
+        quote_amount
+    );
+
+    let presale_state: presale::Presale = fetch(&svm, &sale.presale);
+    assert!(presale_state.pool_created);
+
+    // Sold tokens stay behind for claims, everything else left the presale wallet
+    assert_eq!(
+        token_balance(&svm, &sale.presale_wallet),
+        presale_state.total_sold * TOKEN
+    );
+
+    // All LP tokens sit in the escrow, none with the admin
+    let lock: presale::LpLock = fetch(&svm, &lp_lock);
+    assert!(lock.locked_amount > 0);
+    assert_eq!(token_balance(&svm, &lp_vault), lock.locked_amount);
+    assert_eq!(token_balance(&svm, &admin_lp_token), 0);
+
+    let unlock = |svm: &mut Svm| {
+        send(
+            svm,
+            &[ix(
+                presale::accounts::UnlockLp {
+                    admin: admin.pubkey(),
+                    presale: sale.presale,
+                    lp_lock,
+                    lp_vault,
+                    admin_lp_token,
+                    token_program: spl_token::ID,
+                },
+                presale::instruction::UnlockLp {},
+            )],
+            admin,
+            &[],
+        )
+    };
+
+    assert_error(unlock(&mut svm), PresaleError::LpStillLocked);
+    warp(&mut svm, 180 * DAY);
+    unlock(&mut svm).unwrap();
+    assert_eq!(token_balance(&svm, &admin_lp_token), lock.locked_amount);
+    assert!(fetch::<presale::LpLock>(&svm, &lp_lock).unlocked);
+    assert_error(unlock(&mut svm), PresaleError::LpAlreadyUnlocked);
+}
+
+#[test]
+fn plain_finalize_rejected_when_pool_mode_enabled() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let admin = &sale.admin;
+
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetPoolConfig {
+                admin: admin.pubkey(),
+                presale: sale.presale,
+                config: config_pda(),
+            },
+            presale::instruction::SetPoolConfig {
+                enabled: true,
+                quote_mint: spl_token::native_mint::ID,
+                raise_percent: 50,
+                token_amount: 10_000,
+                lp_lock_duration: 180 * DAY,
+            },
+        )],
+        admin,
+        &[],
+    )
+    .unwrap();
+
+    for _ in 0..3 {
+        set_stage(&mut svm, &sale).unwrap();
+    }
+
+    let liquidity_wallet = create_token_account(&mut svm, admin, &sale.mint, &admin.pubkey());
+    assert_error(finalize(&mut svm, &sale, liquidity_wallet), PresaleError::PoolConfigMismatch);
+}
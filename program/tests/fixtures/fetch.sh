@@ -0,0 +1,5 @@
+#!/usr/bin/env sh
+# Dumps the Raydium CPMM program from mainnet into tests/fixtures for the ignored pool tests.
+set -e
+cd "$(dirname "$0")"
+solana program dump -u "${SOLANA_URL:-m}" CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C raydium_cp_swap.so
@@ -0,0 +1,200 @@
+//! A sale from initialize to claims: deposits, both stages, SOL and USDC buys with referrals, finalize.
+
+mod common;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use common::*;
+use presale::{BuyerLedger, FinalizePresaleEvent, Presale, PresaleError, ReferralCommissionEvent};
+
+#[test]
+fn full_sale_lifecycle() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let stable = accept_stable(&mut svm, &sale);
+
+    // Deposits are recorded before the sale opens
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!((state.sale_stage, state.total_deposited, state.total_reward_deposited), (0, 1_000_000, 100_000));
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), 1_000_000 * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.referral_wallet), 100_000 * TOKEN);
+
+    let sol_buyer = funded_keypair(&mut svm);
+    let usdc_buyer = funded_keypair(&mut svm);
+    let usdc_account = stable_account(&mut svm, &stable, &usdc_buyer.pubkey(), 1_000);
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &sol_buyer, 1_000_000_000, Pubkey::default(), None),
+        PresaleError::PresaleNotActive,
+    );
+
+    // Private sale at $0.0035, the referrer earns 5% in sale tokens
+    set_stage(&mut svm, &sale).unwrap();
+    let token_referrer = Pubkey::new_unique();
+    buy_with_sol(&mut svm, &sale, &sol_buyer, 2_000_000_000, token_referrer, None).unwrap();
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!(state.total_sold, 114_285);
+    assert_eq!(state.referral_charged, 5_714);
+    assert_eq!(svm.get_balance(&sale.merchant_wallet), Some(2_000_000_000));
+
+    // Switch referrals to a commission in the payment currency and buy with USDC
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetReferralRewardMode {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetReferralRewardMode { referral_reward_mode: 1 },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    // The backend co-signs purchases whose referrer it vouches for
+    let backend = funded_keypair(&mut svm);
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetPaymentAuthority {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetPaymentAuthority { payment_authority: backend.pubkey() },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let usdc_referrer = funded_keypair(&mut svm);
+    let referrer_account = stable_account(&mut svm, &stable, &usdc_referrer.pubkey(), 0);
+    let result = buy_with_stable(
+        &mut svm,
+        &sale,
+        &stable,
+        &usdc_buyer,
+        usdc_account,
+        100,
+        usdc_referrer.pubkey(),
+        Some(referrer_account),
+        Some(&backend),
+    )
+    .unwrap();
+
+    let commissions = events::<ReferralCommissionEvent>(&result.logs);
+    assert_eq!(commissions.len(), 1);
+    assert_eq!((commissions[0].commission_amount, commissions[0].payment_currency), (5 * USDC, 1));
+    assert_eq!(token_balance(&svm, &referrer_account), 5 * USDC);
+    assert_eq!(token_balance(&svm, &stable.merchant_account), 95 * USDC);
+    assert_eq!(token_balance(&svm, &usdc_account), 900 * USDC);
+
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!(state.total_sold, 114_285 + 28_571);
+    assert_eq!(state.referral_charged, 5_714); // Commissions do not touch the reward allocation
+    assert_eq!(state.total_raised_stable, 95 * USDC);
+    assert_eq!(state.stable_raised(stable.mint), 95 * USDC); // Pool seeding only pairs the quote mint's share
+
+    // Public sale at $0.007
+    set_stage(&mut svm, &sale).unwrap();
+    buy_with_sol(&mut svm, &sale, &sol_buyer, 1_000_000_000, Pubkey::default(), None).unwrap();
+    let ledger: BuyerLedger = fetch(&svm, &buyer_ledger_pda(&sale.presale, &sol_buyer.pubkey()));
+    assert_eq!((ledger.private_purchased, ledger.public_purchased), (114_285, 28_571));
+
+    // Nothing can be bought or claimed once the sale has ended and before TGE
+    set_stage(&mut svm, &sale).unwrap();
+    assert_error(
+        buy_with_sol(&mut svm, &sale, &sol_buyer, 1_000_000_000, Pubkey::default(), None),
+        PresaleError::PresaleNotActive,
+    );
+    let sol_buyer_tokens = create_token_account(&mut svm, &sol_buyer, &sale.mint, &sol_buyer.pubkey());
+    assert_error(
+        claim_tokens(&mut svm, &sale, &sol_buyer, sol_buyer_tokens),
+        PresaleError::LiquidityPoolNotCreated,
+    );
+
+    // Unsold sale and reward tokens all go to the liquidity wallet
+    let liquidity_wallet = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    let result = finalize(&mut svm, &sale, liquidity_wallet).unwrap();
+    let finalized = events::<FinalizePresaleEvent>(&result.logs);
+    assert_eq!(finalized.len(), 1);
+    assert_eq!((finalized[0].presale, finalized[0].sale_stage), (sale.presale, 3));
+    let sold = 114_285 + 28_571 + 28_571;
+    assert_eq!(finalized[0].unsold_presale_tokens, (1_000_000 - sold) * TOKEN);
+    assert_eq!(finalized[0].unsold_reward_tokens, (100_000 - 5_714) * TOKEN);
+    assert_eq!(finalized[0].burned_tokens, 0);
+    assert_eq!(token_balance(&svm, &liquidity_wallet), (1_000_000 - sold + 100_000 - 5_714) * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), sold * TOKEN);
+    assert!(fetch::<Presale>(&svm, &sale.presale).pool_created);
+
+    // Default vesting releases everything at TGE
+    claim_tokens(&mut svm, &sale, &sol_buyer, sol_buyer_tokens).unwrap();
+    let usdc_buyer_tokens = create_token_account(&mut svm, &usdc_buyer, &sale.mint, &usdc_buyer.pubkey());
+    claim_tokens(&mut svm, &sale, &usdc_buyer, usdc_buyer_tokens).unwrap();
+    assert_eq!(token_balance(&svm, &sol_buyer_tokens), (114_285 + 28_571) * TOKEN);
+    assert_eq!(token_balance(&svm, &usdc_buyer_tokens), 28_571 * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), 0);
+    assert_eq!(fetch::<Presale>(&svm, &sale.presale).reserved_tokens, 0);
+
+    assert_error(
+        claim_tokens(&mut svm, &sale, &sol_buyer, sol_buyer_tokens),
+        PresaleError::NothingToClaim,
+    );
+}
+
+#[test]
+fn referral_bonus_is_claimed_with_the_purchase() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetBuyerBonusRate {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetBuyerBonusRate { buyer_bonus_rate: 10 },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    let backend = funded_keypair(&mut svm);
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetPaymentAuthority {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetPaymentAuthority { payment_authority: backend.pubkey() },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+
+    // 57_142 tokens bought through a vouched referrer earn a 10% bonus and a 5% referral reward
+    set_stage(&mut svm, &sale).unwrap();
+    let buyer = funded_keypair(&mut svm);
+    buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, Pubkey::new_unique(), Some(&backend)).unwrap();
+    let ledger: BuyerLedger = fetch(&svm, &buyer_ledger_pda(&sale.presale, &buyer.pubkey()));
+    assert_eq!(ledger.private_purchased, 57_142 + 5_714);
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!(state.referral_charged, 5_714 + 2_857);
+    assert_eq!(state.reserved_tokens, (57_142 + 5_714) * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), (1_000_000 + 5_714) * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.referral_wallet), (100_000 - 5_714) * TOKEN);
+
+    // The referral reward stays behind, only the unallocated rewards are disposed of
+    set_stage(&mut svm, &sale).unwrap();
+    set_stage(&mut svm, &sale).unwrap();
+    let liquidity_wallet = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    let result = finalize(&mut svm, &sale, liquidity_wallet).unwrap();
+    let finalized = &events::<FinalizePresaleEvent>(&result.logs)[0];
+    assert_eq!(finalized.unsold_reward_tokens, (100_000 - 5_714 - 2_857) * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.referral_wallet), 2_857 * TOKEN);
+
+    let buyer_tokens = create_token_account(&mut svm, &buyer, &sale.mint, &buyer.pubkey());
+    claim_tokens(&mut svm, &sale, &buyer, buyer_tokens).unwrap();
+    assert_eq!(token_balance(&svm, &buyer_tokens), (57_142 + 5_714) * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), 0);
+}
@@ -0,0 +1,238 @@
+//! Presale account layout: fresh sales use the versioned layout, v1 accounts migrate in place.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, system_program, AnchorSerialize, Discriminator, Space};
+use anchor_spl::token::spl_token;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use common::*;
+use presale::constant::{PRESALE_V1_SEED, PRESALE_V1_SPACE, PRESALE_VERSION};
+use presale::{DeliverV1AllocationEvent, Presale, PresaleError, PresaleV1, ProgramConfig};
+
+/// Writes a v1 sale the way the old `Initialize` created it: an admin-keyed `Presale`, sized by
+/// hand, whose wallets were stocked by direct transfers. Purchases were never recorded per buyer.
+fn install_v1_sale(svm: &mut Svm, total_sold: u64) -> TestSale {
+    next_sale_id(svm); // Program config the migration takes the sale id from
+
+    let admin = funded_keypair(svm);
+    let (presale, bump) =
+        Pubkey::find_program_address(&[PRESALE_V1_SEED, admin.pubkey().as_ref()], &presale::ID);
+    let mint_authority = funded_keypair(svm);
+    let mint = create_mint(svm, &mint_authority, 9);
+    let presale_wallet = create_token_account(svm, &admin, &mint, &presale);
+    let referral_wallet = create_token_account(svm, &admin, &mint, &presale);
+    mint_to(svm, &mint, &mint_authority, &presale_wallet, 10_000 * TOKEN);
+    mint_to(svm, &mint, &mint_authority, &referral_wallet, 1_000 * TOKEN);
+    let treasury_wallet = create_token_account(svm, &admin, &mint, &admin.pubkey());
+    let merchant = Keypair::new();
+
+    let v1 = PresaleV1 {
+        admin: admin.pubkey(),
+        private_price: 3500,
+        public_price: 7000,
+        current_price: 7000,
+        sale_stage: 2,
+        total_sold,
+        referral_charged: 56,
+        presale_wallet,
+        referral_wallet,
+        merchant_wallet: merchant.pubkey(),
+        regular_referral_rate: 5,
+        influencer_referral_rate: 10,
+        bump,
+        ..Default::default()
+    };
+
+    let mut data = Presale::DISCRIMINATOR[..].to_vec();
+    v1.serialize(&mut data).unwrap();
+    data.resize(PRESALE_V1_SPACE, 0);
+
+    svm.set_account(
+        presale,
+        Account {
+            lamports: svm.minimum_balance_for_rent_exemption(PRESALE_V1_SPACE),
+            data,
+            owner: presale::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    TestSale {
+        platform_fee_recipient: admin.pubkey(),
+        admin,
+        mint,
+        mint_authority,
+        presale,
+        presale_wallet,
+        referral_wallet,
+        merchant_wallet: merchant.pubkey(),
+        merchant,
+        treasury_wallet,
+        platform_fee_token_account: Pubkey::default(),
+    }
+}
+
+fn migrate(svm: &mut Svm, sale: &TestSale, admin: &Keypair) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::MigratePresale {
+                admin: admin.pubkey(),
+                presale: sale.presale,
+                config: config_pda(),
+                presale_wallet: sale.presale_wallet,
+                referral_wallet: sale.referral_wallet,
+                treasury_wallet: sale.treasury_wallet,
+                system_program: system_program::ID,
+            },
+            presale::instruction::MigratePresale {},
+        )],
+        admin,
+        &[],
+    )
+}
+
+fn deliver(
+    svm: &mut Svm,
+    sale: &TestSale,
+    admin: &Keypair,
+    recipient: Pubkey,
+    amount: u64,
+) -> TransactionResult {
+    send(
+        svm,
+        &[ix(
+            presale::accounts::DeliverV1Allocation {
+                admin: admin.pubkey(),
+                presale: sale.presale,
+                presale_wallet: sale.presale_wallet,
+                recipient_token_account: recipient,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::DeliverV1Allocation { amount },
+        )],
+        admin,
+        &[],
+    )
+}
+
+#[test]
+fn new_sales_use_the_current_layout() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000, 100);
+
+    let account = svm.get_account(&sale.presale).unwrap();
+    assert_eq!(account.data.len(), 8 + Presale::INIT_SPACE);
+    assert_ne!(account.data.len(), PRESALE_V1_SPACE);
+
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!(state.version, PRESALE_VERSION);
+    assert_eq!(state.admin, sale.admin.pubkey());
+    assert_eq!(state.total_deposited, 1_000);
+    assert_eq!(state.purchase_count, 0);
+    assert_eq!((state.sol_price_in_usd, state.payment_authority), (200, Pubkey::default()));
+    assert!(!state.v1_seeds);
+    assert_eq!(state.v1_undelivered, 0);
+    assert_eq!(state.reserved, [0; 7]);
+}
+
+#[test]
+fn migrate_presale_upgrades_v1_accounts_in_place() {
+    let mut svm = setup();
+    let sale = install_v1_sale(&mut svm, 1_234);
+    let sale_id = next_sale_id(&mut svm);
+
+    migrate(&mut svm, &sale, &sale.admin).unwrap();
+
+    let account = svm.get_account(&sale.presale).unwrap();
+    assert_eq!(account.data.len(), 8 + Presale::INIT_SPACE);
+    assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(account.data.len()));
+
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!(state.version, PRESALE_VERSION);
+    assert!(state.v1_seeds);
+    assert_eq!(state.sale_id, sale_id);
+    assert_eq!(fetch::<ProgramConfig>(&svm, &config_pda()).next_sale_id, sale_id + 1);
+    assert_eq!(state.admin, sale.admin.pubkey());
+    assert_eq!(state.sale_stage, 2);
+    assert_eq!(state.current_price, 7000);
+    assert_eq!(state.total_sold, 1_234);
+    assert_eq!(state.referral_charged, 56);
+    assert_eq!((state.regular_referral_rate, state.influencer_referral_rate), (5, 10));
+    assert_eq!(state.merchant_wallet, sale.merchant_wallet);
+    assert_eq!(state.purchase_count, 0);
+
+    // Supply v1 never recorded comes from the wallets, sold tokens stay reserved for their buyers
+    assert_eq!(state.token_mint, sale.mint);
+    assert_eq!(state.treasury_wallet, sale.treasury_wallet);
+    assert_eq!((state.total_deposited, state.total_reward_deposited), (10_000, 1_000));
+    assert_eq!(state.reserved_tokens, 1_234 * TOKEN);
+    assert_eq!(state.v1_undelivered, 1_234);
+    assert_eq!(state.unsold_disposition.liquidity_percent, 100);
+    assert_eq!(state.platform_fee_recipient, sale.admin.pubkey());
+    assert_eq!((state.platform_raise_fee_bps, state.platform_token_fee_bps), (0, 0));
+
+    // The sale keeps its v1 address, which still signs for its wallets
+    let liquidity_wallet = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    set_stage(&mut svm, &sale).unwrap();
+    finalize(&mut svm, &sale, liquidity_wallet).unwrap();
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), 1_234 * TOKEN);
+    assert_eq!(token_balance(&svm, &sale.referral_wallet), 56 * TOKEN);
+    assert_eq!(
+        token_balance(&svm, &liquidity_wallet),
+        (10_000 - 1_234 + 1_000 - 56) * TOKEN
+    );
+
+    // v1 buyers have no ledger, the admin delivers their purchases from the v1 records
+    let buyer = funded_keypair(&mut svm);
+    let buyer_tokens = create_token_account(&mut svm, &buyer, &sale.mint, &buyer.pubkey());
+    assert_error(deliver(&mut svm, &sale, &buyer, buyer_tokens, 1_000), ErrorCode::ConstraintHasOne);
+    assert_error(
+        deliver(&mut svm, &sale, &sale.admin, buyer_tokens, 1_235),
+        PresaleError::InvalidAmount,
+    );
+    let delivered = deliver(&mut svm, &sale, &sale.admin, buyer_tokens, 1_000).unwrap();
+    let event = &events::<DeliverV1AllocationEvent>(&delivered.logs)[0];
+    assert_eq!((event.recipient, event.amount, event.v1_undelivered), (buyer.pubkey(), 1_000, 234));
+    assert_eq!(token_balance(&svm, &buyer_tokens), 1_000 * TOKEN);
+    let state: Presale = fetch(&svm, &sale.presale);
+    assert_eq!((state.v1_undelivered, state.reserved_tokens), (234, 234 * TOKEN));
+    assert_eq!(token_balance(&svm, &sale.presale_wallet), 234 * TOKEN);
+
+    // Already in the current layout
+    assert_error(migrate(&mut svm, &sale, &sale.admin), PresaleError::AlreadyMigrated);
+}
+
+#[test]
+fn migrate_presale_rejects_sold_counts_past_u64_base_units() {
+    let mut svm = setup();
+    let sale = install_v1_sale(&mut svm, u64::MAX / TOKEN + 1);
+
+    assert_error(migrate(&mut svm, &sale, &sale.admin), PresaleError::MathOverflow);
+}
+
+#[test]
+fn migrate_presale_rejects_other_signers() {
+    let mut svm = setup();
+    let sale = install_v1_sale(&mut svm, 1_234);
+
+    let intruder = funded_keypair(&mut svm);
+    assert_error(migrate(&mut svm, &sale, &intruder), PresaleError::Unauthorized);
+
+    // Another sale's wallets cannot stand in for the v1 account's own
+    let other = init_sale(&mut svm, 1_000, 100);
+    let swapped = TestSale {
+        referral_wallet: other.referral_wallet,
+        ..install_v1_sale(&mut svm, 1_234)
+    };
+    assert_error(migrate(&mut svm, &swapped, &swapped.admin), PresaleError::InvalidTokenAccount);
+
+    let account = svm.get_account(&sale.presale).unwrap();
+    assert_eq!(account.data.len(), PRESALE_V1_SPACE);
+}
@@ -0,0 +1,49 @@
+//! Buy events carry the sale context and the counters after the purchase.
+
+mod common;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use common::*;
+use presale::{BuyTokensEvent, Presale, ReferralRewardEvent};
+
+#[test]
+fn purchases_are_numbered_and_report_post_state() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    set_stage(&mut svm, &sale).unwrap();
+
+    let buyer = funded_keypair(&mut svm);
+    let referrer = Pubkey::new_unique();
+
+    let first = buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, Pubkey::default(), None).unwrap();
+    let second = buy_with_sol(&mut svm, &sale, &buyer, 2_000_000_000, referrer, None).unwrap();
+    let state: Presale = fetch(&svm, &sale.presale);
+
+    let first = events::<BuyTokensEvent>(&first.logs);
+    let second_buys = events::<BuyTokensEvent>(&second.logs);
+    assert_eq!((first.len(), second_buys.len()), (1, 1));
+    let (first, second_buy) = (&first[0], &second_buys[0]);
+
+    // 1 SOL at $200 and $0.0035 per token
+    assert_eq!(first.tokens_purchased, 57_142);
+    assert_eq!(first.presale, sale.presale);
+    assert_eq!((first.purchase_seq, second_buy.purchase_seq), (1, 2));
+    assert_eq!((first.sale_stage, first.price), (1, 3500));
+    assert_eq!(first.referrer, Pubkey::default());
+    assert_eq!(first.total_sold, 57_142);
+    assert_eq!(first.total_raised_lamports, 1_000_000_000);
+
+    assert_eq!(second_buy.referrer, referrer);
+    assert_eq!(second_buy.total_sold, state.total_sold);
+    assert_eq!(second_buy.referral_charged, state.referral_charged);
+    assert_eq!(second_buy.total_raised_lamports, state.total_raised_lamports);
+    assert!(second_buy.slot >= first.slot);
+    assert_eq!(state.purchase_count, 2);
+
+    // Side events point back at the purchase that produced them
+    let rewards = events::<ReferralRewardEvent>(&second.logs);
+    assert_eq!(rewards.len(), 1);
+    assert_eq!((rewards[0].presale, rewards[0].purchase_seq), (sale.presale, 2));
+    assert_eq!(rewards[0].referred_buyer, buyer.pubkey());
+}
@@ -0,0 +1,59 @@
+//! `quote_purchase` matches what the buy instructions then do, and reports the limit hit.
+
+mod common;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use common::*;
+use presale::{BuyTokensEvent, PresaleError};
+
+#[test]
+fn quote_matches_the_purchase_it_describes() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+
+    // Not started yet
+    let quote = quote_sol(&mut svm, &sale, 1_000_000_000, Pubkey::default());
+    assert_eq!(quote.limit, Some(PresaleError::PresaleNotActive.into()));
+
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::SetBuyerBonusRate {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+            },
+            presale::instruction::SetBuyerBonusRate { buyer_bonus_rate: 10 },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+    set_stage(&mut svm, &sale).unwrap();
+    let referrer = Pubkey::new_unique();
+    let quote = quote_sol(&mut svm, &sale, 1_000_000_000, referrer);
+    assert_eq!(quote.limit, None);
+    // Nobody vouches for the referrer, so the buyer earns no bonus
+    assert_eq!(quote.bonus_tokens, 0);
+    assert_eq!((quote.sale_stage, quote.price), (1, 3500));
+    assert_eq!(quote.tokens_out, 57_142);
+    assert_eq!(quote.referral_reward, 57_142 * 5 / 100);
+    assert_eq!(quote.remaining_tokens, 1_000_000);
+    // $200 for 57_142 tokens, the rounded-off fraction shows in the effective price
+    assert_eq!(quote.effective_price, 200_000_000u64.div_ceil(57_142));
+    assert!(quote.effective_price > quote.price);
+
+    let buyer = funded_keypair(&mut svm);
+    let bought = buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, referrer, None).unwrap();
+    let event = &events::<BuyTokensEvent>(&bought.logs)[0];
+    assert_eq!((event.tokens_purchased, event.bonus_tokens), (quote.tokens_out, quote.bonus_tokens));
+
+    // More than the remaining supply
+    let quote = quote_sol(&mut svm, &sale, 100 * 1_000_000_000, Pubkey::default());
+    assert_eq!(quote.limit, Some(PresaleError::InsufficientTokens.into()));
+    assert_eq!(quote.remaining_tokens, 1_000_000 - 57_142);
+
+    // Under $1
+    let quote = quote_sol(&mut svm, &sale, 1_000_000, Pubkey::default());
+    assert_eq!(quote.limit, Some(PresaleError::InvalidPrice.into()));
+}
@@ -0,0 +1,46 @@
+//! `get_sale_status` reports the recorded stage next to the one the schedule implies.
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+
+use common::*;
+
+#[test]
+fn status_follows_the_schedule_and_the_counters() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+
+    let status = sale_status(&mut svm, &sale);
+    assert_eq!((status.sale_stage, status.effective_stage), (0, 0));
+    assert_eq!((status.stage_end, status.seconds_remaining), (0, 0));
+    assert!(!status.purchases_open);
+
+    set_stage(&mut svm, &sale).unwrap();
+    let status = sale_status(&mut svm, &sale);
+    assert_eq!((status.sale_stage, status.effective_stage), (1, 1));
+    assert_eq!(status.stage_end, status.presale_start + 15 * DAY);
+    assert_eq!(status.seconds_remaining, 15 * DAY);
+    assert!(status.purchases_open);
+
+    let buyer = funded_keypair(&mut svm);
+    buy_with_sol(&mut svm, &sale, &buyer, 1_000_000_000, Pubkey::new_unique(), None).unwrap();
+
+    // The private sale ran out but nobody called `set_stage`
+    warp(&mut svm, 16 * DAY);
+    let status = sale_status(&mut svm, &sale);
+    assert_eq!((status.sale_stage, status.effective_stage), (1, 2));
+    assert_eq!(status.seconds_remaining, 59 * DAY);
+    assert_eq!(status.current_price, 3500);
+    assert_eq!((status.total_sold, status.remaining_tokens), (57_142, 1_000_000 - 57_142));
+    assert_eq!(status.reserved_tokens, 57_142 * TOKEN);
+    assert_eq!(status.reward_liability, 57_142 * 5 / 100);
+    assert_eq!(status.remaining_rewards, 100_000 - 57_142 * 5 / 100);
+    assert_eq!((status.total_raised_lamports, status.purchase_count), (1_000_000_000, 1));
+
+    warp(&mut svm, 60 * DAY);
+    let status = sale_status(&mut svm, &sale);
+    assert_eq!((status.sale_stage, status.effective_stage), (1, 3));
+    assert_eq!(status.seconds_remaining, 0);
+    assert!(!status.pool_created);
+}
@@ -0,0 +1,140 @@
+//! Staking rewards follow the lockup multiplier only while the lock lasts.
+
+mod common;
+
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use common::*;
+use presale::constant::*;
+use presale::LockupOption;
+
+const REWARD_RATE: u64 = 1_000; // Reward base units per second
+
+#[test]
+fn expired_locks_earn_at_1x_from_the_expiry() {
+    let mut svm = setup();
+    let sale = init_sale(&mut svm, 1_000_000, 100_000);
+    let stake_pool = Pubkey::find_program_address(&[STAKE_POOL_SEED, sale.presale.as_ref()], &presale::ID).0;
+    let stake_vault = Pubkey::find_program_address(&[STAKE_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+    let reward_vault = Pubkey::find_program_address(&[REWARD_VAULT_SEED, stake_pool.as_ref()], &presale::ID).0;
+
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::InitStakePool {
+                admin: sale.admin.pubkey(),
+                presale: sale.presale,
+                token_mint: sale.mint,
+                stake_pool,
+                stake_vault,
+                reward_vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::InitStakePool {
+                reward_rate: REWARD_RATE,
+                lockup_options: vec![
+                    LockupOption {
+                        duration: 0,
+                        multiplier_bps: 10_000,
+                    },
+                    LockupOption {
+                        duration: 30 * DAY,
+                        multiplier_bps: 20_000,
+                    },
+                ],
+            },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+
+    let admin_token_account = create_token_account(&mut svm, &sale.admin, &sale.mint, &sale.admin.pubkey());
+    mint_to(&mut svm, &sale.mint, &sale.mint_authority, &admin_token_account, 100 * TOKEN);
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::FundRewards {
+                admin: sale.admin.pubkey(),
+                stake_pool,
+                reward_vault,
+                admin_token_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::FundRewards { amount: 100 * TOKEN },
+        )],
+        &sale.admin,
+        &[],
+    )
+    .unwrap();
+
+    // Equal stakes, one locked at 2x for 30 days and one unlocked at 1x
+    let stake = |svm: &mut Svm, owner: &Keypair, lockup_option: u8| {
+        let owner_token_account = create_token_account(svm, owner, &sale.mint, &owner.pubkey());
+        mint_to(svm, &sale.mint, &sale.mint_authority, &owner_token_account, 10 * TOKEN);
+        let stake_position = Pubkey::find_program_address(
+            &[STAKE_POSITION_SEED, stake_pool.as_ref(), owner.pubkey().as_ref()],
+            &presale::ID,
+        )
+        .0;
+        send(
+            svm,
+            &[ix(
+                presale::accounts::Stake {
+                    owner: owner.pubkey(),
+                    stake_pool,
+                    stake_position,
+                    stake_vault,
+                    owner_token_account,
+                    system_program: system_program::ID,
+                    token_program: spl_token::ID,
+                },
+                presale::instruction::Stake {
+                    amount: 10 * TOKEN,
+                    lockup_option,
+                },
+            )],
+            owner,
+            &[],
+        )
+        .unwrap();
+        (stake_position, owner_token_account)
+    };
+    let locked = funded_keypair(&mut svm);
+    let unlocked = funded_keypair(&mut svm);
+    let (locked_position, locked_account) = stake(&mut svm, &locked, 1);
+    stake(&mut svm, &unlocked, 0);
+
+    // Nobody touches the locked position when its lock ends
+    warp(&mut svm, 60 * DAY);
+    send(
+        &mut svm,
+        &[ix(
+            presale::accounts::ClaimRewards {
+                owner: locked.pubkey(),
+                stake_pool,
+                stake_position: locked_position,
+                reward_vault,
+                owner_token_account: locked_account,
+                token_program: spl_token::ID,
+            },
+            presale::instruction::ClaimRewards {},
+        )],
+        &locked,
+        &[],
+    )
+    .unwrap();
+
+    // 2/3 of the first 30 days at 2x, then 1/3 of the next 30 days at 1x, against the pool's
+    // weight, which still counts the boost until the locked position settles
+    let emitted_per_half = REWARD_RATE * 30 * DAY as u64;
+    let expected = emitted_per_half * 2 / 3 + emitted_per_half / 3;
+    let earned = token_balance(&svm, &locked_account);
+    assert!(earned.abs_diff(expected) <= 1, "earned {earned}, expected {expected}");
+}